@@ -0,0 +1,29 @@
+-- Users watching a post get notified about new replies, resolutions and approvals
+CREATE TABLE IF NOT EXISTS post_watchers (
+    postid INTEGER NOT NULL REFERENCES posts(postid) ON DELETE CASCADE,
+    userid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (postid, userid)
+);
+
+CREATE INDEX IF NOT EXISTS post_watchers_userid_idx ON post_watchers (userid);
+
+-- Per-user notifications, shown in the header until they are read
+CREATE TABLE IF NOT EXISTS notifications (
+    notificationid SERIAL PRIMARY KEY,
+    userid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    classid INTEGER NOT NULL,
+    postid INTEGER REFERENCES posts(postid) ON DELETE CASCADE,
+    message TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    read BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS notifications_userid_read_idx ON notifications (userid, read);
+
+-- Everyone who already wrote or replied to a post starts out watching it
+INSERT INTO post_watchers (postid, userid)
+    SELECT postid, authorid FROM posts
+    UNION
+    SELECT postid, authorid FROM replies
+ON CONFLICT DO NOTHING;
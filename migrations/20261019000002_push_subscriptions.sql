@@ -0,0 +1,11 @@
+-- Web Push subscriptions, one per user per browser/device
+CREATE TABLE IF NOT EXISTS push_subscriptions (
+    subscriptionid SERIAL PRIMARY KEY,
+    userid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    endpoint TEXT NOT NULL UNIQUE,
+    p256dh TEXT NOT NULL,
+    auth TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS push_subscriptions_userid_idx ON push_subscriptions (userid);
@@ -0,0 +1,7 @@
+-- When each user last opened each post, used for unread badges and "new replies" markers
+CREATE TABLE IF NOT EXISTS post_views (
+    postid INTEGER NOT NULL REFERENCES posts(postid) ON DELETE CASCADE,
+    userid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    last_viewed TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (postid, userid)
+);
@@ -0,0 +1,3 @@
+-- Instructors and TAs can pin posts to the top of a class, optionally until a given time
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS pinned BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS pinned_until TIMESTAMP;
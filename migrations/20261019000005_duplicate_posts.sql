@@ -0,0 +1,6 @@
+-- Trigram similarity is used alongside full-text search to suggest existing posts while composing
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+CREATE INDEX IF NOT EXISTS posts_title_trgm_idx ON posts USING GIN (title gin_trgm_ops);
+
+-- Instructors can mark a post as a duplicate of an earlier one in the same class
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS duplicate_of INTEGER REFERENCES posts(postid) ON DELETE SET NULL;
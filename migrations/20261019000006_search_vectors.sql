@@ -0,0 +1,18 @@
+-- Full-text search vectors kept up to date by Postgres. Rich text is stored as HTML, so tags are
+-- stripped before indexing, and post titles rank above their bodies.
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', COALESCE(title, '')), 'A') ||
+    setweight(to_tsvector('english', regexp_replace(COALESCE(contents, ''), '<[^>]*>', ' ', 'g')), 'B')
+) STORED;
+CREATE INDEX IF NOT EXISTS posts_search_vector_idx ON posts USING GIN (search_vector);
+
+ALTER TABLE replies ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', regexp_replace(COALESCE(contents, ''), '<[^>]*>', ' ', 'g')), 'B')
+) STORED;
+CREATE INDEX IF NOT EXISTS replies_search_vector_idx ON replies USING GIN (search_vector);
+
+ALTER TABLE announcements ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', COALESCE(title, '')), 'A') ||
+    setweight(to_tsvector('english', regexp_replace(COALESCE(contents, ''), '<[^>]*>', ' ', 'g')), 'B')
+) STORED;
+CREATE INDEX IF NOT EXISTS announcements_search_vector_idx ON announcements USING GIN (search_vector);
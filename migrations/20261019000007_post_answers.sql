@@ -0,0 +1,12 @@
+-- Each post has one instructor answer and one student answer, edited collaboratively. Every save
+-- is kept as a revision; the newest revision of each kind is the current answer.
+CREATE TABLE IF NOT EXISTS answer_revisions (
+    revisionid SERIAL PRIMARY KEY,
+    postid INTEGER NOT NULL REFERENCES posts(postid) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('instructor', 'student')),
+    contents TEXT NOT NULL,
+    editorid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS answer_revisions_postid_kind_idx ON answer_revisions (postid, kind, time DESC);
@@ -0,0 +1,25 @@
+-- Every edit of a post or reply is kept as a revision. The version from before the first edit is
+-- recorded along with the edit, so an unedited post or reply has no revisions.
+CREATE TABLE IF NOT EXISTS post_revisions (
+    revisionid SERIAL PRIMARY KEY,
+    postid INTEGER NOT NULL REFERENCES posts(postid) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    contents TEXT NOT NULL,
+    editorid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS post_revisions_postid_idx ON post_revisions (postid, time DESC);
+
+CREATE TABLE IF NOT EXISTS reply_revisions (
+    revisionid SERIAL PRIMARY KEY,
+    replyid INTEGER NOT NULL REFERENCES replies(replyid) ON DELETE CASCADE,
+    contents TEXT NOT NULL,
+    editorid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    time TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS reply_revisions_replyid_idx ON reply_revisions (replyid, time DESC);
+
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS edited_at TIMESTAMP;
+ALTER TABLE replies ADD COLUMN IF NOT EXISTS edited_at TIMESTAMP;
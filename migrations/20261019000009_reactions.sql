@@ -0,0 +1,16 @@
+-- Lightweight reactions on posts and replies. Each user can leave each reaction once per post or reply.
+CREATE TABLE IF NOT EXISTS post_reactions (
+    postid INTEGER NOT NULL REFERENCES posts(postid) ON DELETE CASCADE,
+    userid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reaction TEXT NOT NULL CHECK (reaction IN ('thanks', 'same_question', 'confused')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (postid, userid, reaction)
+);
+
+CREATE TABLE IF NOT EXISTS reply_reactions (
+    replyid INTEGER NOT NULL REFERENCES replies(replyid) ON DELETE CASCADE,
+    userid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reaction TEXT NOT NULL CHECK (reaction IN ('thanks', 'same_question', 'confused')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (replyid, userid, reaction)
+);
@@ -0,0 +1,18 @@
+-- Files uploaded to a class. An upload starts out unattached, visible only to its uploader, and is
+-- attached to a post or reply when that is submitted.
+CREATE TABLE IF NOT EXISTS attachments (
+    attachmentid SERIAL PRIMARY KEY,
+    storage_key TEXT NOT NULL UNIQUE DEFAULT gen_random_uuid()::TEXT,
+    thumbnail_key TEXT,
+    filename TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    uploaderid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    classid INTEGER NOT NULL,
+    postid INTEGER REFERENCES posts(postid) ON DELETE CASCADE,
+    replyid INTEGER REFERENCES replies(replyid) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS attachments_postid_idx ON attachments (postid);
+CREATE INDEX IF NOT EXISTS attachments_replyid_idx ON attachments (replyid);
@@ -0,0 +1,3 @@
+-- Images pasted or dropped into the editor are shown in the content itself rather than in the
+-- list of attached files
+ALTER TABLE attachments ADD COLUMN IF NOT EXISTS inline BOOLEAN NOT NULL DEFAULT false;
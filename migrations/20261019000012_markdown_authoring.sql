@@ -0,0 +1,4 @@
+-- Users can write posts, replies and announcements in Markdown instead of the rich text editor
+ALTER TABLE users ADD COLUMN IF NOT EXISTS markdownauthoring BOOLEAN NOT NULL DEFAULT false;
+-- Announcements are plain text unless they were written in Markdown
+ALTER TABLE announcements ADD COLUMN IF NOT EXISTS markdown BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,14 @@
+-- What a user is writing is autosaved as a draft so it survives a closed tab or an expired session.
+-- A user has at most one draft per place they can write: a new post or announcement in a class
+-- (targetid is the class), or a reply to or edit of a post (targetid is the post).
+CREATE TABLE IF NOT EXISTS drafts (
+    draftid SERIAL PRIMARY KEY,
+    userid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    targetid INTEGER NOT NULL,
+    classid INTEGER NOT NULL,
+    title TEXT NOT NULL DEFAULT '',
+    contents TEXT NOT NULL DEFAULT '',
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (userid, kind, targetid)
+);
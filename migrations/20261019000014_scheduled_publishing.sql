@@ -0,0 +1,20 @@
+-- Announcements can be written ahead of time and published later. A scheduled announcement keeps
+-- its publish time here until the scheduler publishes it, and is hidden from the class until then.
+ALTER TABLE announcements ADD COLUMN IF NOT EXISTS publish_at TIMESTAMP;
+CREATE INDEX IF NOT EXISTS announcements_publish_at_idx ON announcements (publish_at)
+    WHERE publish_at IS NOT NULL;
+
+-- Instructors' posts waiting to be published. They are only turned into posts when they are due, so
+-- that nothing reading posts has to know about them. Contents are kept as they were written.
+CREATE TABLE IF NOT EXISTS scheduled_posts (
+    scheduledpostid SERIAL PRIMARY KEY,
+    classid INTEGER NOT NULL,
+    authorid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    title TEXT NOT NULL,
+    contents TEXT NOT NULL,
+    anonymous BOOLEAN NOT NULL DEFAULT false,
+    private BOOLEAN NOT NULL DEFAULT false,
+    attachment_ids INTEGER[] NOT NULL DEFAULT '{}',
+    publish_at TIMESTAMP NOT NULL
+);
+CREATE INDEX IF NOT EXISTS scheduled_posts_publish_at_idx ON scheduled_posts (publish_at);
@@ -0,0 +1,13 @@
+-- Announcements can be edited, pinned to the front of the list, and given a time after which they
+-- are collapsed
+ALTER TABLE announcements ADD COLUMN IF NOT EXISTS edited_at TIMESTAMP;
+ALTER TABLE announcements ADD COLUMN IF NOT EXISTS pinned BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE announcements ADD COLUMN IF NOT EXISTS expires_at TIMESTAMP;
+
+-- Who has opened each announcement, so instructors can see how much of the class has read it
+CREATE TABLE IF NOT EXISTS announcement_reads (
+    announcementid INTEGER NOT NULL REFERENCES announcements(announcementid) ON DELETE CASCADE,
+    userid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    read_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (announcementid, userid)
+);
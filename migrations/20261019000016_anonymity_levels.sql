@@ -0,0 +1,11 @@
+-- Anonymous posts and replies are hidden from classmates but still identifiable to instructors,
+-- unless the author chose to hide from instructors too, which a class has to allow
+ALTER TABLE classes ADD COLUMN IF NOT EXISTS allow_full_anonymity BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS fully_anonymous BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE replies ADD COLUMN IF NOT EXISTS fully_anonymous BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE scheduled_posts ADD COLUMN IF NOT EXISTS fully_anonymous BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Everything posted anonymously before now was hidden from instructors, so it stays that way
+UPDATE posts SET fully_anonymous = TRUE WHERE anonymous;
+UPDATE replies SET fully_anonymous = TRUE WHERE anonymous;
+UPDATE scheduled_posts SET fully_anonymous = TRUE WHERE anonymous;
@@ -0,0 +1,9 @@
+-- Classmates a private post is shared with, who can see it along with its author and instructors
+CREATE TABLE IF NOT EXISTS post_audience (
+    postid INTEGER NOT NULL REFERENCES posts(postid) ON DELETE CASCADE,
+    userid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (postid, userid)
+);
+CREATE INDEX IF NOT EXISTS post_audience_userid ON post_audience (userid);
+
+ALTER TABLE scheduled_posts ADD COLUMN IF NOT EXISTS audience INTEGER[] NOT NULL DEFAULT '{}';
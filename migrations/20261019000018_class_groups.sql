@@ -0,0 +1,40 @@
+-- Groups within a class, such as lab sections or project teams
+CREATE TABLE IF NOT EXISTS class_groups (
+    groupid SERIAL PRIMARY KEY,
+    classid INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (classid, name)
+);
+
+CREATE TABLE IF NOT EXISTS class_group_members (
+    groupid INTEGER NOT NULL REFERENCES class_groups(groupid) ON DELETE CASCADE,
+    userid INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    PRIMARY KEY (groupid, userid)
+);
+CREATE INDEX IF NOT EXISTS class_group_members_userid ON class_group_members (userid);
+
+-- Groups a private post is shared with, alongside the classmates in post_audience
+CREATE TABLE IF NOT EXISTS post_audience_groups (
+    postid INTEGER NOT NULL REFERENCES posts(postid) ON DELETE CASCADE,
+    groupid INTEGER NOT NULL REFERENCES class_groups(groupid) ON DELETE CASCADE,
+    PRIMARY KEY (postid, groupid)
+);
+ALTER TABLE scheduled_posts ADD COLUMN IF NOT EXISTS audience_groups INTEGER[] NOT NULL DEFAULT '{}';
+
+-- Announcements and polls meant for some groups only. They stay restricted to staff and their
+-- author if every group they were for is deleted.
+ALTER TABLE announcements ADD COLUMN IF NOT EXISTS targeted BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE polls ADD COLUMN IF NOT EXISTS targeted BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS announcement_groups (
+    announcementid INTEGER NOT NULL REFERENCES announcements(announcementid) ON DELETE CASCADE,
+    groupid INTEGER NOT NULL REFERENCES class_groups(groupid) ON DELETE CASCADE,
+    PRIMARY KEY (announcementid, groupid)
+);
+
+CREATE TABLE IF NOT EXISTS poll_groups (
+    pollid INTEGER NOT NULL REFERENCES polls(id) ON DELETE CASCADE,
+    groupid INTEGER NOT NULL REFERENCES class_groups(groupid) ON DELETE CASCADE,
+    PRIMARY KEY (pollid, groupid)
+);
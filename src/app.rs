@@ -14,7 +14,7 @@ use crate::{
         live_poll::LivePoll,
         login_page::LoginPage,
         register_page::RegisterPage,
-        user_profile::user_profile_page::UserProfile,
+        user_profile::{member_profile::MemberProfile, user_profile_page::UserProfile},
         user_settings::user_settings_page::UserSettings,
         view_class_posts::{
             announcement_details::AnnouncementDetails, class::ClassPage,
@@ -61,6 +61,9 @@ pub fn App() -> impl IntoView {
       // tiptap.js files
       <Script type_="module" src="/js/tiptap-bundle.min.js" />
       <Script type_="module" src="/js/tiptap.js" />
+      <Script type_="module" src="/js/editor.js" />
+      // Web Push subscription helper
+      <Script type_="module" src="/js/push.js" />
 
       // injects a stylesheet into the document <head>
       // id=leptos means cargo-leptos will hot-reload this stylesheet
@@ -95,6 +98,7 @@ pub fn App() -> impl IntoView {
                 <Route path="/class/:class_id/poll" view=LivePoll />
                 <Route path="/settings" view=UserSettings />
                 <Route path="/profile" view=UserProfile />
+                <Route path="/profile/:user_id" view=MemberProfile />
               </Route>
               // Accessible when logged out
               <Route path="" view=UnauthenticatedRoutes>
@@ -229,7 +233,8 @@ fn start_timer() {
     #[cfg(target_arch = "wasm32")]
     {
         use crate::pages::global_components::push_notifications::{
-            configure_notifications, send_newest_announcement_notification,
+            configure_notifications, register_push_subscription,
+            send_newest_announcement_notification,
         };
         use gloo_timers::callback::Interval;
         use web_sys::window;
@@ -237,16 +242,24 @@ fn start_timer() {
         // Timer signals
         let (tick_counter, set_tick_counter) = create_signal(0);
 
+        // Set once the server is delivering notifications through Web Push
+        let (push_registered, set_push_registered) = create_signal(false);
+
         // Timer interval
         let interval = Interval::new(3000, move || {
             set_tick_counter.update(|v| *v += 1);
         });
 
         // Configure push notifications once
-        spawn_local(async {
+        spawn_local(async move {
             if let Some(window) = window() {
                 if let Err(err) = configure_notifications(&window).await {
                     logging::log!("Notification handling error: {:?}", err);
+                    return;
+                }
+                match register_push_subscription(&window).await {
+                    Ok(()) => set_push_registered(true),
+                    Err(err) => logging::log!("Falling back to in-tab notifications: {:?}", err),
                 }
             }
         });
@@ -255,6 +268,11 @@ fn start_timer() {
         create_effect(move |_| {
             let _tick_value = tick_counter.get();
 
+            // Announcements already arrive through Web Push
+            if push_registered.get_untracked() {
+                return;
+            }
+
             // Call the send_newest_announcement_notification function
             spawn_local(async move {
                 if let Err(err) = send_newest_announcement_notification().await {
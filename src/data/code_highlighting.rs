@@ -0,0 +1,95 @@
+/**
+ * Server-side syntax highlighting of the code blocks in rich text content. Code is turned into
+ * spans with `hl-` prefixed classes when content is saved, so browsers only need the stylesheet.
+ */
+use once_cell::sync::Lazy;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+/// Prefix of the classes on highlighted spans, which the sanitizer lets through
+pub const HIGHLIGHT_CLASS_PREFIX: &str = "hl-";
+
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+
+const CODE_BLOCK_START: &str = "<pre><code";
+const CODE_BLOCK_END: &str = "</code></pre>";
+
+/// The text of sanitized HTML, dropping any tags and decoding the entities the sanitizer writes
+fn html_to_code(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&amp;", "&")
+}
+
+/// Highlighted HTML for some code, or None if the language isn't known
+fn highlight(code: &str, language: &str) -> Option<String> {
+    let syntax = SYNTAX_SET.find_syntax_by_token(language)?;
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(
+        syntax,
+        &SYNTAX_SET,
+        ClassStyle::SpacedPrefixed {
+            prefix: HIGHLIGHT_CLASS_PREFIX,
+        },
+    );
+    for line in LinesWithEndings::from(code) {
+        generator
+            .parse_html_for_line_which_includes_newline(line)
+            .ok()?;
+    }
+    Some(generator.finalize())
+}
+
+/// The language in the attributes of a `<code>` tag, from the `language-` class the editor sets
+fn language_of(code_attributes: &str) -> Option<&str> {
+    let class = code_attributes.split("class=\"").nth(1)?.split('"').next()?;
+    class
+        .split_whitespace()
+        .find_map(|class| class.strip_prefix("language-"))
+}
+
+/// Highlight every code block with a known language in sanitized rich text HTML
+pub fn highlight_code_blocks(html: &str) -> String {
+    let mut highlighted = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find(CODE_BLOCK_START) {
+        let after_start = &rest[start + CODE_BLOCK_START.len()..];
+        let (Some(tag_end), Some(block_end)) =
+            (after_start.find('>'), after_start.find(CODE_BLOCK_END))
+        else {
+            break;
+        };
+        if tag_end > block_end {
+            break;
+        }
+        let attributes = &after_start[..tag_end];
+        let code_html = &after_start[tag_end + 1..block_end];
+
+        highlighted.push_str(&rest[..start + CODE_BLOCK_START.len()]);
+        highlighted.push_str(attributes);
+        highlighted.push('>');
+        match language_of(attributes)
+            .and_then(|language| highlight(&html_to_code(code_html), language))
+        {
+            Some(code) => highlighted.push_str(&code),
+            None => highlighted.push_str(code_html),
+        }
+        highlighted.push_str(CODE_BLOCK_END);
+        rest = &after_start[block_end + CODE_BLOCK_END.len()..];
+    }
+    highlighted.push_str(rest);
+    highlighted
+}
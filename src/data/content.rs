@@ -0,0 +1,295 @@
+/**
+ * Cleaning of the rich text HTML that posts, replies and answers are stored as. Only the markup the
+ * editor produces is kept, and images are only kept when they are served from our own uploads route.
+ * Users who write in Markdown have it converted into the same HTML here.
+ */
+use crate::data::code_highlighting::{highlight_code_blocks, HIGHLIGHT_CLASS_PREFIX};
+use crate::data::database::user_functions::uses_markdown;
+use crate::data::database::mention_functions::link_mentions;
+use crate::data::math::{render_math, DISPLAY_MATH_CLASS, INLINE_MATH_CLASS};
+use crate::data::mentions::{MENTION_CLASS, POST_REFERENCE_CLASS};
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag, TagEnd};
+use sqlx::PgPool;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+const UPLOADS_PREFIX: &str = "/uploads/";
+
+/// The MathML elements that rendered math is made of
+const MATH_TAGS: [&str; 24] = [
+    "math", "semantics", "annotation", "mrow", "mi", "mn", "mo", "ms", "mtext", "mspace", "msup",
+    "msub", "msubsup", "mfrac", "msqrt", "mroot", "mover", "munder", "munderover", "mtable", "mtr",
+    "mtd", "mstyle", "mpadded",
+];
+
+/// Presentation attributes of MathML elements
+const MATH_ATTRIBUTES: [&str; 14] = [
+    "display", "mathvariant", "stretchy", "fence", "separator", "lspace", "rspace", "accent",
+    "accentunder", "linethickness", "columnalign", "width", "movablelimits", "scriptlevel",
+];
+
+/// The attachment id of an image source on our uploads route, which is either
+/// `/uploads/{id}` or `/uploads/{id}/thumbnail`
+fn upload_id_of(src: &str) -> Option<i32> {
+    let rest = src.strip_prefix(UPLOADS_PREFIX)?;
+    let id = rest.strip_suffix("/thumbnail").unwrap_or(rest);
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    id.parse().ok()
+}
+
+/// Only text alignment, which the editor sets on headings and paragraphs, is kept from inline styles
+fn is_allowed_style(style: &str) -> bool {
+    matches!(
+        style.trim().trim_end_matches(';').trim(),
+        "text-align: left" | "text-align: center" | "text-align: right" | "text-align: justify"
+    )
+}
+
+/// Code blocks may name their language, highlighted code is made of spans with prefixed classes, and
+/// mentions and post references are links with their own class
+fn is_allowed_class(element: &str, class: &str) -> bool {
+    match element {
+        "a" => class == MENTION_CLASS || class == POST_REFERENCE_CLASS,
+        "code" => class.split_whitespace().all(|class| {
+            class.strip_prefix("language-").is_some_and(|language| {
+                !language.is_empty()
+                    && language
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b == b'+' || b == b'#')
+            })
+        }),
+        "span" => {
+            class == INLINE_MATH_CLASS
+                || class == DISPLAY_MATH_CLASS
+                || class
+                    .split_whitespace()
+                    .all(|class| class.starts_with(HIGHLIGHT_CLASS_PREFIX))
+        }
+        _ => false,
+    }
+}
+
+/// Remove anything from rich text HTML that the editor couldn't have produced
+pub fn sanitize_html(html: &str) -> String {
+    clean_html(html, true)
+}
+
+/// Remove anything from rich text HTML written by a user that the editor couldn't have produced.
+/// Mention and post reference links are only made by `link_mentions`, so the editor never sends them.
+fn sanitize_authored_html(html: &str) -> String {
+    clean_html(html, false)
+}
+
+fn clean_html(html: &str, keep_mention_links: bool) -> String {
+    let mut tags = HashSet::from([
+        "p", "br", "h1", "h2", "h3", "h4", "h5", "h6", "strong", "b", "em", "i", "s", "mark",
+        "blockquote", "ul", "ol", "li", "code", "pre", "hr", "img", "a", "span",
+    ]);
+    tags.extend(MATH_TAGS);
+    let mut tag_attributes: HashMap<&str, HashSet<&str>> = MATH_TAGS
+        .into_iter()
+        .map(|tag| (tag, HashSet::from(MATH_ATTRIBUTES)))
+        .collect();
+    tag_attributes.extend([
+        ("img", HashSet::from(["src", "alt", "title"])),
+        if keep_mention_links {
+            ("a", HashSet::from(["href", "class", "data-id"]))
+        } else {
+            ("a", HashSet::from(["href"]))
+        },
+        ("code", HashSet::from(["class"])),
+        ("span", HashSet::from(["class", "data-latex"])),
+    ]);
+
+    ammonia::Builder::default()
+        .tags(tags)
+        .generic_attributes(HashSet::from(["style"]))
+        .tag_attributes(tag_attributes)
+        .url_relative(ammonia::UrlRelative::PassThrough)
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("img", "src") => upload_id_of(value).map(|_| Cow::Borrowed(value)),
+            (_, "style") => is_allowed_style(value).then_some(Cow::Borrowed(value)),
+            (_, "class") => is_allowed_class(element, value).then_some(Cow::Borrowed(value)),
+            _ => Some(Cow::Borrowed(value)),
+        })
+        .clean(html)
+        .to_string()
+}
+
+/// Rich text HTML written by a user as it is stored: sanitized, then with its code blocks
+/// highlighted and math rendered. The result is sanitized again since the LaTeX is unescaped for
+/// rendering.
+pub fn prepare_contents(html: &str) -> String {
+    sanitize_authored_html(&render_math(&highlight_code_blocks(&sanitize_authored_html(html))))
+}
+
+/// HTML for Markdown, in the shape the rich text editor writes. Math is handed back as `$…$` and
+/// `$$…$$` text so that `render_math` renders it like math typed into the editor.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_MATH;
+    let mut in_code_block = false;
+    let events = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::Start(Tag::CodeBlock(_)) => {
+            in_code_block = true;
+            event
+        }
+        Event::End(TagEnd::CodeBlock) => {
+            in_code_block = false;
+            event
+        }
+        Event::InlineMath(latex) => Event::Text(CowStr::from(format!("${}$", latex))),
+        Event::DisplayMath(latex) => Event::Text(CowStr::from(format!("$${}$$", latex))),
+        // Dollar signs outside of math were escaped or didn't pair up, so they stay dollar signs
+        Event::Text(text) if !in_code_block && text.contains('$') => {
+            Event::Text(CowStr::from(text.replace('$', "\\$")))
+        }
+        event => event,
+    });
+    let mut html = String::with_capacity(markdown.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut html, events);
+    html
+}
+
+/// Contents written by a user in a class as they are stored, converting them from Markdown first if
+/// that is how the user writes, and linking their mentions and post references. Also returns the
+/// ids of the class members it mentions.
+pub async fn prepare_authored_contents(
+    pool: &PgPool,
+    author_id: i32,
+    class_id: i32,
+    contents: &str,
+) -> Result<(String, Vec<i32>), sqlx::Error> {
+    let contents = if uses_markdown(pool, author_id).await? {
+        prepare_contents(&markdown_to_html(contents))
+    } else {
+        prepare_contents(contents)
+    };
+    link_mentions(pool, author_id, class_id, &contents).await
+}
+
+/// The ids of the uploaded images shown in sanitized rich text HTML
+pub fn inline_upload_ids(html: &str) -> Vec<i32> {
+    let mut ids: Vec<i32> = html
+        .split("src=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .filter_map(upload_id_of)
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_are_only_kept_from_uploads() {
+        for src in ["/uploads/12", "/uploads/12/thumbnail"] {
+            let html = format!("<img src=\"{}\">", src);
+            assert_eq!(sanitize_html(&html), html);
+        }
+        for src in [
+            "https://example.com/cat.png",
+            "//example.com/uploads/12",
+            "/uploads/",
+            "/uploads/abc",
+            "/uploads/12/../../admin",
+            "/uploads/12?x=1",
+            "javascript:alert(1)",
+            "data:image/png;base64,AAAA",
+        ] {
+            let html = format!("<img src=\"{}\">", src);
+            assert_eq!(sanitize_html(&html), "<img>", "{}", src);
+        }
+        assert_eq!(inline_upload_ids("<img src=\"/uploads/3\"><img src=\"/uploads/3/thumbnail\">"), vec![3]);
+    }
+
+    #[test]
+    fn only_text_alignment_styles_are_kept() {
+        let html = "<p style=\"text-align: center;\">x</p>";
+        assert_eq!(sanitize_html(html), html);
+        for style in [
+            "color: red",
+            "text-align: center; position: fixed",
+            "background: url(javascript:alert(1))",
+        ] {
+            let html = format!("<p style=\"{}\">x</p>", style);
+            assert_eq!(sanitize_html(&html), "<p>x</p>", "{}", style);
+        }
+    }
+
+    #[test]
+    fn script_links_are_dropped() {
+        for href in [
+            "javascript:alert(1)",
+            "JaVaScRiPt:alert(1)",
+            " javascript:alert(1)",
+            "java\tscript:alert(1)",
+            "vbscript:msgbox(1)",
+            "data:text/html,<script>alert(1)</script>",
+        ] {
+            let html = format!("<a href=\"{}\">x</a>", href);
+            let sanitized = sanitize_html(&html).to_lowercase();
+            assert!(!sanitized.contains("href"), "{}", sanitized);
+            assert!(sanitized.contains(">x</a>"));
+        }
+    }
+
+    #[test]
+    fn scripts_and_event_handlers_are_dropped() {
+        assert_eq!(sanitize_html("<script>alert(1)</script><p>ok</p>"), "<p>ok</p>");
+        assert_eq!(sanitize_html("<p onclick=\"alert(1)\">ok</p>"), "<p>ok</p>");
+        assert_eq!(sanitize_html("<iframe src=\"/\"></iframe><p>ok</p>"), "<p>ok</p>");
+    }
+
+    #[test]
+    fn classes_are_only_kept_where_the_editor_sets_them() {
+        let html = "<pre><code class=\"language-rust\">x</code></pre>";
+        assert_eq!(sanitize_html(html), html);
+        assert_eq!(
+            sanitize_html("<pre><code class=\"language-rust hidden\">x</code></pre>"),
+            "<pre><code>x</code></pre>"
+        );
+        let html = "<span class=\"hl-source hl-rust\">x</span>";
+        assert_eq!(sanitize_html(html), html);
+        assert_eq!(sanitize_html("<span class=\"hidden\">x</span>"), "<span>x</span>");
+        assert_eq!(sanitize_html("<p class=\"hidden\">x</p>"), "<p>x</p>");
+    }
+
+    #[test]
+    fn mathml_only_keeps_presentation_attributes() {
+        let sanitized = sanitize_html(
+            "<span class=\"math-inline\" data-latex=\"x\"><math display=\"inline\"><mi mathvariant=\"bold\" onclick=\"alert(1)\" href=\"javascript:alert(1)\" xlink:href=\"javascript:alert(1)\">x</mi></math></span>",
+        );
+        assert!(sanitized.contains("<math display=\"inline\">"), "{}", sanitized);
+        assert!(sanitized.contains("mathvariant=\"bold\""), "{}", sanitized);
+        assert!(!sanitized.contains("onclick"), "{}", sanitized);
+        assert!(!sanitized.contains("href"), "{}", sanitized);
+        let sanitized = sanitize_html("<math><mtext><img src=\"x\" onerror=\"alert(1)\"></mtext></math>");
+        assert!(!sanitized.contains("onerror"), "{}", sanitized);
+    }
+
+    #[test]
+    fn mention_links_are_only_kept_when_stored() {
+        let html = "<p><a class=\"mention\" href=\"/profile/3\" data-id=\"3\">@Ann</a></p>";
+        let sanitized = sanitize_html(html);
+        assert!(sanitized.contains("class=\"mention\""), "{}", sanitized);
+        assert!(sanitized.contains("data-id=\"3\""), "{}", sanitized);
+        // Only link_mentions may make them when contents are saved
+        let prepared = prepare_contents(html);
+        assert!(!prepared.contains("mention"), "{}", prepared);
+        assert!(!prepared.contains("data-id"), "{}", prepared);
+    }
+
+    #[test]
+    fn rendered_math_is_sanitized() {
+        let prepared = prepare_contents("<p>$\\text{&lt;img src=x onerror=alert(1)&gt;}$</p>");
+        assert!(!prepared.contains("onerror=\""), "{}", prepared);
+        let prepared = prepare_contents("<p>$$\\href{javascript:alert(1)}{x}$$</p>");
+        assert!(!prepared.contains("href=\"javascript"), "{}", prepared);
+    }
+}
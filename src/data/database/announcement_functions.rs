@@ -16,6 +16,145 @@ pub struct AnnouncementInfo {
     pub contents: String,
     pub class_id: i32,
     pub author_id: i32,
+    /// Whether the contents are Markdown rather than plain text
+    pub markdown: bool,
+    /// The contents as HTML with their math rendered, filled in after the announcement is loaded
+    #[cfg_attr(feature = "ssr", sqlx(default))]
+    pub rendered_contents: String,
+    /// When a scheduled announcement will be published. None once it has been.
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    pub edited_at: Option<chrono::NaiveDateTime>,
+    /// Pinned announcements are listed before the others
+    pub pinned: bool,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    /// Past `expires_at`, so shown collapsed
+    pub expired: bool,
+    /// Students the announcement is for who have opened it
+    pub read_count: i64,
+    pub student_count: i64,
+    /// Whether the announcement is only for some groups
+    pub targeted: bool,
+    /// The groups a targeted announcement is for, which may have all been deleted since
+    pub group_names: Vec<String>,
+}
+
+#[cfg(feature = "ssr")]
+impl AnnouncementInfo {
+    /// The announcement with its contents rendered for display
+    fn rendered(mut self) -> AnnouncementInfo {
+        use crate::data::content::{markdown_to_html, prepare_contents};
+        use crate::data::math::render_plain_text_math;
+
+        self.rendered_contents = if self.markdown {
+            prepare_contents(&markdown_to_html(&self.contents))
+        } else {
+            render_plain_text_math(&self.contents)
+        };
+        self
+    }
+}
+
+/// The columns of an `AnnouncementInfo`, selected from `announcements`
+#[cfg(feature = "ssr")]
+const ANNOUNCEMENT_COLUMNS: &str = "announcementid as announcement_id, time, title, contents,
+    classid as class_id, authorid as author_id, markdown, publish_at, edited_at, pinned, expires_at,
+    (expires_at IS NOT NULL AND expires_at <= CURRENT_TIMESTAMP) as expired,
+    (SELECT COUNT(*) FROM announcement_reads
+        JOIN enrolled ON enrolled.studentid = announcement_reads.userid AND enrolled.courseid = announcements.classid
+        WHERE announcement_reads.announcementid = announcements.announcementid) as read_count,
+    (SELECT COUNT(*) FROM enrolled WHERE enrolled.courseid = announcements.classid
+        AND (NOT announcements.targeted
+            OR enrolled.studentid IN (SELECT class_group_members.userid FROM announcement_groups
+                JOIN class_group_members ON class_group_members.groupid = announcement_groups.groupid
+                WHERE announcement_groups.announcementid = announcements.announcementid))) as student_count,
+    targeted,
+    ARRAY(SELECT class_groups.name FROM announcement_groups
+        JOIN class_groups ON class_groups.groupid = announcement_groups.groupid
+        WHERE announcement_groups.announcementid = announcements.announcementid
+        ORDER BY class_groups.name) as group_names";
+
+/// SQL condition that an announcement is visible to the user given by `viewer`: it is for the
+/// whole class, or the user wrote it, helps run the class or is in one of the groups it is for
+#[cfg(feature = "ssr")]
+pub fn announcement_visible_to(viewer: &str) -> String {
+    format!(
+        "(NOT announcements.targeted
+        OR announcements.authorid = {viewer}
+        OR EXISTS (SELECT 1 FROM instructing WHERE instructing.courseid = announcements.classid AND instructing.professorid = {viewer})
+        OR EXISTS (SELECT 1 FROM ta WHERE ta.classid = announcements.classid AND ta.id = {viewer})
+        OR EXISTS (SELECT 1 FROM announcement_groups
+            JOIN class_group_members ON class_group_members.groupid = announcement_groups.groupid
+            WHERE announcement_groups.announcementid = announcements.announcementid AND class_group_members.userid = {viewer}))"
+    )
+}
+
+/// Loads an announcement with its contents rendered for display
+#[cfg(feature = "ssr")]
+async fn fetch_announcement(
+    pool: &sqlx::PgPool,
+    announcement_id: i32,
+) -> Result<AnnouncementInfo, sqlx::Error> {
+    let announcement: AnnouncementInfo = sqlx::query_as(&format!(
+        "SELECT {ANNOUNCEMENT_COLUMNS} FROM announcements WHERE announcementid = $1"
+    ))
+    .bind(announcement_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(announcement.rendered())
+}
+
+/// The class an announcement was made in and its author, checking that a user may change it: its
+/// author or an instructor of the class
+#[cfg(feature = "ssr")]
+async fn check_can_change_announcement(
+    pool: &sqlx::PgPool,
+    announcement_id: i32,
+    user_id: i32,
+) -> Result<i32, ServerFnError> {
+    use crate::data::database::class_functions::check_user_is_instructor;
+    use leptos::server_fn::error::NoCustomError;
+
+    let (class_id, author_id): (i32, i32) =
+        sqlx::query_as("SELECT classid, authorid FROM announcements WHERE announcementid = $1")
+            .bind(announcement_id)
+            .fetch_one(pool)
+            .await
+            .map_err(|_| {
+                ServerFnError::<NoCustomError>::ServerError("Announcement not found".to_string())
+            })?;
+    if author_id != user_id && !check_user_is_instructor(user_id, class_id).await? {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "User does not have access to change this announcement".to_string(),
+        ));
+    }
+    Ok(class_id)
+}
+
+/// Tells the members of a class other than its author about a newly published announcement, or
+/// only the members of the groups it is for
+#[cfg(feature = "ssr")]
+pub async fn notify_announcement(
+    pool: &sqlx::PgPool,
+    announcement_id: i32,
+    class_id: i32,
+    author_id: i32,
+    title: &str,
+) -> Result<(), sqlx::Error> {
+    use crate::data::database::notification_functions::{notify_class_members, notify_group_members};
+
+    let message = format!("New announcement: {}", title);
+    let (targeted, group_ids): (bool, Vec<i32>) = sqlx::query_as(
+        "SELECT targeted, ARRAY(SELECT groupid FROM announcement_groups WHERE announcementid = $1)
+        FROM announcements WHERE announcementid = $1",
+    )
+    .bind(announcement_id)
+    .fetch_one(pool)
+    .await?;
+    if !targeted {
+        notify_class_members(pool, class_id, Some(author_id), &message).await
+    } else {
+        notify_group_members(pool, class_id, &group_ids, Some(author_id), &message).await
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -23,14 +162,25 @@ pub struct AddAnnouncementInfo {
     pub title: String,
     pub contents: String,
     pub class_id: i32,
+    /// When to publish the announcement. It is published straight away if this is None or has passed.
+    #[serde(default)]
+    pub publish_at: Option<chrono::NaiveDateTime>,
+    /// When the announcement collapses, if ever
+    #[serde(default)]
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    /// The groups the announcement is for. It is for the whole class if this is empty.
+    #[serde(default)]
+    pub group_ids: Vec<i32>,
 }
 
 /**
- * Get all announcements from the database
- *
+ * Get all of a class's announcements that a user can see, pinned ones first and then newest first
  */
 #[server(GetAnnouncementsList)]
-pub async fn get_announcement_list(class_id: i32) -> Result<Vec<AnnouncementInfo>, ServerFnError> {
+pub async fn get_announcement_list(
+    class_id: i32,
+    user_id: i32,
+) -> Result<Vec<AnnouncementInfo>, ServerFnError> {
     use leptos::{server_fn::error::NoCustomError, use_context};
     use sqlx::postgres::PgPool;
 
@@ -39,13 +189,85 @@ pub async fn get_announcement_list(class_id: i32) -> Result<Vec<AnnouncementInfo
     ))?;
 
     let announcements: Vec<AnnouncementInfo> =
-        sqlx::query_as("SELECT announcementid as announcement_id, time, title, contents, classid as class_id, authorid as author_id from announcements where classid = $1")
+        sqlx::query_as(&format!("SELECT {ANNOUNCEMENT_COLUMNS} from announcements where classid = $1 and publish_at is null
+                        and {}
+                        order by pinned desc, time desc, announcementid desc", announcement_visible_to("$2")))
             .bind(class_id)
+            .bind(user_id)
             .fetch_all(&pool)
             .await
             .expect("select should work");
 
-    Ok(announcements)
+    Ok(announcements.into_iter().map(AnnouncementInfo::rendered).collect())
+}
+
+/// Number of announcements returned by each call to `get_announcement_page`
+pub const ANNOUNCEMENTS_PAGE_SIZE: usize = 12;
+
+/**
+ * Position in the announcement list to continue from: the last announcement of the previous page
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AnnouncementCursor {
+    pub time: chrono::NaiveDateTime,
+    pub announcement_id: i32,
+}
+
+impl AnnouncementCursor {
+    /// The cursor continuing after the oldest unpinned announcement in a list
+    pub fn after(announcements: &[AnnouncementInfo]) -> Option<AnnouncementCursor> {
+        announcements
+            .iter()
+            .filter(|announcement| !announcement.pinned)
+            .map(|announcement| AnnouncementCursor {
+                time: announcement.time,
+                announcement_id: announcement.announcement_id,
+            })
+            .min_by_key(|cursor| (cursor.time, cursor.announcement_id))
+    }
+}
+
+/**
+ * Get a page of the class's announcements a user can see, newest first. The first page (no cursor)
+ * also contains every pinned announcement, before the others.
+ */
+#[server(GetAnnouncementPage)]
+pub async fn get_announcement_page(
+    class_id: i32,
+    user_id: i32,
+    cursor: Option<AnnouncementCursor>,
+) -> Result<Vec<AnnouncementInfo>, ServerFnError> {
+    use leptos::{server_fn::error::NoCustomError, use_context};
+    use sqlx::postgres::PgPool;
+
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let visible = announcement_visible_to("$5");
+    let announcements: Vec<AnnouncementInfo> = sqlx::query_as(&format!(
+        "SELECT * FROM (
+            (SELECT {ANNOUNCEMENT_COLUMNS} FROM announcements
+            WHERE classid = $1 AND publish_at IS NULL AND pinned AND $2::TIMESTAMP IS NULL AND {visible})
+            UNION ALL
+            (SELECT {ANNOUNCEMENT_COLUMNS} FROM announcements
+            WHERE classid = $1 AND publish_at IS NULL AND NOT pinned AND {visible}
+            AND ($2::TIMESTAMP IS NULL OR (time, announcementid) < ($2, $3))
+            ORDER BY time DESC, announcementid DESC
+            LIMIT $4)
+        ) page
+        ORDER BY pinned DESC, time DESC, announcement_id DESC"
+    ))
+    .bind(class_id)
+    .bind(cursor.map(|cursor| cursor.time))
+    .bind(cursor.map(|cursor| cursor.announcement_id))
+    .bind(ANNOUNCEMENTS_PAGE_SIZE as i64)
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(announcements.into_iter().map(AnnouncementInfo::rendered).collect())
 }
 
 #[server(PostAnnouncement)]
@@ -53,6 +275,10 @@ pub async fn post_announcement(
     new_announcement_info: AddAnnouncementInfo,
     user_id: i32,
 ) -> Result<AnnouncementInfo, ServerFnError> {
+    use crate::data::database::class_functions::check_user_is_instructor;
+    use crate::data::database::draft_functions::{discard_draft, DraftContext};
+    use crate::data::database::group_functions::groups_in_class;
+    use crate::data::database::user_functions::uses_markdown;
     use leptos::{server_fn::error::NoCustomError, use_context};
     use sqlx::postgres::PgPool;
 
@@ -60,24 +286,67 @@ pub async fn post_announcement(
         "Unable to complete Request".to_string(),
     ))?;
 
-    let announcement: AnnouncementInfo = sqlx::query_as(
-        "INSERT INTO announcements (classid, authorid, title, contents, time)
-         VALUES ($1, $2, $3, $4, CURRENT_TIMESTAMP)
-            RETURNING
-            announcementid as announcement_id,
-            time,
-            title,
-            contents,
-            classid as class_id,
-            authorid as author_id",
+    if !check_user_is_instructor(user_id, new_announcement_info.class_id).await? {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "Only instructors can post announcements".to_string(),
+        ));
+    }
+
+    let markdown = uses_markdown(&pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    let group_ids = groups_in_class(&pool, new_announcement_info.class_id, &new_announcement_info.group_ids)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    let announcement_id: i32 = sqlx::query_scalar(
+        "INSERT INTO announcements (classid, authorid, title, contents, markdown, time, publish_at, expires_at, targeted)
+         VALUES ($1, $2, $3, $4, $5, CURRENT_TIMESTAMP,
+            CASE WHEN $6::TIMESTAMP > CURRENT_TIMESTAMP THEN $6 END, $7, $8)
+            RETURNING announcementid",
     )
     .bind(new_announcement_info.class_id)
     .bind(user_id)
     .bind(new_announcement_info.title)
     .bind(new_announcement_info.contents)
+    .bind(markdown)
+    .bind(new_announcement_info.publish_at)
+    .bind(new_announcement_info.expires_at)
+    .bind(!group_ids.is_empty())
     .fetch_one(&pool)
     .await
-    .expect("failed adding announcement");
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    sqlx::query(
+        "INSERT INTO announcement_groups (announcementid, groupid) SELECT $1, UNNEST($2::INTEGER[])",
+    )
+    .bind(announcement_id)
+    .bind(&group_ids)
+    .execute(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let announcement = fetch_announcement(&pool, announcement_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    // Scheduled announcements notify the class when the scheduler publishes them
+    if announcement.publish_at.is_none() {
+        if let Err(e) = notify_announcement(
+            &pool,
+            announcement_id,
+            announcement.class_id,
+            user_id,
+            &announcement.title,
+        )
+        .await
+        {
+            leptos::logging::error!("Failed to notify class of announcement: {:?}", e);
+        }
+    }
+    let draft = DraftContext::Announcement { class_id: announcement.class_id };
+    if let Err(e) = discard_draft(&pool, user_id, draft).await {
+        leptos::logging::error!("Failed to discard draft of announcement: {:?}", e);
+    }
 
     Ok(announcement)
 }
@@ -85,6 +354,7 @@ pub async fn post_announcement(
 #[server(GetAnnouncementByID)]
 pub async fn get_announcement_by_id(
     announcement_id: i32,
+    user_id: i32,
 ) -> Result<AnnouncementInfo, ServerFnError> {
     use leptos::{server_fn::error::NoCustomError, use_context};
     use sqlx::postgres::PgPool;
@@ -94,15 +364,31 @@ pub async fn get_announcement_by_id(
         "Unable to complete Request".to_string(),
     ))?;
 
+    // Announcements for some groups only are hidden from the rest of the class, and scheduled
+    // ones from everyone but the class's staff until they are published
+    let visible: bool = sqlx::query_scalar(&format!(
+        "SELECT {} AND (publish_at IS NULL
+            OR EXISTS (SELECT 1 FROM instructing WHERE instructing.courseid = announcements.classid AND instructing.professorid = $2)
+            OR EXISTS (SELECT 1 FROM ta WHERE ta.classid = announcements.classid AND ta.id = $2))
+        FROM announcements WHERE announcementid = $1",
+        announcement_visible_to("$2")
+    ))
+    .bind(announcement_id)
+    .bind(user_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?
+    .unwrap_or(false);
+    if !visible {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "Announcement not found".to_string(),
+        ));
+    }
+
     // Fetch the specific announcement by announcement_id
-    let announcement: AnnouncementInfo =
-        sqlx::query_as("SELECT announcementid as announcement_id, time, title, contents, classid as class_id, authorid as author_id
-                        FROM announcements
-                        WHERE announcementid = $1")
-            .bind(announcement_id)
-            .fetch_one(&pool)
-            .await
-            .expect("select should work");
+    let announcement = fetch_announcement(&pool, announcement_id)
+        .await
+        .expect("select should work");
 
     Ok(announcement)
 }
@@ -128,3 +414,97 @@ pub async fn delete_announcement(announcement_id: i32) -> Result<(), ServerFnErr
 
     Ok(())
 }
+
+/**
+ * Edit an announcement's title, contents and expiry. Only its author and the class's instructors
+ * can. The announcement is marked as edited unless it is still scheduled to be published.
+ */
+#[server(EditAnnouncement)]
+pub async fn edit_announcement(
+    announcement_id: i32,
+    user_id: i32,
+    title: String,
+    contents: String,
+    expires_at: Option<chrono::NaiveDateTime>,
+) -> Result<AnnouncementInfo, ServerFnError> {
+    use leptos::{server_fn::error::NoCustomError, use_context};
+    use sqlx::postgres::PgPool;
+
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_can_change_announcement(&pool, announcement_id, user_id).await?;
+
+    sqlx::query(
+        "UPDATE announcements SET title = $2, contents = $3, expires_at = $4,
+            edited_at = CASE WHEN publish_at IS NULL THEN CURRENT_TIMESTAMP ELSE edited_at END
+        WHERE announcementid = $1",
+    )
+    .bind(announcement_id)
+    .bind(title)
+    .bind(contents)
+    .bind(expires_at)
+    .execute(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    fetch_announcement(&pool, announcement_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))
+}
+
+/**
+ * Pin or unpin an announcement, listing it before the class's other announcements
+ */
+#[server(PinAnnouncement)]
+pub async fn pin_announcement(
+    announcement_id: i32,
+    user_id: i32,
+    pinned: bool,
+) -> Result<(), ServerFnError> {
+    use leptos::{server_fn::error::NoCustomError, use_context};
+    use sqlx::postgres::PgPool;
+
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_can_change_announcement(&pool, announcement_id, user_id).await?;
+
+    sqlx::query("UPDATE announcements SET pinned = $2 WHERE announcementid = $1")
+        .bind(announcement_id)
+        .bind(pinned)
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(())
+}
+
+/**
+ * Record that a user has opened an announcement, for its read receipts
+ */
+#[server(MarkAnnouncementRead)]
+pub async fn mark_announcement_read(announcement_id: i32, user_id: i32) -> Result<(), ServerFnError> {
+    use leptos::{server_fn::error::NoCustomError, use_context};
+    use sqlx::postgres::PgPool;
+
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    // Opening a scheduled announcement early doesn't count towards its read receipt
+    sqlx::query(
+        "INSERT INTO announcement_reads (announcementid, userid)
+        SELECT announcementid, $2 FROM announcements WHERE announcementid = $1 AND publish_at IS NULL
+        ON CONFLICT DO NOTHING",
+    )
+    .bind(announcement_id)
+    .bind(user_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(())
+}
@@ -0,0 +1,313 @@
+/**
+ * Server functions for the collaboratively edited instructor and student answers on a post.
+ * Every save is stored as a revision so the edit history can be shown.
+ */
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{logging, server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use crate::data::database::post_functions::{check_user_can_see_post, post_visible_to};
+        use crate::data::database::class_functions::{check_user_is_instructor, get_post_class_id, ClassId};
+        use crate::data::database::mention_functions::notify_mentioned_users;
+        use crate::data::database::watch_functions::{add_post_watcher, notify_post_watchers};
+        use crate::data::database::attachment_functions::attach_inline_images;
+        use crate::data::content::prepare_authored_contents;
+
+        /// A current answer along with which of the two answers it is
+        #[derive(sqlx::FromRow)]
+        struct AnswerRow {
+            kind: String,
+            contents: String,
+            editor_name: String,
+            edited_at: chrono::NaiveDateTime,
+            revision_count: i64,
+        }
+
+        impl AnswerRow {
+            fn into_answer(self) -> Answer {
+                Answer {
+                    contents: self.contents,
+                    editor_name: self.editor_name,
+                    edited_at: self.edited_at,
+                    revision_count: self.revision_count,
+                }
+            }
+        }
+
+        /// Checks that a user may edit the given answer on a post
+        async fn check_can_edit_answer(
+            pool: &PgPool,
+            post_id: i32,
+            user_id: i32,
+            kind: AnswerKind,
+        ) -> Result<(), ServerFnError> {
+            let ClassId(class_id) = sqlx::query_as("SELECT classid FROM posts WHERE postid = $1 AND removed = false")
+                .bind(post_id)
+                .fetch_one(pool)
+                .await
+                .map_err(|_| ServerFnError::<NoCustomError>::ServerError("Post not found".to_string()))?;
+
+            let allowed = match kind {
+                AnswerKind::Instructor => check_user_is_instructor(user_id, class_id).await?,
+                // Anyone who can see the post can help write the student answer
+                AnswerKind::Student => sqlx::query_scalar(&format!(
+                    "SELECT EXISTS(SELECT 1 FROM posts WHERE postid = $1
+                    AND {})",
+                    post_visible_to("$2")
+                ))
+                .bind(post_id)
+                .bind(user_id)
+                .fetch_one(pool)
+                .await
+                .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?,
+            };
+
+            if !allowed {
+                return Err(ServerFnError::<NoCustomError>::ServerError(
+                    "User does not have access to edit this answer".to_string(),
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+/**
+ * Which of a post's two answers
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum AnswerKind {
+    Instructor,
+    Student,
+}
+
+impl AnswerKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnswerKind::Instructor => "instructor",
+            AnswerKind::Student => "student",
+        }
+    }
+}
+
+/**
+ * Struct to hold the current version of an answer
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct Answer {
+    pub contents: String,
+    /// Name of whoever saved the current version
+    pub editor_name: String,
+    pub edited_at: chrono::NaiveDateTime,
+    pub revision_count: i64,
+}
+
+/**
+ * Struct to hold both answers to a post, either of which may not be written yet
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct PostAnswers {
+    pub instructor: Option<Answer>,
+    pub student: Option<Answer>,
+}
+
+/**
+ * Struct to hold one saved version of an answer
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct AnswerRevision {
+    pub revision_id: i32,
+    pub contents: String,
+    pub editor_name: String,
+    pub time: chrono::NaiveDateTime,
+}
+
+/**
+ * Get the current instructor and student answers to a post a user can see
+ */
+#[server(GetPostAnswers)]
+pub async fn get_post_answers(post_id: i32, user_id: i32) -> Result<PostAnswers, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_user_can_see_post(&pool, post_id, user_id).await?;
+
+    let rows: Vec<AnswerRow> = sqlx::query_as(
+        "SELECT DISTINCT ON (kind)
+            kind,
+            contents,
+            users.firstname || ' ' || users.lastname as editor_name,
+            time as edited_at,
+            count(*) OVER (PARTITION BY kind) as revision_count
+        FROM answer_revisions JOIN users ON answer_revisions.editorid = users.id
+        WHERE postid = $1
+        ORDER BY kind, time DESC, revisionid DESC",
+    )
+    .bind(post_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    let mut answers = PostAnswers::default();
+    for row in rows {
+        if row.kind == AnswerKind::Instructor.as_str() {
+            answers.instructor = Some(row.into_answer());
+        } else {
+            answers.student = Some(row.into_answer());
+        }
+    }
+    Ok(answers)
+}
+
+/**
+ * Save a new version of one of a post's answers. Only instructors and TAs may write the
+ * instructor answer, and saving it marks the post resolved.
+ */
+#[server(SaveAnswer)]
+pub async fn save_answer(
+    post_id: i32,
+    user_id: i32,
+    kind: AnswerKind,
+    contents: String,
+) -> Result<Answer, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    if contents.trim().is_empty() {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "Answer cannot be empty".to_string(),
+        ));
+    }
+
+    check_can_edit_answer(&pool, post_id, user_id, kind).await?;
+    let class_id = get_post_class_id(&pool, post_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let (contents, mentioned) = prepare_authored_contents(&pool, user_id, class_id, &contents)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let previous_contents: Option<String> = sqlx::query_scalar(
+        "SELECT contents FROM answer_revisions WHERE postid = $1 AND kind = $2
+        ORDER BY time DESC, revisionid DESC LIMIT 1",
+    )
+    .bind(post_id)
+    .bind(kind.as_str())
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    // The revision is saved together with its inline images and the post being resolved
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let revision: AnswerRevision = sqlx::query_as(
+        "WITH inserted AS (
+            INSERT INTO answer_revisions (postid, kind, contents, editorid) VALUES ($1, $2, $3, $4)
+            RETURNING revisionid, contents, editorid, time
+        )
+        SELECT revisionid as revision_id, contents, users.firstname || ' ' || users.lastname as editor_name, time
+        FROM inserted JOIN users ON inserted.editorid = users.id",
+    )
+    .bind(post_id)
+    .bind(kind.as_str())
+    .bind(&contents)
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    let revision_count: i64 = sqlx::query_scalar(
+        "SELECT count(*) FROM answer_revisions WHERE postid = $1 AND kind = $2",
+    )
+    .bind(post_id)
+    .bind(kind.as_str())
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    attach_inline_images(&mut *tx, &contents, user_id, Some(post_id), None)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    if kind == AnswerKind::Instructor {
+        sqlx::query("UPDATE posts SET resolved = true WHERE postid = $1")
+            .bind(post_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    // The answer is saved by now, so these only get logged if they fail
+    if let Err(e) = add_post_watcher(&pool, post_id, user_id).await {
+        logging::error!("Failed to watch answered post: {:?}", e);
+    }
+    let message = match kind {
+        AnswerKind::Instructor => "Instructor answer updated on",
+        AnswerKind::Student => "Student answer updated on",
+    };
+    if let Err(e) = notify_post_watchers(&pool, post_id, Some(user_id), message).await {
+        logging::error!("Failed to notify watchers of updated answer: {:?}", e);
+    }
+    if let Err(e) = notify_mentioned_users(
+        &pool,
+        post_id,
+        user_id,
+        &mentioned,
+        previous_contents.as_deref().unwrap_or_default(),
+    )
+    .await
+    {
+        logging::error!("Failed to notify users mentioned in answer: {:?}", e);
+    }
+
+    Ok(Answer {
+        contents: revision.contents,
+        editor_name: revision.editor_name,
+        edited_at: revision.time,
+        revision_count,
+    })
+}
+
+/**
+ * Get every saved version of one of the answers to a post a user can see, newest first
+ */
+#[server(GetAnswerHistory)]
+pub async fn get_answer_history(
+    post_id: i32,
+    user_id: i32,
+    kind: AnswerKind,
+) -> Result<Vec<AnswerRevision>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_user_can_see_post(&pool, post_id, user_id).await?;
+
+    let revisions: Vec<AnswerRevision> = sqlx::query_as(
+        "SELECT
+            revisionid as revision_id,
+            contents,
+            users.firstname || ' ' || users.lastname as editor_name,
+            time
+        FROM answer_revisions JOIN users ON answer_revisions.editorid = users.id
+        WHERE postid = $1 AND kind = $2
+        ORDER BY time DESC, revisionid DESC",
+    )
+    .bind(post_id)
+    .bind(kind.as_str())
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(revisions)
+}
@@ -0,0 +1,190 @@
+/**
+ * Server functions for files attached to posts and replies. The files themselves are uploaded and
+ * served by the handlers in `uploads`; these functions deal with the database records.
+ */
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use crate::data::content::inline_upload_ids;
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::{PgExecutor, PgPool};
+        use crate::data::database::post_functions::post_visible_to;
+
+        /// Attaches uploads to a new post or reply. Only the uploader's own uploads to the post's class
+        /// that aren't attached to anything yet are attached; any other ids are ignored.
+        pub async fn attach_uploads<'e>(
+            executor: impl PgExecutor<'e>,
+            attachment_ids: &[i32],
+            uploader_id: i32,
+            post_id: Option<i32>,
+            reply_id: Option<i32>,
+        ) -> Result<(), sqlx::Error> {
+            if attachment_ids.is_empty() {
+                return Ok(());
+            }
+            sqlx::query(
+                "UPDATE attachments SET postid = $3, replyid = $4
+                WHERE attachmentid = ANY($1) AND uploaderid = $2 AND postid IS NULL AND replyid IS NULL
+                AND classid = (SELECT classid FROM posts
+                    WHERE postid = coalesce($3, (SELECT postid FROM replies WHERE replyid = $4)))",
+            )
+            .bind(attachment_ids)
+            .bind(uploader_id)
+            .bind(post_id)
+            .bind(reply_id)
+            .execute(executor)
+            .await?;
+            Ok(())
+        }
+
+        /// Attaches the images shown in a post's or reply's sanitized contents, marking them inline so
+        /// they aren't listed again with its attached files. Like `attach_uploads`, only the
+        /// uploader's unattached images from the same class are claimed.
+        pub async fn attach_inline_images<'e>(
+            executor: impl PgExecutor<'e>,
+            contents: &str,
+            uploader_id: i32,
+            post_id: Option<i32>,
+            reply_id: Option<i32>,
+        ) -> Result<(), sqlx::Error> {
+            let image_ids = inline_upload_ids(contents);
+            if image_ids.is_empty() {
+                return Ok(());
+            }
+            sqlx::query(
+                "UPDATE attachments SET postid = $3, replyid = $4, inline = true
+                WHERE attachmentid = ANY($1) AND uploaderid = $2 AND postid IS NULL AND replyid IS NULL
+                AND classid = (SELECT classid FROM posts
+                    WHERE postid = coalesce($3, (SELECT postid FROM replies WHERE replyid = $4)))",
+            )
+            .bind(&image_ids)
+            .bind(uploader_id)
+            .bind(post_id)
+            .bind(reply_id)
+            .execute(executor)
+            .await?;
+            Ok(())
+        }
+
+        /// Whether a user is a student, TA or instructor of a class
+        pub async fn user_in_class(pool: &PgPool, class_id: i32, user_id: i32) -> Result<bool, sqlx::Error> {
+            sqlx::query_scalar(
+                "SELECT $1 IN (
+                    SELECT courseid FROM enrolled WHERE studentid = $2
+                    UNION SELECT classid FROM ta WHERE id = $2
+                    UNION SELECT courseid FROM instructing WHERE professorid = $2)",
+            )
+            .bind(class_id)
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+        }
+
+        /// Whether a user may download an attachment. Uploaders can always see their own files;
+        /// anyone else needs to be in the class and able to see the post the file is attached to.
+        pub async fn user_can_view_attachment(
+            pool: &PgPool,
+            attachment_id: i32,
+            user_id: i32,
+        ) -> Result<bool, sqlx::Error> {
+            sqlx::query_scalar(&format!(
+                "SELECT EXISTS(
+                    SELECT 1 FROM attachments
+                    LEFT JOIN replies ON replies.replyid = attachments.replyid
+                    LEFT JOIN posts ON posts.postid = coalesce(attachments.postid, replies.postid)
+                    WHERE attachments.attachmentid = $1
+                    AND (attachments.uploaderid = $2
+                        OR (posts.removed = false
+                            AND (replies.replyid IS NULL OR replies.removed = false)
+                            AND posts.classid IN (
+                                SELECT courseid FROM enrolled WHERE studentid = $2
+                                UNION SELECT classid FROM ta WHERE id = $2
+                                UNION SELECT courseid FROM instructing WHERE professorid = $2)
+                            AND {})))",
+                post_visible_to("$2")
+            ))
+            .bind(attachment_id)
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+        }
+    }
+}
+
+/// Largest file that can be uploaded, in bytes
+pub const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Image types that get a thumbnail and can be shown inline
+pub const IMAGE_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// Other file types that can be uploaded. They are always served as downloads.
+pub const DOCUMENT_TYPES: [&str; 3] = ["application/pdf", "text/plain", "application/zip"];
+
+/**
+ * Struct to hold an uploaded file
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct Attachment {
+    pub attachment_id: i32,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub has_thumbnail: bool,
+    pub post_id: Option<i32>,
+    pub reply_id: Option<i32>,
+}
+
+impl Attachment {
+    pub fn url(&self) -> String {
+        format!("/uploads/{}", self.attachment_id)
+    }
+
+    pub fn thumbnail_url(&self) -> Option<String> {
+        self.has_thumbnail
+            .then(|| format!("/uploads/{}/thumbnail", self.attachment_id))
+    }
+
+    pub fn is_image(&self) -> bool {
+        IMAGE_TYPES.contains(&self.content_type.as_str())
+    }
+}
+
+/**
+ * Get the files attached to a post and to its replies, if the user can see the post. Images shown
+ * inline in the contents are left out.
+ */
+#[server(GetPostAttachments)]
+pub async fn get_post_attachments(
+    post_id: i32,
+    user_id: i32,
+) -> Result<Vec<Attachment>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let attachments: Vec<Attachment> = sqlx::query_as(&format!(
+        "SELECT attachments.attachmentid as attachment_id, filename, content_type, size_bytes,
+            thumbnail_key IS NOT NULL as has_thumbnail, attachments.postid as post_id,
+            attachments.replyid as reply_id
+        FROM attachments
+        LEFT JOIN replies ON replies.replyid = attachments.replyid
+        JOIN posts ON posts.postid = coalesce(attachments.postid, replies.postid)
+        WHERE posts.postid = $1
+        AND attachments.inline = false
+        AND posts.removed = false
+        AND (replies.replyid IS NULL OR replies.removed = false)
+        AND {}
+        ORDER BY attachments.created_at, attachments.attachmentid",
+        post_visible_to("$2")
+    ))
+    .bind(post_id)
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(attachments)
+}
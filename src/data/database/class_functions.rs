@@ -21,6 +21,22 @@ cfg_if::cfg_if! {
 
         #[derive(sqlx::FromRow)]
         pub struct IsInstructor(i64);
+
+        /// The class a post was made in
+        pub async fn get_post_class_id(pool: &PgPool, post_id: i32) -> Result<i32, sqlx::Error> {
+            sqlx::query_scalar("SELECT classid FROM posts WHERE postid = $1")
+                .bind(post_id)
+                .fetch_one(pool)
+                .await
+        }
+
+        /// Whether a class lets authors hide their names from instructors as well as classmates
+        pub async fn class_allows_full_anonymity(pool: &PgPool, class_id: i32) -> Result<bool, sqlx::Error> {
+            sqlx::query_scalar("SELECT allow_full_anonymity FROM classes WHERE courseid = $1")
+                .bind(class_id)
+                .fetch_one(pool)
+                .await
+        }
     }
 }
 
@@ -359,6 +375,48 @@ pub async fn check_user_is_instructor(user_id: i32, class_id: i32) -> Result<boo
     }
 }
 
+/**
+ * Whether posts and replies in a class can be anonymous to instructors as well as classmates
+ */
+#[server(GetClassAllowsFullAnonymity)]
+pub async fn get_class_allows_full_anonymity(class_id: i32) -> Result<bool, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    class_allows_full_anonymity(&pool, class_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))
+}
+
+/**
+ * Let authors in a class hide their names from instructors too, or stop letting them. Posts and
+ * replies that were already fully anonymous stay that way.
+ */
+#[server(SetClassAllowsFullAnonymity)]
+pub async fn set_class_allows_full_anonymity(
+    class_id: i32,
+    user_id: i32,
+    allow: bool,
+) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    if !check_user_is_instructor(user_id, class_id).await? {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "Only instructors can change anonymity settings".to_string(),
+        ));
+    }
+    sqlx::query("UPDATE classes SET allow_full_anonymity = $1 WHERE courseid = $2")
+        .bind(allow)
+        .bind(class_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    Ok(())
+}
+
 #[server(AddTAToClass)]
 pub async fn add_ta_to_class(user_id: i32, class_id: i32) -> Result<(), ServerFnError> {
     let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
@@ -0,0 +1,307 @@
+/**
+ * Server functions for drafts: what a user is writing is autosaved every few seconds so that it can
+ * be restored when the composer is opened again. Submitting discards the draft.
+ */
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use crate::data::database::post_functions::{check_user_can_see_post, post_visible_to};
+
+        /// A draft as stored, with the class and post it belongs to
+        #[derive(sqlx::FromRow)]
+        struct DraftRow {
+            draft_id: i32,
+            kind: String,
+            target_id: i32,
+            class_id: i32,
+            class_name: String,
+            post_title: Option<String>,
+            title: String,
+            contents: String,
+            updated_at: chrono::NaiveDateTime,
+        }
+
+        impl DraftRow {
+            fn into_summary(self) -> Option<DraftSummary> {
+                Some(DraftSummary {
+                    draft_id: self.draft_id,
+                    context: DraftContext::from_stored(&self.kind, self.target_id)?,
+                    class_id: self.class_id,
+                    class_name: self.class_name,
+                    post_title: self.post_title,
+                    title: self.title,
+                    contents: self.contents,
+                    updated_at: self.updated_at,
+                })
+            }
+        }
+
+        /// Fails unless a user can write where a draft is for: a reply needs them to see the post,
+        /// and an edit needs them to be the post's author or an instructor of its class
+        async fn check_can_draft(
+            pool: &PgPool,
+            user_id: i32,
+            context: DraftContext,
+        ) -> Result<(), ServerFnError> {
+            match context {
+                DraftContext::Reply { post_id } => {
+                    check_user_can_see_post(pool, post_id, user_id).await
+                }
+                DraftContext::EditPost { post_id } => {
+                    let can_edit: bool = sqlx::query_scalar(
+                        "SELECT EXISTS(SELECT 1 FROM posts WHERE postid = $1 AND (authorid = $2
+                        OR classid IN (SELECT courseid FROM instructing WHERE professorid = $2)))",
+                    )
+                    .bind(post_id)
+                    .bind(user_id)
+                    .fetch_one(pool)
+                    .await
+                    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+                    if !can_edit {
+                        return Err(ServerFnError::<NoCustomError>::ServerError(
+                            "Only the author or an instructor can edit this post".to_string(),
+                        ));
+                    }
+                    Ok(())
+                }
+                DraftContext::NewPost { .. } | DraftContext::Announcement { .. } => Ok(()),
+            }
+        }
+
+        /// Deletes a user's draft once what it was a draft of has been submitted
+        pub async fn discard_draft(
+            pool: &PgPool,
+            user_id: i32,
+            context: DraftContext,
+        ) -> Result<(), sqlx::Error> {
+            sqlx::query("DELETE FROM drafts WHERE userid = $1 AND kind = $2 AND targetid = $3")
+                .bind(user_id)
+                .bind(context.kind())
+                .bind(context.target_id())
+                .execute(pool)
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+/**
+ * Where a draft is being written
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum DraftContext {
+    NewPost { class_id: i32 },
+    Reply { post_id: i32 },
+    EditPost { post_id: i32 },
+    Announcement { class_id: i32 },
+}
+
+impl DraftContext {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DraftContext::NewPost { .. } => "post",
+            DraftContext::Reply { .. } => "reply",
+            DraftContext::EditPost { .. } => "edit",
+            DraftContext::Announcement { .. } => "announcement",
+        }
+    }
+
+    /// The class or post the draft belongs to, depending on its kind
+    pub fn target_id(&self) -> i32 {
+        match *self {
+            DraftContext::NewPost { class_id } | DraftContext::Announcement { class_id } => class_id,
+            DraftContext::Reply { post_id } | DraftContext::EditPost { post_id } => post_id,
+        }
+    }
+
+    /// The class the draft belongs to, if it doesn't have to be looked up from a post
+    pub fn class_id(&self) -> Option<i32> {
+        match *self {
+            DraftContext::NewPost { class_id } | DraftContext::Announcement { class_id } => {
+                Some(class_id)
+            }
+            DraftContext::Reply { .. } | DraftContext::EditPost { .. } => None,
+        }
+    }
+
+    pub fn from_stored(kind: &str, target_id: i32) -> Option<DraftContext> {
+        match kind {
+            "post" => Some(DraftContext::NewPost { class_id: target_id }),
+            "reply" => Some(DraftContext::Reply { post_id: target_id }),
+            "edit" => Some(DraftContext::EditPost { post_id: target_id }),
+            "announcement" => Some(DraftContext::Announcement { class_id: target_id }),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Struct to hold a saved draft
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct Draft {
+    pub title: String,
+    pub contents: String,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+/**
+ * Struct to hold a draft in the list on the profile page
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct DraftSummary {
+    pub draft_id: i32,
+    pub context: DraftContext,
+    pub class_id: i32,
+    pub class_name: String,
+    /// Title of the post being replied to or edited
+    pub post_title: Option<String>,
+    pub title: String,
+    pub contents: String,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl DraftSummary {
+    /// Where the composer the draft is restored into can be opened
+    pub fn href(&self) -> String {
+        match self.context {
+            DraftContext::NewPost { class_id } => format!("/classes/{}?compose=post", class_id),
+            DraftContext::Reply { post_id } => format!("/classes/{}/{}", self.class_id, post_id),
+            DraftContext::EditPost { post_id } => {
+                format!("/classes/{}/{}/edit", self.class_id, post_id)
+            }
+            DraftContext::Announcement { class_id } => {
+                format!("/classes/{}?compose=announcement", class_id)
+            }
+        }
+    }
+
+    /// What the draft is for, such as `Reply to "Homework 2 question"`
+    pub fn description(&self) -> String {
+        let post_title = self.post_title.clone().unwrap_or_default();
+        match self.context {
+            DraftContext::NewPost { .. } => "New post".to_string(),
+            DraftContext::Reply { .. } => format!("Reply to \"{}\"", post_title),
+            DraftContext::EditPost { .. } => format!("Edit of \"{}\"", post_title),
+            DraftContext::Announcement { .. } => "New announcement".to_string(),
+        }
+    }
+}
+
+/**
+ * Get a user's draft for a place they can write, if they have one
+ */
+#[server(GetDraft)]
+pub async fn get_draft(user_id: i32, context: DraftContext) -> Result<Option<Draft>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+    check_can_draft(&pool, user_id, context).await?;
+
+    let draft: Option<Draft> = sqlx::query_as(
+        "SELECT title, contents, updated_at FROM drafts
+        WHERE userid = $1 AND kind = $2 AND targetid = $3",
+    )
+    .bind(user_id)
+    .bind(context.kind())
+    .bind(context.target_id())
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(draft)
+}
+
+/**
+ * Save a user's draft, replacing the one they had for the same place. An empty draft is deleted
+ * instead. Returns when the draft was saved, if it was kept.
+ */
+#[server(SaveDraft)]
+pub async fn save_draft(
+    user_id: i32,
+    context: DraftContext,
+    title: String,
+    contents: String,
+) -> Result<Option<chrono::NaiveDateTime>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    // The rich text editor leaves an empty paragraph behind when everything is deleted
+    let is_empty = |text: &str| matches!(text.trim(), "" | "<p></p>");
+    if is_empty(&title) && is_empty(&contents) {
+        discard_draft(&pool, user_id, context)
+            .await
+            .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+        return Ok(None);
+    }
+    check_can_draft(&pool, user_id, context).await?;
+
+    let updated_at: chrono::NaiveDateTime = sqlx::query_scalar(
+        "INSERT INTO drafts (userid, kind, targetid, classid, title, contents)
+        VALUES ($1, $2, $3, COALESCE($4, (SELECT classid FROM posts WHERE postid = $3)), $5, $6)
+        ON CONFLICT (userid, kind, targetid) DO UPDATE
+        SET title = EXCLUDED.title, contents = EXCLUDED.contents, updated_at = CURRENT_TIMESTAMP
+        RETURNING updated_at",
+    )
+    .bind(user_id)
+    .bind(context.kind())
+    .bind(context.target_id())
+    .bind(context.class_id())
+    .bind(title)
+    .bind(contents)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(Some(updated_at))
+}
+
+/**
+ * Delete one of a user's drafts
+ */
+#[server(DeleteDraft)]
+pub async fn delete_draft(user_id: i32, context: DraftContext) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    discard_draft(&pool, user_id, context)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))
+}
+
+/**
+ * Get all of a user's drafts, most recently saved first
+ */
+#[server(GetDrafts)]
+pub async fn get_drafts(user_id: i32) -> Result<Vec<DraftSummary>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    // Titles of posts the user can no longer see are left out
+    let rows: Vec<DraftRow> = sqlx::query_as(&format!(
+        "SELECT drafts.draftid as draft_id, drafts.kind, drafts.targetid as target_id,
+            drafts.classid as class_id, classes.coursename as class_name,
+            posts.title as post_title, drafts.title, drafts.contents, drafts.updated_at
+        FROM drafts
+        JOIN classes ON drafts.classid = classes.courseid
+        LEFT JOIN posts ON drafts.kind IN ('reply', 'edit') AND posts.postid = drafts.targetid
+            AND {}
+        WHERE drafts.userid = $1
+        ORDER BY drafts.updated_at DESC",
+        post_visible_to("$1")
+    ))
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(rows.into_iter().filter_map(DraftRow::into_summary).collect())
+}
@@ -0,0 +1,132 @@
+/**
+ * Server functions for finding questions that have already been asked and marking duplicates
+ */
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use crate::data::database::post_functions::post_visible_to;
+        use crate::data::database::class_functions::{check_user_is_instructor, ClassId};
+        use crate::data::database::watch_functions::notify_post_watchers;
+    }
+}
+
+/**
+ * Struct to hold an existing post that looks like the one being written
+ */
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct SimilarPost {
+    pub post_id: i32,
+    pub title: String,
+    pub resolved: bool,
+    pub score: f64,
+}
+
+/**
+ * Get the posts in a class most similar to a draft, ranked by full-text match on the title and
+ * body plus trigram similarity of the titles. Duplicates are skipped in favour of their originals.
+ */
+#[server(GetSimilarPosts)]
+pub async fn get_similar_posts(
+    class_id: i32,
+    user_id: i32,
+    title: String,
+    contents: String,
+) -> Result<Vec<SimilarPost>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    // Any word of the draft may match, so the words of plainto_tsquery are OR'd together
+    let posts: Vec<SimilarPost> = sqlx::query_as(&format!(
+        "WITH draft AS (
+            SELECT NULLIF(replace(plainto_tsquery('english', $3 || ' ' || regexp_replace($4, '<[^>]*>', ' ', 'g'))::text, '&', '|'), '')::tsquery AS query
+        )
+        SELECT postid as post_id, title, resolved,
+            (COALESCE(ts_rank(search_vector, draft.query), 0) + similarity(title, $3))::FLOAT8 as score
+        FROM posts, draft
+        WHERE classid = $1
+        AND removed = false
+        AND duplicate_of IS NULL
+        AND (search_vector @@ draft.query OR title % $3)
+        AND {}
+        ORDER BY score DESC
+        LIMIT 5",
+        post_visible_to("$2")
+    ))
+    .bind(class_id)
+    .bind(user_id)
+    .bind(title)
+    .bind(contents)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(posts)
+}
+
+/**
+ * Mark a post as a duplicate of another post in the same class, or clear the mark with None.
+ * Only instructors and TAs may mark duplicates.
+ */
+#[server(MarkPostDuplicate)]
+pub async fn mark_post_duplicate(
+    post_id: i32,
+    original_post_id: Option<i32>,
+    user_id: i32,
+) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let ClassId(class_id) = sqlx::query_as("SELECT classid FROM posts WHERE postid = $1")
+        .bind(post_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| ServerFnError::<NoCustomError>::ServerError("Post not found".to_string()))?;
+
+    if !check_user_is_instructor(user_id, class_id).await? {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "User does not have access to mark duplicates".to_string(),
+        ));
+    }
+
+    if let Some(original_post_id) = original_post_id {
+        // The original has to be another live post in the class that isn't a duplicate itself
+        let valid_original: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM posts
+            WHERE postid = $1 AND postid <> $2 AND classid = $3 AND removed = false AND duplicate_of IS NULL)",
+        )
+        .bind(original_post_id)
+        .bind(post_id)
+        .bind(class_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+        if !valid_original {
+            return Err(ServerFnError::<NoCustomError>::ServerError(
+                "Original post is not a valid post in this class".to_string(),
+            ));
+        }
+    }
+
+    sqlx::query("UPDATE posts SET duplicate_of = $1 WHERE postid = $2")
+        .bind(original_post_id)
+        .bind(post_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    if original_post_id.is_some() {
+        notify_post_watchers(&pool, post_id, Some(user_id), "Marked as a duplicate:")
+            .await
+            .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    }
+
+    Ok(())
+}
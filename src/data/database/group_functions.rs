@@ -0,0 +1,472 @@
+/**
+ * Server functions for groups within a class, such as lab sections and project teams. Instructors
+ * create groups and assign students to them by hand, from a CSV, or by shuffling the class into them.
+ */
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use crate::data::database::attachment_functions::user_in_class;
+        use crate::data::database::class_functions::check_user_is_instructor;
+
+        /// The columns of a `ClassGroup`, selected from `class_groups`
+        const GROUP_COLUMNS: &str = "groupid as group_id, classid as class_id, name,
+            ARRAY(SELECT userid FROM class_group_members
+                WHERE class_group_members.groupid = class_groups.groupid ORDER BY userid) as member_ids";
+
+        /// The groups among `group_ids` that belong to a class, so ids sent by a client can't
+        /// reach into another class
+        pub async fn groups_in_class(
+            pool: &PgPool,
+            class_id: i32,
+            group_ids: &[i32],
+        ) -> Result<Vec<i32>, sqlx::Error> {
+            if group_ids.is_empty() {
+                return Ok(Vec::new());
+            }
+            sqlx::query_scalar("SELECT groupid FROM class_groups WHERE classid = $1 AND groupid = ANY($2)")
+                .bind(class_id)
+                .bind(group_ids)
+                .fetch_all(pool)
+                .await
+        }
+
+        /// The class a group belongs to, checking that a user is one of its instructors
+        async fn check_can_change_group(
+            pool: &PgPool,
+            group_id: i32,
+            user_id: i32,
+        ) -> Result<i32, ServerFnError> {
+            let class_id: i32 = sqlx::query_scalar("SELECT classid FROM class_groups WHERE groupid = $1")
+                .bind(group_id)
+                .fetch_one(pool)
+                .await
+                .map_err(|_| ServerFnError::<NoCustomError>::ServerError("Group not found".to_string()))?;
+            check_can_manage_groups(class_id, user_id).await?;
+            Ok(class_id)
+        }
+
+        async fn check_can_manage_groups(class_id: i32, user_id: i32) -> Result<(), ServerFnError> {
+            if !check_user_is_instructor(user_id, class_id).await? {
+                return Err(ServerFnError::<NoCustomError>::ServerError(
+                    "Only instructors can manage groups".to_string(),
+                ));
+            }
+            Ok(())
+        }
+
+        /// The rows of CSV text, each as written and split into its fields. Fields may be quoted to
+        /// hold commas, line breaks or doubled quotes.
+        fn parse_csv(csv: &str) -> Vec<(String, Vec<String>)> {
+            let mut rows = Vec::new();
+            let mut fields = Vec::new();
+            let mut field = String::new();
+            let mut row_start = 0;
+            let mut in_quotes = false;
+            let mut chars = csv.char_indices().peekable();
+            while let Some((index, c)) = chars.next() {
+                match c {
+                    '"' if in_quotes && chars.peek().is_some_and(|(_, next)| *next == '"') => {
+                        field.push('"');
+                        chars.next();
+                    }
+                    '"' => in_quotes = !in_quotes,
+                    ',' if !in_quotes => fields.push(std::mem::take(&mut field).trim().to_string()),
+                    '\n' if !in_quotes => {
+                        fields.push(std::mem::take(&mut field).trim().to_string());
+                        rows.push((csv[row_start..index].trim().to_string(), std::mem::take(&mut fields)));
+                        row_start = index + 1;
+                    }
+                    _ => field.push(c),
+                }
+            }
+            fields.push(field.trim().to_string());
+            rows.push((csv[row_start..].trim().to_string(), fields));
+            rows.retain(|(row, _)| !row.is_empty());
+            rows
+        }
+
+        /// The rows of an import CSV as (row, group name, username), and the rows that don't have
+        /// exactly those two fields. A first row naming its columns, like `group,username`, is
+        /// left out.
+        fn csv_assignments(csv: &str) -> (Vec<(String, String, String)>, Vec<String>) {
+            let mut assignments = Vec::new();
+            let mut malformed = Vec::new();
+            for (index, (row, columns)) in parse_csv(csv).into_iter().enumerate() {
+                match <[String; 2]>::try_from(columns) {
+                    Ok([group_name, _]) if index == 0 && group_name.eq_ignore_ascii_case("group") => {}
+                    Ok([group_name, username]) => assignments.push((row, group_name, username)),
+                    Err(_) => malformed.push(row),
+                }
+            }
+            (assignments, malformed)
+        }
+
+        async fn fetch_class_groups(pool: &PgPool, class_id: i32) -> Result<Vec<ClassGroup>, sqlx::Error> {
+            sqlx::query_as(&format!(
+                "SELECT {GROUP_COLUMNS} FROM class_groups WHERE classid = $1 ORDER BY name"
+            ))
+            .bind(class_id)
+            .fetch_all(pool)
+            .await
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+
+            fn assignment(row: &str, group_name: &str, username: &str) -> (String, String, String) {
+                (row.to_string(), group_name.to_string(), username.to_string())
+            }
+
+            #[test]
+            fn header_row_is_left_out() {
+                let (assignments, malformed) = csv_assignments("Group,Username\nTeam 1,jsmith");
+                assert_eq!(assignments, vec![assignment("Team 1,jsmith", "Team 1", "jsmith")]);
+                assert!(malformed.is_empty());
+            }
+
+            #[test]
+            fn only_the_first_row_can_be_a_header() {
+                let (assignments, _) = csv_assignments("Team 1,jsmith\ngroup,adoe");
+                assert_eq!(
+                    assignments,
+                    vec![
+                        assignment("Team 1,jsmith", "Team 1", "jsmith"),
+                        assignment("group,adoe", "group", "adoe"),
+                    ]
+                );
+            }
+
+            #[test]
+            fn crlf_line_endings_are_trimmed() {
+                let (assignments, _) = csv_assignments("Team 1,jsmith\r\n\"Team 2\",adoe\r\n");
+                assert_eq!(
+                    assignments,
+                    vec![
+                        assignment("Team 1,jsmith", "Team 1", "jsmith"),
+                        assignment("\"Team 2\",adoe", "Team 2", "adoe"),
+                    ]
+                );
+            }
+
+            #[test]
+            fn quoted_fields_hold_commas_and_line_breaks() {
+                let (assignments, malformed) =
+                    csv_assignments("\"Smith, Jones\",jsmith\n\"Lab\nSection\",adoe");
+                assert_eq!(
+                    assignments,
+                    vec![
+                        assignment("\"Smith, Jones\",jsmith", "Smith, Jones", "jsmith"),
+                        assignment("\"Lab\nSection\",adoe", "Lab\nSection", "adoe"),
+                    ]
+                );
+                assert!(malformed.is_empty());
+            }
+
+            #[test]
+            fn doubled_quotes_are_quotes() {
+                let (assignments, _) = csv_assignments("\"The \"\"A\"\" Team\",jsmith");
+                assert_eq!(assignments[0].1, "The \"A\" Team");
+            }
+
+            #[test]
+            fn blank_lines_are_skipped() {
+                let (assignments, malformed) =
+                    csv_assignments("\n\nTeam 1, jsmith\n   \n\nTeam 2 ,adoe\n\n");
+                assert_eq!(
+                    assignments,
+                    vec![
+                        assignment("Team 1, jsmith", "Team 1", "jsmith"),
+                        assignment("Team 2 ,adoe", "Team 2", "adoe"),
+                    ]
+                );
+                assert!(malformed.is_empty());
+            }
+
+            #[test]
+            fn rows_without_two_fields_are_malformed() {
+                let (assignments, malformed) =
+                    csv_assignments("jsmith\nTeam 1,adoe,extra\nTeam 2,bkim");
+                assert_eq!(assignments, vec![assignment("Team 2,bkim", "Team 2", "bkim")]);
+                assert_eq!(malformed, vec!["jsmith", "Team 1,adoe,extra"]);
+            }
+        }
+    }
+}
+
+/**
+ * Struct to hold a group and the ids of its members
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct ClassGroup {
+    pub group_id: i32,
+    pub class_id: i32,
+    pub name: String,
+    pub member_ids: Vec<i32>,
+}
+
+/**
+ * What importing groups from a CSV did
+ */
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct GroupImport {
+    /// Every group of the class after the import
+    pub groups: Vec<ClassGroup>,
+    pub assigned: usize,
+    /// Rows that didn't name a group and a member of the class, as written
+    pub skipped_rows: Vec<String>,
+}
+
+/**
+ * Get the groups of a class by name, with their members. Only members of the class can.
+ */
+#[server(GetClassGroups)]
+pub async fn get_class_groups(class_id: i32, user_id: i32) -> Result<Vec<ClassGroup>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    if !user_in_class(&pool, class_id, user_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?
+    {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "User is not a member of this class".to_string(),
+        ));
+    }
+
+    fetch_class_groups(&pool, class_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))
+}
+
+#[server(CreateClassGroup)]
+pub async fn create_class_group(
+    class_id: i32,
+    user_id: i32,
+    name: String,
+) -> Result<ClassGroup, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_can_manage_groups(class_id, user_id).await?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "Groups need a name".to_string(),
+        ));
+    }
+
+    sqlx::query_as(&format!(
+        "INSERT INTO class_groups (classid, name) VALUES ($1, $2) RETURNING {GROUP_COLUMNS}"
+    ))
+    .bind(class_id)
+    .bind(name)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+            ServerFnError::<NoCustomError>::ServerError(format!("There is already a group named {}", name))
+        }
+        e => ServerFnError::<NoCustomError>::ServerError(e.to_string()),
+    })
+}
+
+#[server(DeleteClassGroup)]
+pub async fn delete_class_group(group_id: i32, user_id: i32) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_can_change_group(&pool, group_id, user_id).await?;
+    sqlx::query("DELETE FROM class_groups WHERE groupid = $1")
+        .bind(group_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(())
+}
+
+/**
+ * Replace the members of a group. Only members of the group's class are kept.
+ */
+#[server(SetGroupMembers)]
+pub async fn set_group_members(
+    group_id: i32,
+    user_id: i32,
+    member_ids: Vec<i32>,
+) -> Result<ClassGroup, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let class_id = check_can_change_group(&pool, group_id, user_id).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    sqlx::query("DELETE FROM class_group_members WHERE groupid = $1")
+        .bind(group_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    sqlx::query(
+        "INSERT INTO class_group_members (groupid, userid)
+        SELECT $1, member.userid FROM UNNEST($3::INTEGER[]) AS member(userid)
+        WHERE member.userid IN (
+            SELECT studentid FROM enrolled WHERE courseid = $2
+            UNION SELECT id FROM ta WHERE classid = $2
+            UNION SELECT professorid FROM instructing WHERE courseid = $2)
+        ON CONFLICT DO NOTHING",
+    )
+    .bind(group_id)
+    .bind(class_id)
+    .bind(&member_ids)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    tx.commit()
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    sqlx::query_as(&format!("SELECT {GROUP_COLUMNS} FROM class_groups WHERE groupid = $1"))
+        .bind(group_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))
+}
+
+/**
+ * Assign students to groups from CSV rows of a group name and a username, e.g. "Team 1,jsmith".
+ * Groups that don't exist yet are created, and a header row is skipped. Students keep any groups
+ * they were already in. Nothing is saved if the import fails part way.
+ */
+#[server(ImportGroupsCsv)]
+pub async fn import_groups_csv(
+    class_id: i32,
+    user_id: i32,
+    csv: String,
+) -> Result<GroupImport, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_can_manage_groups(class_id, user_id).await?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let mut assigned = 0;
+    let (assignments, mut skipped_rows) = csv_assignments(&csv);
+    for (row, group_name, username) in assignments {
+        let member_id: Option<i32> = sqlx::query_scalar(
+            "SELECT id FROM users WHERE username = $2 AND id IN (
+                SELECT studentid FROM enrolled WHERE courseid = $1
+                UNION SELECT id FROM ta WHERE classid = $1)",
+        )
+        .bind(class_id)
+        .bind(&username)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+        let (Some(member_id), false) = (member_id, group_name.is_empty()) else {
+            skipped_rows.push(row);
+            continue;
+        };
+
+        sqlx::query(
+            "WITH new_group AS (
+                INSERT INTO class_groups (classid, name) VALUES ($1, $2)
+                ON CONFLICT (classid, name) DO UPDATE SET name = EXCLUDED.name
+                RETURNING groupid
+            )
+            INSERT INTO class_group_members (groupid, userid)
+            SELECT groupid, $3 FROM new_group
+            ON CONFLICT DO NOTHING",
+        )
+        .bind(class_id)
+        .bind(&group_name)
+        .bind(member_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+        assigned += 1;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    let groups = fetch_class_groups(&pool, class_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    Ok(GroupImport {
+        groups,
+        assigned,
+        skipped_rows,
+    })
+}
+
+/**
+ * Shuffle every student of a class into the given groups so their sizes differ by at most one,
+ * replacing the groups' members
+ */
+#[server(BalanceGroups)]
+pub async fn balance_groups(
+    class_id: i32,
+    user_id: i32,
+    group_ids: Vec<i32>,
+) -> Result<Vec<ClassGroup>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_can_manage_groups(class_id, user_id).await?;
+    let group_ids = groups_in_class(&pool, class_id, &group_ids)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    if group_ids.is_empty() {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "Pick at least one group to assign students to".to_string(),
+        ));
+    }
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    sqlx::query("DELETE FROM class_group_members WHERE groupid = ANY($1)")
+        .bind(&group_ids)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    // Students are dealt out one at a time in a random order
+    sqlx::query(
+        "INSERT INTO class_group_members (groupid, userid)
+        SELECT ($2::INTEGER[])[(shuffled.position - 1) % cardinality($2::INTEGER[]) + 1], shuffled.studentid
+        FROM (
+            SELECT studentid, (row_number() OVER (ORDER BY random()))::INTEGER AS position
+            FROM enrolled WHERE courseid = $1
+        ) shuffled",
+    )
+    .bind(class_id)
+    .bind(&group_ids)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    tx.commit()
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    fetch_class_groups(&pool, class_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))
+}
@@ -6,6 +6,7 @@ cfg_if::cfg_if! {
         use leptos::{server_fn::error::NoCustomError, use_context};
         use sqlx::postgres::PgPool;
         use crate::data::database::class_functions::check_user_is_instructor;
+        use crate::data::database::group_functions::groups_in_class;
     }
 }
 
@@ -67,24 +68,38 @@ pub async fn create_poll(
     question: String,
     course_id: i32,
     answers: Vec<String>,
+    group_ids: Vec<i32>,
 ) -> Result<Poll, ServerFnError> {
     let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
         "Unable to complete request".to_string(),
     ))?;
 
+    // Polls for some groups only are hidden from the rest of the class
+    let group_ids = groups_in_class(&pool, course_id, &group_ids)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
     // Insert poll
     let poll_id = sqlx::query_scalar::<_, i32>(
-        "INSERT INTO polls (question, is_active, course_id)
-         VALUES ($1, $2, $3)
+        "INSERT INTO polls (question, is_active, course_id, targeted)
+         VALUES ($1, $2, $3, $4)
          RETURNING id",
     )
     .bind(&question)
     .bind(true)
     .bind(course_id)
+    .bind(!group_ids.is_empty())
     .fetch_one(&pool)
     .await
     .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
 
+    sqlx::query("INSERT INTO poll_groups (pollid, groupid) SELECT $1, UNNEST($2::INTEGER[])")
+        .bind(poll_id)
+        .bind(&group_ids)
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
     // Insert answers
     for answer_text in answers {
         sqlx::query(
@@ -347,14 +362,22 @@ pub async fn get_all_polls(
         "Unable to complete request".to_string(),
     ))?;
 
-    let polls: Vec<Poll> =
-        sqlx::query_as("SELECT * FROM polls WHERE course_id = $1 order by created_at DESC")
-            .bind(course_id)
-            .fetch_all(&pool)
-            .await
-            .map_err(|_| {
-                ServerFnError::<NoCustomError>::ServerError("Unable to delete poll".to_string())
-            })?;
+    // Polls for some groups only are shown to the members of those groups and the class's staff
+    let polls: Vec<Poll> = sqlx::query_as(
+        "SELECT * FROM polls WHERE course_id = $1
+        AND (NOT polls.targeted
+            OR EXISTS (SELECT 1 FROM instructing WHERE instructing.courseid = $1 AND instructing.professorid = $2)
+            OR EXISTS (SELECT 1 FROM ta WHERE ta.classid = $1 AND ta.id = $2)
+            OR EXISTS (SELECT 1 FROM poll_groups
+                JOIN class_group_members ON class_group_members.groupid = poll_groups.groupid
+                WHERE poll_groups.pollid = polls.id AND class_group_members.userid = $2))
+        order by created_at DESC",
+    )
+    .bind(course_id)
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| ServerFnError::<NoCustomError>::ServerError("Unable to delete poll".to_string()))?;
 
     let polls_with_answers = futures::future::join_all(polls.into_iter().map(|poll| async {
         let poll_id = poll.id;
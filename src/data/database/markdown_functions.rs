@@ -0,0 +1,16 @@
+/**
+ * Server function for previewing Markdown while it is written, converted exactly as it will be
+ * when saved
+ */
+use leptos::{server, ServerFnError};
+
+#[cfg(feature = "ssr")]
+use crate::data::content::{markdown_to_html, prepare_contents};
+
+/**
+ * Render Markdown the way it will appear once saved
+ */
+#[server(PreviewMarkdown)]
+pub async fn preview_markdown(markdown: String) -> Result<String, ServerFnError> {
+    Ok(prepare_contents(&markdown_to_html(&markdown)))
+}
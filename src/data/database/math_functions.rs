@@ -0,0 +1,25 @@
+/**
+ * Server function for previewing LaTeX while it is written in the editor, so the browser doesn't
+ * need a math renderer of its own
+ */
+use leptos::{server, ServerFnError};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use crate::data::content::sanitize_html;
+        use crate::data::math::render_latex;
+        use leptos::server_fn::error::NoCustomError;
+    }
+}
+
+/**
+ * Render LaTeX the way it will appear once saved, or fail with a message if it doesn't parse
+ */
+#[server(PreviewMath)]
+pub async fn preview_math(latex: String, display: bool) -> Result<String, ServerFnError> {
+    render_latex(&latex, display)
+        .map(|math| sanitize_html(&math))
+        .ok_or(ServerFnError::<NoCustomError>::ServerError(
+            "This LaTeX could not be rendered".to_string(),
+        ))
+}
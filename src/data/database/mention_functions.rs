@@ -0,0 +1,161 @@
+/**
+ * Server functions for @mentions and #post references: resolving them when content is saved,
+ * notifying mentioned users, and finding posts to suggest while a reference is typed
+ */
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use crate::data::database::post_functions::post_visible_to;
+        use std::collections::HashMap;
+        use crate::data::database::notification_functions::{push_created_notifications, CreatedNotification};
+        use crate::data::mentions::{link_tokens, mentioned_user_ids, token_ids};
+
+        /// Link the mention and post reference tokens in sanitized contents. Only members of the
+        /// class and posts in it that the author can see are linked; other tokens stay as typed.
+        /// Returns the linked contents and the ids of the users mentioned in them.
+        pub async fn link_mentions(
+            pool: &PgPool,
+            author_id: i32,
+            class_id: i32,
+            contents: &str,
+        ) -> Result<(String, Vec<i32>), sqlx::Error> {
+            let (user_ids, post_ids) = token_ids(contents);
+            if user_ids.is_empty() && post_ids.is_empty() {
+                return Ok((contents.to_string(), Vec::new()));
+            }
+
+            let users: HashMap<i32, String> = sqlx::query_as::<_, (i32, String)>(
+                "SELECT id, CONCAT(firstname, ' ', lastname) FROM users
+                WHERE id = ANY($1)
+                AND id IN (
+                    SELECT studentid FROM enrolled WHERE courseid = $2
+                    UNION SELECT id FROM ta WHERE classid = $2
+                    UNION SELECT professorid FROM instructing WHERE courseid = $2
+                )",
+            )
+            .bind(&user_ids)
+            .bind(class_id)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+
+            let posts: HashMap<i32, String> = sqlx::query_as::<_, (i32, String)>(&format!(
+                "SELECT postid, title FROM posts
+                WHERE postid = ANY($1)
+                AND classid = $2
+                AND removed = false
+                AND {}",
+                post_visible_to("$3")
+            ))
+            .bind(&post_ids)
+            .bind(class_id)
+            .bind(author_id)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+
+            let linked = link_tokens(contents, class_id, &users, &posts);
+            let mentioned = mentioned_user_ids(&linked)
+                .into_iter()
+                .filter(|user_id| users.contains_key(user_id))
+                .collect();
+            Ok((linked, mentioned))
+        }
+
+        /// Notify the users `link_mentions` found mentioned in contents added to a post, skipping
+        /// the author and anyone already mentioned in `previous_contents`. Users who aren't in the
+        /// class or can't see the post aren't notified.
+        pub async fn notify_mentioned_users(
+            pool: &PgPool,
+            post_id: i32,
+            author_id: i32,
+            mentioned: &[i32],
+            previous_contents: &str,
+        ) -> Result<(), sqlx::Error> {
+            let previously_mentioned = mentioned_user_ids(previous_contents);
+            let mentioned: Vec<i32> = mentioned
+                .iter()
+                .copied()
+                .filter(|user_id| *user_id != author_id && !previously_mentioned.contains(user_id))
+                .collect();
+            if mentioned.is_empty() {
+                return Ok(());
+            }
+
+            let created: Vec<CreatedNotification> = sqlx::query_as(&format!(
+                "INSERT INTO notifications (userid, classid, postid, message)
+                SELECT mentioned.userid, posts.classid, posts.postid, 'You were mentioned in \"' || posts.title || '\"'
+                FROM UNNEST($2::INTEGER[]) AS mentioned(userid) JOIN posts ON posts.postid = $1
+                WHERE posts.removed = false
+                AND mentioned.userid IN (
+                    SELECT studentid FROM enrolled WHERE courseid = posts.classid
+                    UNION SELECT id FROM ta WHERE classid = posts.classid
+                    UNION SELECT professorid FROM instructing WHERE courseid = posts.classid
+                )
+                AND {}
+                RETURNING userid as user_id, notificationid as notification_id, classid as class_id, postid as post_id, message, created_at",
+                post_visible_to("mentioned.userid")
+            ))
+            .bind(post_id)
+            .bind(&mentioned)
+            .fetch_all(pool)
+            .await?;
+
+            push_created_notifications(pool, created);
+            Ok(())
+        }
+    }
+}
+
+/**
+ * Struct to hold a post that can be referenced with #
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct PostReference {
+    pub post_id: i32,
+    pub title: String,
+}
+
+/// Number of class members or posts suggested while a mention or reference is typed
+pub const MENTION_SUGGESTIONS: i64 = 6;
+
+/**
+ * Find posts in a class whose titles contain the query, newest first, among the posts the user can see
+ */
+#[server(SearchPostReferences)]
+pub async fn search_post_references(
+    class_id: i32,
+    user_id: i32,
+    query: String,
+) -> Result<Vec<PostReference>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let posts: Vec<PostReference> = sqlx::query_as(&format!(
+        "SELECT postid as post_id, title FROM posts
+        WHERE classid = $1
+        AND removed = false
+        AND {}
+        AND title ILIKE '%' || $3 || '%'
+        ORDER BY timestamp DESC
+        LIMIT $4",
+        post_visible_to("$2")
+    ))
+    .bind(class_id)
+    .bind(user_id)
+    .bind(query.trim())
+    .bind(MENTION_SUGGESTIONS)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(posts)
+}
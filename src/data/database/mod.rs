@@ -1,10 +1,26 @@
 pub mod ai_functions;
 pub mod announcement_functions;
+pub mod answer_functions;
+pub mod attachment_functions;
 pub mod class_functions;
+pub mod draft_functions;
+pub mod duplicate_functions;
+pub mod group_functions;
 pub mod live_poll_functions;
+pub mod markdown_functions;
+pub mod math_functions;
+pub mod mention_functions;
+pub mod notification_functions;
 pub mod post_functions;
+pub mod post_view_functions;
+pub mod push_functions;
+pub mod reaction_functions;
 pub mod reply_functions;
+pub mod revision_functions;
+pub mod schedule_functions;
+pub mod search_functions;
 pub mod user_functions;
+pub mod watch_functions;
 
 #[macro_export]
 macro_rules! expect_authenticated_user_or_redirect_to_login {
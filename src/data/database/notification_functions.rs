@@ -0,0 +1,170 @@
+/**
+ * Server functions for the per-user notifications shown in the header
+ */
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use crate::data::database::push_functions::{send_push_to_user, PushPayload};
+
+        /// A notification row as returned by an INSERT, along with who it was created for
+        #[derive(sqlx::FromRow)]
+        pub struct CreatedNotification {
+            pub user_id: i32,
+            #[sqlx(flatten)]
+            pub notification: UserNotification,
+        }
+
+        /// Sends a Web Push for each newly created notification without holding up the request
+        pub fn push_created_notifications(pool: &PgPool, created: Vec<CreatedNotification>) {
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                for created in created {
+                    let payload = PushPayload {
+                        title: "Encampus".to_string(),
+                        body: created.notification.message.clone(),
+                        url: created.notification.href(),
+                    };
+                    send_push_to_user(&pool, created.user_id, &payload).await;
+                }
+            });
+        }
+
+        /// Creates a notification for every member of a class except the user who caused it
+        pub async fn notify_class_members(
+            pool: &PgPool,
+            class_id: i32,
+            actor_id: Option<i32>,
+            message: &str,
+        ) -> Result<(), sqlx::Error> {
+            let created: Vec<CreatedNotification> = sqlx::query_as(
+                "INSERT INTO notifications (userid, classid, message)
+                SELECT members.userid, $1, $3 FROM (
+                    SELECT studentid as userid FROM enrolled WHERE courseid = $1
+                    UNION SELECT id FROM ta WHERE classid = $1
+                    UNION SELECT professorid FROM instructing WHERE courseid = $1
+                ) members
+                WHERE $2::INTEGER IS NULL OR members.userid <> $2
+                RETURNING userid as user_id, notificationid as notification_id, classid as class_id, postid as post_id, message, created_at",
+            )
+            .bind(class_id)
+            .bind(actor_id)
+            .bind(message)
+            .fetch_all(pool)
+            .await?;
+
+            push_created_notifications(pool, created);
+            Ok(())
+        }
+
+        /// Notify the members of some groups of a class, other than the user whose action caused it
+        pub async fn notify_group_members(
+            pool: &PgPool,
+            class_id: i32,
+            group_ids: &[i32],
+            actor_id: Option<i32>,
+            message: &str,
+        ) -> Result<(), sqlx::Error> {
+            let created: Vec<CreatedNotification> = sqlx::query_as(
+                "INSERT INTO notifications (userid, classid, message)
+                SELECT DISTINCT class_group_members.userid, $1, $4
+                FROM class_group_members JOIN class_groups ON class_groups.groupid = class_group_members.groupid
+                WHERE class_groups.classid = $1 AND class_groups.groupid = ANY($2)
+                AND ($3::INTEGER IS NULL OR class_group_members.userid <> $3)
+                RETURNING userid as user_id, notificationid as notification_id, classid as class_id, postid as post_id, message, created_at",
+            )
+            .bind(class_id)
+            .bind(group_ids)
+            .bind(actor_id)
+            .bind(message)
+            .fetch_all(pool)
+            .await?;
+
+            push_created_notifications(pool, created);
+            Ok(())
+        }
+    }
+}
+
+/**
+ * Struct to hold a notification for a user
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct UserNotification {
+    pub notification_id: i32,
+    pub class_id: i32,
+    pub post_id: Option<i32>,
+    pub message: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl UserNotification {
+    /// Where clicking the notification should take the user
+    pub fn href(&self) -> String {
+        match self.post_id {
+            Some(post_id) => format!("/classes/{}/{}", self.class_id, post_id),
+            None => format!("/classes/{}", self.class_id),
+        }
+    }
+}
+
+#[server(GetUnreadNotifications)]
+pub async fn get_unread_notifications(
+    user_id: i32,
+) -> Result<Vec<UserNotification>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let notifications: Vec<UserNotification> = sqlx::query_as(
+        "SELECT notificationid as notification_id, classid as class_id, postid as post_id, message, created_at
+        FROM notifications
+        WHERE userid = $1 AND read = false
+        ORDER BY created_at DESC
+        LIMIT 20",
+    )
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(notifications)
+}
+
+#[server(MarkNotificationRead)]
+pub async fn mark_notification_read(
+    notification_id: i32,
+    user_id: i32,
+) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    sqlx::query("UPDATE notifications SET read = true WHERE notificationid = $1 AND userid = $2")
+        .bind(notification_id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(())
+}
+
+#[server(MarkAllNotificationsRead)]
+pub async fn mark_all_notifications_read(user_id: i32) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    sqlx::query("UPDATE notifications SET read = true WHERE userid = $1 AND read = false")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(())
+}
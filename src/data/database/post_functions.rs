@@ -1,18 +1,193 @@
 use crate::pages::view_class_posts::create_post::AddPostInfo;
 use leptos::{server, ServerFnError};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
 
 cfg_if::cfg_if! {
     if #[cfg(feature = "ssr")] {
         use super::user_functions::UserId;
         use leptos::logging::error;
         use leptos::{server_fn::error::NoCustomError, use_context};
-        use sqlx::postgres::PgPool;
-        use crate::data::database::class_functions::get_class_description;
+        use sqlx::postgres::{PgConnection, PgPool, Postgres};
+        use sqlx::Acquire;
+        use crate::data::database::class_functions::{check_user_is_instructor, class_allows_full_anonymity, get_class_description, get_post_class_id, ClassId};
+        use crate::data::database::mention_functions::notify_mentioned_users;
+        use crate::data::database::draft_functions::{discard_draft, DraftContext};
         use crate::pages::view_class_posts::focused_post::AddReplyInfo;
         use crate::data::database::reply_functions::add_reply;
         use crate::data::database::ai_functions::get_gemini_response;
+        use crate::data::database::watch_functions::{add_post_watcher, notify_post_watchers};
+        use crate::data::database::revision_functions::record_post_edit;
+        use crate::data::database::attachment_functions::{attach_inline_images, attach_uploads};
+        use crate::data::content::prepare_authored_contents;
+        use crate::AuthenticationSession;
+
+        /// SQL condition that the post in `posts` is visible to the user given by `viewer`: it is
+        /// public, or the user wrote it, is the class's instructor or it was shared with them
+        pub fn post_visible_to(viewer: &str) -> String {
+            format!(
+                "(posts.private = false
+                OR posts.authorid = {viewer}
+                OR (SELECT instructorid FROM classes WHERE courseid = posts.classid) = {viewer}
+                OR EXISTS (SELECT 1 FROM post_audience WHERE post_audience.postid = posts.postid AND post_audience.userid = {viewer})
+                OR EXISTS (SELECT 1 FROM post_audience_groups
+                    JOIN class_group_members ON class_group_members.groupid = post_audience_groups.groupid
+                    WHERE post_audience_groups.postid = posts.postid AND class_group_members.userid = {viewer}))"
+            )
+        }
+
+        /// Whether a user can see a post: anyone in the class for public posts, and for private
+        /// ones the author, the instructor and the classmates it was shared with
+        pub async fn user_can_see_post(pool: &PgPool, post_id: i32, user_id: i32) -> Result<bool, sqlx::Error> {
+            sqlx::query_scalar(&format!(
+                "SELECT EXISTS(SELECT 1 FROM posts WHERE postid = $1 AND {})",
+                post_visible_to("$2")
+            ))
+            .bind(post_id)
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+        }
+
+        /// Fails unless a user can see a post, for server functions that load something of it
+        pub async fn check_user_can_see_post(pool: &PgPool, post_id: i32, user_id: i32) -> Result<(), ServerFnError> {
+            if !user_can_see_post(pool, post_id, user_id)
+                .await
+                .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?
+            {
+                return Err(ServerFnError::<NoCustomError>::ServerError(
+                    "User does not have access to this post".to_string(),
+                ));
+            }
+            Ok(())
+        }
+
+        /// Replaces who a private post is shared with. Only members and groups of the post's class
+        /// are kept, and a public post is shared with no one in particular.
+        pub async fn set_post_audience<'c>(
+            conn: impl Acquire<'c, Database = Postgres>,
+            post_id: i32,
+            audience: &[i32],
+            audience_groups: &[i32],
+        ) -> Result<(), sqlx::Error> {
+            let mut tx = conn.begin().await?;
+            sqlx::query("DELETE FROM post_audience WHERE postid = $1")
+                .bind(post_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("DELETE FROM post_audience_groups WHERE postid = $1")
+                .bind(post_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO post_audience_groups (postid, groupid)
+                SELECT posts.postid, class_groups.groupid
+                FROM posts JOIN class_groups ON class_groups.classid = posts.classid
+                WHERE posts.postid = $1 AND posts.private AND class_groups.groupid = ANY($2)",
+            )
+            .bind(post_id)
+            .bind(audience_groups)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                "INSERT INTO post_audience (postid, userid)
+                SELECT posts.postid, member.userid
+                FROM UNNEST($2::INTEGER[]) AS member(userid) JOIN posts ON posts.postid = $1
+                WHERE posts.private
+                AND member.userid <> posts.authorid
+                AND member.userid IN (
+                    SELECT studentid FROM enrolled WHERE courseid = posts.classid
+                    UNION SELECT id FROM ta WHERE classid = posts.classid
+                    UNION SELECT professorid FROM instructing WHERE courseid = posts.classid)
+                ON CONFLICT DO NOTHING",
+            )
+            .bind(post_id)
+            .bind(audience)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await
+        }
+
+        /// Inserts a new post as part of the transaction `tx`, preparing its contents and setting
+        /// its audience and uploads. Returns the post and the users it mentions, for
+        /// `after_post_created` once the transaction is committed. Shared by `create_post` and the
+        /// scheduler publishing scheduled posts.
+        pub async fn insert_post(
+            pool: &PgPool,
+            tx: &mut PgConnection,
+            new_post_info: &AddPostInfo,
+            user_id: i32,
+        ) -> Result<(Post, Vec<i32>), sqlx::Error> {
+            let (contents, mentioned) = prepare_authored_contents(
+                pool,
+                user_id,
+                new_post_info.classid,
+                &new_post_info.contents,
+            )
+            .await?;
+            let fully_anonymous = new_post_info.anonymous
+                && new_post_info.fully_anonymous
+                && class_allows_full_anonymity(pool, new_post_info.classid).await?;
+            let post: Post = sqlx::query_as("INSERT INTO posts(timestamp, title, contents, authorid, anonymous, fully_anonymous, limitedvisibility, classid, resolved, private) VALUES(CURRENT_TIMESTAMP, $1, $2, $3, $4, $5, $6, $7, false, $8)
+                                RETURNING
+                                title,
+                                postid as post_id,
+                                resolved,
+                                authorid as author_id,
+                                private,
+                                endorsed,
+                                pinned,
+                                pinned_until,
+                                last_bumped,
+                                created_at,
+                                coalesce(last_bumped, timestamp) as last_activity;")
+                .bind(&new_post_info.title)
+                .bind(&contents)
+                .bind(user_id)
+                .bind(new_post_info.anonymous)
+                .bind(fully_anonymous)
+                .bind(new_post_info.limited_visibility)
+                .bind(new_post_info.classid)
+                .bind(new_post_info.private)
+                .fetch_one(&mut *tx)
+                .await?;
+
+            set_post_audience(
+                &mut *tx,
+                post.post_id,
+                &new_post_info.audience,
+                &new_post_info.audience_groups,
+            )
+            .await?;
+            attach_uploads(&mut *tx, &new_post_info.attachment_ids, user_id, Some(post.post_id), None)
+                .await?;
+            attach_inline_images(&mut *tx, &contents, user_id, Some(post.post_id), None).await?;
+            Ok((post, mentioned))
+        }
+
+        /// Has the author of a post that was just saved watch it, and notifies anyone it mentions
+        pub async fn after_post_created(pool: &PgPool, post_id: i32, user_id: i32, mentioned: &[i32]) {
+            // Authors automatically watch their own posts
+            if let Err(e) = add_post_watcher(pool, post_id, user_id).await {
+                error!("Failed to watch new post: {:?}", e);
+            }
+            if let Err(e) = notify_mentioned_users(pool, post_id, user_id, mentioned, "").await {
+                error!("Failed to notify users mentioned in new post: {:?}", e);
+            }
+        }
+
+        /// Inserts a new post with its audience and uploads, saving none of it if any part fails,
+        /// then notifies anyone it mentions
+        pub async fn create_post(
+            pool: &PgPool,
+            new_post_info: &AddPostInfo,
+            user_id: i32,
+        ) -> Result<Post, sqlx::Error> {
+            let mut tx = pool.begin().await?;
+            let (post, mentioned) = insert_post(pool, &mut tx, new_post_info, user_id).await?;
+            tx.commit().await?;
+            after_post_created(pool, post.post_id, user_id, &mentioned).await;
+            Ok(post)
+        }
     }
 }
 
@@ -28,39 +203,220 @@ pub struct Post {
     pub private: bool,
     pub author_id: i32,
     pub endorsed: bool,
-    // pub pinned: bool,
+    /// Pinned and not yet past `pinned_until`
+    pub pinned: bool,
+    pub pinned_until: Option<chrono::NaiveDateTime>,
     pub last_bumped: Option<chrono::NaiveDateTime>,
     pub created_at: chrono::NaiveDateTime,
+    /// When the post was last bumped, or created if it never was. Posts are listed newest activity first.
+    pub last_activity: chrono::NaiveDateTime,
+    /// True if the requesting user has never opened the post
+    #[cfg_attr(feature = "ssr", sqlx(default))]
+    pub unread: bool,
+    /// Replies by other users since the requesting user last opened the post
+    #[cfg_attr(feature = "ssr", sqlx(default))]
+    pub new_replies: i64,
+    #[cfg_attr(feature = "ssr", sqlx(default))]
+    pub thanks_count: i64,
+    #[cfg_attr(feature = "ssr", sqlx(default))]
+    pub same_question_count: i64,
+    #[cfg_attr(feature = "ssr", sqlx(default))]
+    pub confused_count: i64,
+    /// True if the requesting user is notified about activity on the post
+    #[cfg_attr(feature = "ssr", sqlx(default))]
+    pub watched: bool,
 }
 
 #[derive(PartialEq, Clone, Copy)]
 pub struct PostFetcher {
     pub class_id: i32,
     pub user_id: i32,
+    pub query: PostQuery,
+}
+
+/**
+ * Order of the post grid
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum PostSort {
+    /// Most recently created or bumped first
+    #[default]
+    Activity,
+    Newest,
+    Oldest,
+    /// Posts without replies, oldest first
+    OldestUnanswered,
+    /// Most "same question" reactions first, then by activity
+    SameQuestion,
 }
 
 /**
- * Get all posts for a class given the class id
+ * Filters and sort order for the post grid. Every filter left at its default matches all posts.
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct PostQuery {
+    /// Some(true) for resolved posts only, Some(false) for unresolved only
+    pub resolved: Option<bool>,
+    pub mine: bool,
+    pub endorsed: bool,
+    pub private: bool,
+    /// Posts nobody has replied to yet
+    pub unanswered: bool,
+    /// Posts the user hasn't opened or that have replies since they last did
+    pub unread: bool,
+    pub after: Option<chrono::NaiveDate>,
+    /// Posts created before the end of this day
+    pub before: Option<chrono::NaiveDate>,
+    /// Posts written by members of this group or shared with it
+    pub group_id: Option<i32>,
+    pub sort: PostSort,
+}
+
+/// Number of unpinned posts returned by each call to `get_posts`
+pub const POSTS_PAGE_SIZE: usize = 30;
+
+/**
+ * Position in the post list to continue from: the last post of the previous page
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub struct PostCursor {
+    /// The value of the time column the posts are sorted by
+    pub sort_time: chrono::NaiveDateTime,
+    /// The "same question" reaction count, when sorting by it
+    pub sort_count: i64,
+    pub post_id: i32,
+}
+
+impl PostCursor {
+    /// The cursor continuing after the last unpinned post of a list in the given order
+    pub fn after(posts: &[Post], sort: PostSort) -> Option<PostCursor> {
+        let cursors = posts.iter().filter(|post| !post.pinned).map(|post| PostCursor {
+            sort_time: match sort {
+                PostSort::Activity => post.last_activity,
+                PostSort::SameQuestion => post.last_activity,
+                _ => post.created_at,
+            },
+            sort_count: post.same_question_count,
+            post_id: post.post_id,
+        });
+        match sort {
+            PostSort::Activity | PostSort::Newest => {
+                cursors.min_by_key(|cursor| (cursor.sort_time, cursor.post_id))
+            }
+            PostSort::SameQuestion => {
+                cursors.min_by_key(|cursor| (cursor.sort_count, cursor.sort_time, cursor.post_id))
+            }
+            PostSort::Oldest | PostSort::OldestUnanswered => {
+                cursors.max_by_key(|cursor| (cursor.sort_time, cursor.post_id))
+            }
+        }
+    }
+}
+
+/**
+ * Get a page of the posts in a class matching a query. The first page (no cursor) also contains
+ * every matching pinned post; later pages continue through the unpinned posts after the cursor.
  */
 #[server(GetPosts)]
-pub async fn get_posts(class_id: i32, user_id: i32) -> Result<Vec<Post>, ServerFnError> {
+pub async fn get_posts(
+    class_id: i32,
+    user_id: i32,
+    query: PostQuery,
+    cursor: Option<PostCursor>,
+) -> Result<Vec<Post>, ServerFnError> {
     let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
         "Unable to complete Request".to_string(),
     ))?;
 
-    let rows: Vec<Post> = sqlx::query_as(
-        "select title, postid as post_id, resolved, private, authorid as author_id, endorsed, last_bumped, timestamp, created_at
-        from posts where removed = false
-        and ((posts.classid = $1 and private = false)
-            or (posts.classid = $1 and authorid = $2 and private = true)
-            or (classid = $1 and (select instructorid from classes where courseid = $1) = $2))
-        ORDER BY last_bumped desc, timestamp desc;",
-    )
+    let (sort_columns, cursor_values, direction, comparison) = match query.sort {
+        PostSort::Activity => (vec!["last_activity", "post_id"], "$3, $4", "desc", "<"),
+        PostSort::Newest => (vec!["created_at", "post_id"], "$3, $4", "desc", "<"),
+        PostSort::Oldest | PostSort::OldestUnanswered => {
+            (vec!["created_at", "post_id"], "$3, $4", "asc", ">")
+        }
+        PostSort::SameQuestion => (
+            vec!["same_question_count", "last_activity", "post_id"],
+            "$14, $3, $4",
+            "desc",
+            "<",
+        ),
+    };
+    let sort_key = sort_columns.join(", ");
+    let order_by = sort_columns
+        .iter()
+        .map(|column| format!("{column} {direction}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let unanswered = query.unanswered || query.sort == PostSort::OldestUnanswered;
+    let visible = post_visible_to("$2");
+
+    let rows: Vec<Post> = sqlx::query_as(&format!(
+        "with visible_posts as (
+            select title, posts.postid as post_id, resolved, private,
+                -- Only the author learns which anonymous posts are theirs
+                case when anonymous and authorid <> $2 then 0 else authorid end as author_id, endorsed,
+                (pinned and (pinned_until is null or pinned_until > current_timestamp)) as pinned, pinned_until,
+                last_bumped, timestamp, created_at, coalesce(last_bumped, timestamp) as last_activity,
+                (post_views.last_viewed is null and authorid <> $2) as unread,
+                (select count(*) from replies
+                    where replies.postid = posts.postid and replies.removed = false
+                    and replies.authorid <> $2 and replies.time > post_views.last_viewed) as new_replies,
+                (select count(*) from post_reactions
+                    where post_reactions.postid = posts.postid and reaction = 'thanks') as thanks_count,
+                (select count(*) from post_reactions
+                    where post_reactions.postid = posts.postid and reaction = 'same_question') as same_question_count,
+                (select count(*) from post_reactions
+                    where post_reactions.postid = posts.postid and reaction = 'confused') as confused_count,
+                exists (select 1 from post_watchers
+                    where post_watchers.postid = posts.postid and post_watchers.userid = $2) as watched
+            from posts left join post_views on post_views.postid = posts.postid and post_views.userid = $2
+            where removed = false
+            and posts.classid = $1 and {visible}
+            and ($6::boolean is null or resolved = $6)
+            and (not $7 or authorid = $2)
+            and (not $8 or endorsed)
+            and (not $9 or private)
+            and (not $10 or not exists (
+                select 1 from replies where replies.postid = posts.postid and replies.removed = false)
+                and not exists (select 1 from answer_revisions where answer_revisions.postid = posts.postid))
+            and ($12::date is null or created_at >= $12)
+            and ($13::date is null or created_at < $13 + 1)
+            and ($15::integer is null
+                or (authorid in (select userid from class_group_members where groupid = $15) and not anonymous)
+                or exists (select 1 from post_audience_groups
+                    where post_audience_groups.postid = posts.postid and post_audience_groups.groupid = $15))
+        ),
+        matching_posts as (
+            select * from visible_posts where not $11 or unread or new_replies > 0
+        )
+        select * from (
+            (select * from matching_posts where pinned and $3::timestamp is null)
+            union all
+            (select * from matching_posts
+                where not pinned and ($3::timestamp is null or ({sort_key}) {comparison} ({cursor_values}))
+                order by {order_by}
+                limit $5)
+        ) page
+        ORDER BY pinned desc, {order_by};"
+    ))
     .bind(class_id)
     .bind(user_id)
+    .bind(cursor.map(|cursor| cursor.sort_time))
+    .bind(cursor.map(|cursor| cursor.post_id))
+    .bind(POSTS_PAGE_SIZE as i64)
+    .bind(query.resolved)
+    .bind(query.mine)
+    .bind(query.endorsed)
+    .bind(query.private)
+    .bind(unanswered)
+    .bind(query.unread)
+    .bind(query.after)
+    .bind(query.before)
+    .bind(cursor.map(|cursor| cursor.sort_count))
+    .bind(query.group_id)
     .fetch_all(&pool)
     .await
-    .expect("select should work");
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
 
     Ok(rows)
 }
@@ -74,26 +430,13 @@ pub async fn add_post(new_post_info: AddPostInfo, user_id: i32) -> Result<Post,
         "Unable to complete Request".to_string(),
     ))?;
 
-    let post: Post = sqlx::query_as("INSERT INTO posts(timestamp, title, contents, authorid, anonymous, limitedvisibility, classid, resolved, private) VALUES(CURRENT_TIMESTAMP, $1, $2, $3, $4, $5, $6, false, $7)
-                        RETURNING                
-                        title, 
-                        postid as post_id,
-                        resolved,
-                        authorid as author_id,
-                        private,
-                        endorsed,
-                        last_bumped,
-                        created_at;")
-        .bind(new_post_info.clone().title)
-        .bind(new_post_info.clone().contents)
-        .bind(user_id)
-        .bind(new_post_info.clone().anonymous)
-        .bind(new_post_info.clone().limited_visibility)
-        .bind(new_post_info.clone().classid)
-        .bind(new_post_info.clone().private)
-        .fetch_one(&pool)
+    let post = create_post(&pool, &new_post_info, user_id)
         .await
-        .expect("failed adding post");
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let draft = DraftContext::NewPost { class_id: new_post_info.classid };
+    if let Err(e) = discard_draft(&pool, user_id, draft).await {
+        error!("Failed to discard draft of new post: {:?}", e);
+    }
 
     if new_post_info.ai_response {
         let class_description = get_class_description(new_post_info.classid).await?;
@@ -123,7 +466,9 @@ pub async fn add_post(new_post_info: AddPostInfo, user_id: i32) -> Result<Post,
         let reply_info = AddReplyInfo {
             post_id: post.post_id,
             anonymous: false,
+            fully_anonymous: false,
             contents: ai_response,
+            attachment_ids: vec![],
         };
 
         add_reply(reply_info, "EncampusAssistant".to_string()).await?;
@@ -144,6 +489,20 @@ pub async fn resolve_post(post_id: i32, status: bool) -> Result<(), ServerFnErro
         .await
         .expect("Cannot resolve post");
 
+    if status {
+        let actor_id = match use_context::<AuthenticationSession>() {
+            Some(auth_session) => auth_session
+                .get_authentication()
+                .await
+                .get_user()
+                .map(|user| user.id),
+            None => None,
+        };
+        if let Err(e) = notify_post_watchers(&pool, post_id, actor_id, "Resolved:").await {
+            error!("Failed to notify watchers of resolved post: {:?}", e);
+        }
+    }
+
     Ok(())
 }
 
@@ -175,43 +534,6 @@ pub async fn remove_post(post_id: i32, user_id: i32) -> Result<(), ServerFnError
     Ok(())
 }
 
-#[server(FilterPosts)]
-pub async fn get_search_posts(
-    class_id: i32,
-    user_id: i32,
-    filter_keyword: String,
-) -> Result<HashSet<i32>, ServerFnError> {
-    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
-        "Unable to complete Request".to_string(),
-    ))?;
-
-    let posts: Vec<i32> = sqlx::query_scalar("
-        SELECT
-            postid
-        FROM posts
-        WHERE to_tsvector(title || ' ' || CONTENTS) @@ to_tsquery($3)
-        AND classid = $1
-        AND removed = FALSE
-        AND (PRIVATE = FALSE
-            OR (authorid = $2
-                AND PRIVATE = TRUE)
-            OR (
-                    (SELECT instructorid
-                    FROM classes
-                    WHERE courseid = $1) = $2))
-        ORDER BY timestamp DESC")
-        .bind(class_id)
-        .bind(user_id)
-        .bind(filter_keyword)
-        .fetch_all(&pool)
-        .await
-        .expect("No posts found");
-
-    let set_of_filtered_post_ids: HashSet<i32> = posts.into_iter().collect();
-
-    Ok(set_of_filtered_post_ids)
-}
-
 #[server(EditPost)]
 pub async fn edit_post(
     post_id: i32,
@@ -220,6 +542,9 @@ pub async fn edit_post(
     user_id: i32,
     private: bool,
     anonymous: bool,
+    fully_anonymous: bool,
+    audience: Vec<i32>,
+    audience_groups: Vec<i32>,
 ) -> Result<(), ServerFnError> {
     let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
         "Unable to complete Request".to_string(),
@@ -232,15 +557,61 @@ pub async fn edit_post(
         .expect("Cannot get author id");
 
     if author_id == user_id {
-        sqlx::query("update posts set title = $1, contents = $2, private = $3, anonymous = $4 where postid = $5")
-            .bind(new_title)
-            .bind(new_contents)
+        let class_id = get_post_class_id(&pool, post_id)
+            .await
+            .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+        let (new_contents, mentioned) =
+            prepare_authored_contents(&pool, user_id, class_id, &new_contents)
+                .await
+                .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+        // A post already hidden from instructors may stay hidden after the class stops allowing it
+        let was_fully_anonymous: bool =
+            sqlx::query_scalar("select fully_anonymous from posts where postid = $1")
+                .bind(post_id)
+                .fetch_one(&pool)
+                .await
+                .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+        let fully_anonymous = anonymous
+            && fully_anonymous
+            && (was_fully_anonymous
+                || class_allows_full_anonymity(&pool, class_id)
+                    .await
+                    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?);
+        sqlx::query("update posts set private = $1, anonymous = $2, fully_anonymous = $3 where postid = $4")
             .bind(private)
             .bind(anonymous)
+            .bind(fully_anonymous)
             .bind(post_id)
             .execute(&pool)
             .await
             .expect("Cannot edit post");
+        set_post_audience(&pool, post_id, &audience, &audience_groups)
+            .await
+            .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+        // Only changes to the title or contents go in the edit history
+        let (title, contents): (String, String) =
+            sqlx::query_as("select title, contents from posts where postid = $1")
+                .bind(post_id)
+                .fetch_one(&pool)
+                .await
+                .expect("Cannot get post contents");
+        if title != new_title || contents != new_contents {
+            record_post_edit(&pool, post_id, user_id, &new_title, &new_contents)
+                .await
+                .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+            attach_inline_images(&pool, &new_contents, user_id, Some(post_id), None)
+                .await
+                .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+            if let Err(e) =
+                notify_mentioned_users(&pool, post_id, user_id, &mentioned, &contents).await
+            {
+                error!("Failed to notify users mentioned in edited post: {:?}", e);
+            }
+        }
+        if let Err(e) = discard_draft(&pool, user_id, DraftContext::EditPost { post_id }).await {
+            error!("Failed to discard draft of post edit: {:?}", e);
+        }
     }
     Ok(())
 }
@@ -261,6 +632,43 @@ pub async fn endorse_post(post_id: i32, status: bool) -> Result<(), ServerFnErro
     Ok(())
 }
 
+/**
+ * Pin or unpin a post to the top of its class. Only instructors and TAs may pin.
+ */
+#[server(PinPost)]
+pub async fn pin_post(
+    post_id: i32,
+    user_id: i32,
+    pinned: bool,
+    pinned_until: Option<chrono::NaiveDateTime>,
+) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let ClassId(class_id) = sqlx::query_as("select classid from posts where postid = $1")
+        .bind(post_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| ServerFnError::<NoCustomError>::ServerError("Post not found".to_string()))?;
+
+    if !check_user_is_instructor(user_id, class_id).await? {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "User does not have access to pin".to_string(),
+        ));
+    }
+
+    sqlx::query("update posts set pinned = $1, pinned_until = $2 where postid = $3")
+        .bind(pinned)
+        .bind(if pinned { pinned_until } else { None })
+        .bind(post_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(())
+}
+
 #[server(BumpPost)]
 pub async fn bump_post(post_id: i32) -> Result<(), ServerFnError> {
     use leptos::{server_fn::error::NoCustomError, use_context};
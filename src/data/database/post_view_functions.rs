@@ -0,0 +1,68 @@
+/**
+ * Server functions tracking when each user last viewed each post
+ */
+use leptos::{server, ServerFnError};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+    }
+}
+
+/**
+ * Record that the user is viewing a post, returning when they last viewed it before now
+ */
+#[server(MarkPostViewed)]
+pub async fn mark_post_viewed(
+    post_id: i32,
+    user_id: i32,
+) -> Result<Option<chrono::NaiveDateTime>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let previous_view: Option<chrono::NaiveDateTime> = sqlx::query_scalar(
+        "SELECT last_viewed FROM post_views WHERE postid = $1 AND userid = $2",
+    )
+    .bind(post_id)
+    .bind(user_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    sqlx::query(
+        "INSERT INTO post_views (postid, userid, last_viewed) VALUES ($1, $2, CURRENT_TIMESTAMP)
+        ON CONFLICT (postid, userid) DO UPDATE SET last_viewed = CURRENT_TIMESTAMP",
+    )
+    .bind(post_id)
+    .bind(user_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(previous_view)
+}
+
+/**
+ * Mark every post in a class as read for the user
+ */
+#[server(MarkClassRead)]
+pub async fn mark_class_read(class_id: i32, user_id: i32) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    sqlx::query(
+        "INSERT INTO post_views (postid, userid, last_viewed)
+        SELECT postid, $2, CURRENT_TIMESTAMP FROM posts WHERE classid = $1 AND removed = false
+        ON CONFLICT (postid, userid) DO UPDATE SET last_viewed = CURRENT_TIMESTAMP",
+    )
+    .bind(class_id)
+    .bind(user_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+/**
+ * Server functions for the lightweight reactions users can leave on posts and replies
+ */
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use crate::data::database::post_functions::check_user_can_see_post;
+
+        /// The count of one reaction on one post or reply
+        #[derive(sqlx::FromRow)]
+        struct ReactionRow {
+            target_id: i32,
+            reaction: String,
+            count: i64,
+            reacted: bool,
+        }
+
+        /// Table and id column holding the reactions on a kind of target
+        fn reaction_table(target: ReactionTarget) -> (&'static str, &'static str, i32) {
+            match target {
+                ReactionTarget::Post(post_id) => ("post_reactions", "postid", post_id),
+                ReactionTarget::Reply(reply_id) => ("reply_reactions", "replyid", reply_id),
+            }
+        }
+
+        /// Fails unless a user can see the post reacted to, or the post of the reply reacted to
+        async fn check_can_see_target(
+            pool: &PgPool,
+            target: ReactionTarget,
+            user_id: i32,
+        ) -> Result<(), ServerFnError> {
+            let post_id = match target {
+                ReactionTarget::Post(post_id) => post_id,
+                ReactionTarget::Reply(reply_id) => {
+                    sqlx::query_scalar("SELECT postid FROM replies WHERE replyid = $1")
+                        .bind(reply_id)
+                        .fetch_one(pool)
+                        .await
+                        .map_err(|_| ServerFnError::<NoCustomError>::ServerError("Reply not found".to_string()))?
+                }
+            };
+            check_user_can_see_post(pool, post_id, user_id).await
+        }
+
+        fn group_reaction_rows(rows: Vec<ReactionRow>) -> HashMap<i32, ReactionCounts> {
+            let mut counts: HashMap<i32, ReactionCounts> = HashMap::new();
+            for row in rows {
+                if let Some(reaction) = Reaction::parse(&row.reaction) {
+                    counts
+                        .entry(row.target_id)
+                        .or_default()
+                        .add(reaction, row.count, row.reacted);
+                }
+            }
+            counts
+        }
+    }
+}
+
+/**
+ * The fixed set of reactions
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum Reaction {
+    Thanks,
+    SameQuestion,
+    Confused,
+}
+
+impl Reaction {
+    pub const ALL: [Reaction; 3] = [Reaction::Thanks, Reaction::SameQuestion, Reaction::Confused];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Reaction::Thanks => "thanks",
+            Reaction::SameQuestion => "same_question",
+            Reaction::Confused => "confused",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Reaction> {
+        Reaction::ALL
+            .into_iter()
+            .find(|reaction| reaction.as_str() == value)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Reaction::Thanks => "Thanks",
+            Reaction::SameQuestion => "Same question",
+            Reaction::Confused => "Confused",
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            Reaction::Thanks => "🙏",
+            Reaction::SameQuestion => "✋",
+            Reaction::Confused => "😕",
+        }
+    }
+}
+
+/**
+ * A post or reply that can be reacted to
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum ReactionTarget {
+    Post(i32),
+    Reply(i32),
+}
+
+/**
+ * Struct to hold the reaction counts on a post or reply, and which reactions the requesting user left
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct ReactionCounts {
+    pub thanks: i64,
+    pub same_question: i64,
+    pub confused: i64,
+    pub reacted: Vec<Reaction>,
+}
+
+impl ReactionCounts {
+    pub fn count(&self, reaction: Reaction) -> i64 {
+        match reaction {
+            Reaction::Thanks => self.thanks,
+            Reaction::SameQuestion => self.same_question,
+            Reaction::Confused => self.confused,
+        }
+    }
+
+    pub fn has_reacted(&self, reaction: Reaction) -> bool {
+        self.reacted.contains(&reaction)
+    }
+
+    fn add(&mut self, reaction: Reaction, count: i64, reacted: bool) {
+        match reaction {
+            Reaction::Thanks => self.thanks += count,
+            Reaction::SameQuestion => self.same_question += count,
+            Reaction::Confused => self.confused += count,
+        }
+        if reacted && !self.has_reacted(reaction) {
+            self.reacted.push(reaction);
+        }
+    }
+}
+
+/**
+ * Get the reaction counts on a post or reply
+ */
+#[server(GetReactions)]
+pub async fn get_reactions(
+    target: ReactionTarget,
+    user_id: i32,
+) -> Result<ReactionCounts, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_can_see_target(&pool, target, user_id).await?;
+    let (table, id_column, target_id) = reaction_table(target);
+    let rows: Vec<ReactionRow> = sqlx::query_as(&format!(
+        "SELECT {id_column} as target_id, reaction, count(*) as count, bool_or(userid = $2) as reacted
+        FROM {table} WHERE {id_column} = $1
+        GROUP BY {id_column}, reaction"
+    ))
+    .bind(target_id)
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(group_reaction_rows(rows)
+        .remove(&target_id)
+        .unwrap_or_default())
+}
+
+/**
+ * Get the reaction counts on every reply to a post, by reply id. Replies without reactions are left out.
+ */
+#[server(GetReplyReactions)]
+pub async fn get_reply_reactions(
+    post_id: i32,
+    user_id: i32,
+) -> Result<HashMap<i32, ReactionCounts>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_user_can_see_post(&pool, post_id, user_id).await?;
+    let rows: Vec<ReactionRow> = sqlx::query_as(
+        "SELECT reply_reactions.replyid as target_id, reaction, count(*) as count,
+            bool_or(reply_reactions.userid = $2) as reacted
+        FROM reply_reactions JOIN replies ON reply_reactions.replyid = replies.replyid
+        WHERE replies.postid = $1 AND replies.removed = false
+        GROUP BY reply_reactions.replyid, reaction",
+    )
+    .bind(post_id)
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(group_reaction_rows(rows))
+}
+
+/**
+ * Add a reaction to a post or reply, or take it back if the user already left it. Returns the new counts.
+ */
+#[server(ToggleReaction)]
+pub async fn toggle_reaction(
+    target: ReactionTarget,
+    user_id: i32,
+    reaction: Reaction,
+) -> Result<ReactionCounts, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_can_see_target(&pool, target, user_id).await?;
+    let (table, id_column, target_id) = reaction_table(target);
+    let removed = sqlx::query(&format!(
+        "DELETE FROM {table} WHERE {id_column} = $1 AND userid = $2 AND reaction = $3"
+    ))
+    .bind(target_id)
+    .bind(user_id)
+    .bind(reaction.as_str())
+    .execute(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?
+    .rows_affected();
+
+    if removed == 0 {
+        sqlx::query(&format!(
+            "INSERT INTO {table} ({id_column}, userid, reaction) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING"
+        ))
+        .bind(target_id)
+        .bind(user_id)
+        .bind(reaction.as_str())
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    }
+
+    get_reactions(target, user_id).await
+}
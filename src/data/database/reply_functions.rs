@@ -6,7 +6,12 @@ cfg_if::cfg_if! {
         use leptos::{server_fn::error::NoCustomError, use_context};
         use sqlx::postgres::PgPool;
         use crate::data::database::user_functions::UserId;
-        use crate::data::database::class_functions::{check_user_is_instructor, ClassId};
+        use crate::data::database::class_functions::{check_user_is_instructor, class_allows_full_anonymity, get_post_class_id, ClassId};
+        use crate::data::database::mention_functions::notify_mentioned_users;
+        use crate::data::database::draft_functions::{discard_draft, DraftContext};
+        use crate::data::database::watch_functions::{add_post_watcher, notify_post_watchers};
+        use crate::data::database::attachment_functions::{attach_inline_images, attach_uploads};
+        use crate::data::content::prepare_authored_contents;
     }
 }
 
@@ -61,6 +66,20 @@ pub async fn approve_reply(reply_id: i32, user_id: i32, status: bool) -> Result<
             .execute(&pool)
             .await
             .expect("Cannot approve reply");
+
+        if status {
+            let post_id: i32 = sqlx::query_scalar("select postid from replies where replyid = $1")
+                .bind(reply_id)
+                .fetch_one(&pool)
+                .await
+                .expect("Cannot get post id");
+            if let Err(e) =
+                notify_post_watchers(&pool, post_id, Some(user_id), "Instructor approved a reply on")
+                    .await
+            {
+                logging::error!("Failed to notify watchers of approved reply: {:?}", e);
+            }
+        }
     } else {
         return Err(ServerFnError::<NoCustomError>::ServerError(
             "User does not have access to approve".to_string(),
@@ -82,23 +101,38 @@ pub async fn add_reply(reply_info: AddReplyInfo, user: String) -> Result<Reply,
         .await
         .expect("select should work");
 
+    let class_id = get_post_class_id(&pool, reply_info.post_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let (contents, mentioned) =
+        prepare_authored_contents(&pool, user_id.0, class_id, &reply_info.contents)
+            .await
+            .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let fully_anonymous = reply_info.anonymous
+        && reply_info.fully_anonymous
+        && class_allows_full_anonymity(&pool, class_id)
+            .await
+            .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
     let newreply: Reply = sqlx::query_as(
-        "INSERT INTO replies (time, authorid, postid, anonymous, contents) 
-                        VALUES (CURRENT_TIMESTAMP, $1, $2, $3, $4)
+        "INSERT INTO replies (time, authorid, postid, anonymous, fully_anonymous, contents) 
+                        VALUES (CURRENT_TIMESTAMP, $1, $2, $3, $4, $5)
                 RETURNING                 
                 time, 
                 contents,
                 'You' as author_name, 
                 authorid as author_id,
                 anonymous,
+                fully_anonymous,
                 replyid as reply_id,
                 removed,
-                approved;",
+                approved,
+                edited_at;",
     )
     .bind(user_id.0)
     .bind(reply_info.post_id)
     .bind(reply_info.anonymous)
-    .bind(reply_info.contents)
+    .bind(fully_anonymous)
+    .bind(&contents)
     .fetch_one(&pool)
     .await
     .map_err(|db_error| {
@@ -109,5 +143,37 @@ pub async fn add_reply(reply_info: AddReplyInfo, user: String) -> Result<Reply,
         ServerFnError::<NoCustomError>::ServerError("Unable to add Reply".to_string())
     })?;
 
+    attach_uploads(
+        &pool,
+        &reply_info.attachment_ids,
+        user_id.0,
+        None,
+        Some(newreply.reply_id),
+    )
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    attach_inline_images(&pool, &contents, user_id.0, None, Some(newreply.reply_id))
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    // Repliers automatically watch the post they replied to
+    if let Err(e) = add_post_watcher(&pool, reply_info.post_id, user_id.0).await {
+        logging::error!("Failed to watch replied post: {:?}", e);
+    }
+    if let Err(e) =
+        notify_post_watchers(&pool, reply_info.post_id, Some(user_id.0), "New reply on").await
+    {
+        logging::error!("Failed to notify watchers of new reply: {:?}", e);
+    }
+    if let Err(e) =
+        notify_mentioned_users(&pool, reply_info.post_id, user_id.0, &mentioned, "").await
+    {
+        logging::error!("Failed to notify users mentioned in new reply: {:?}", e);
+    }
+    let draft = DraftContext::Reply { post_id: reply_info.post_id };
+    if let Err(e) = discard_draft(&pool, user_id.0, draft).await {
+        logging::error!("Failed to discard draft of reply: {:?}", e);
+    }
+
     Ok(newreply)
 }
@@ -0,0 +1,324 @@
+/**
+ * Server functions for editing replies and for the edit history of posts and replies. Each edit is
+ * stored as a revision; instructors can roll a post or reply back to any earlier revision.
+ */
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use crate::data::database::class_functions::{check_user_is_instructor, get_post_class_id, ClassId};
+        use crate::data::database::mention_functions::notify_mentioned_users;
+        use crate::data::database::attachment_functions::attach_inline_images;
+        use crate::data::database::post_functions::check_user_can_see_post;
+        use crate::data::content::prepare_authored_contents;
+        use crate::pages::view_class_posts::focused_post::VIEWER_SEES_AUTHOR;
+
+        /// Saves a new title and contents for a post and records the edit as a revision. The first
+        /// edit also records the original version, credited to the author at the time it was posted.
+        pub async fn record_post_edit(
+            pool: &PgPool,
+            post_id: i32,
+            editor_id: i32,
+            title: &str,
+            contents: &str,
+        ) -> Result<chrono::NaiveDateTime, sqlx::Error> {
+            let mut tx = pool.begin().await?;
+
+            sqlx::query(
+                "INSERT INTO post_revisions (postid, title, contents, editorid, time)
+                SELECT postid, title, contents, authorid, timestamp FROM posts
+                WHERE postid = $1 AND NOT EXISTS(SELECT 1 FROM post_revisions WHERE postid = $1)",
+            )
+            .bind(post_id)
+            .execute(&mut *tx)
+            .await?;
+
+            let edited_at: chrono::NaiveDateTime = sqlx::query_scalar(
+                "UPDATE posts SET title = $2, contents = $3, edited_at = CURRENT_TIMESTAMP
+                WHERE postid = $1 RETURNING edited_at",
+            )
+            .bind(post_id)
+            .bind(title)
+            .bind(contents)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query("INSERT INTO post_revisions (postid, title, contents, editorid, time) VALUES ($1, $2, $3, $4, $5)")
+                .bind(post_id)
+                .bind(title)
+                .bind(contents)
+                .bind(editor_id)
+                .bind(edited_at)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(edited_at)
+        }
+
+        /// Saves new contents for a reply and records the edit as a revision, the same way as
+        /// `record_post_edit`
+        pub async fn record_reply_edit(
+            pool: &PgPool,
+            reply_id: i32,
+            editor_id: i32,
+            contents: &str,
+        ) -> Result<chrono::NaiveDateTime, sqlx::Error> {
+            let mut tx = pool.begin().await?;
+
+            sqlx::query(
+                "INSERT INTO reply_revisions (replyid, contents, editorid, time)
+                SELECT replyid, contents, authorid, time FROM replies
+                WHERE replyid = $1 AND NOT EXISTS(SELECT 1 FROM reply_revisions WHERE replyid = $1)",
+            )
+            .bind(reply_id)
+            .execute(&mut *tx)
+            .await?;
+
+            let edited_at: chrono::NaiveDateTime = sqlx::query_scalar(
+                "UPDATE replies SET contents = $2, edited_at = CURRENT_TIMESTAMP
+                WHERE replyid = $1 RETURNING edited_at",
+            )
+            .bind(reply_id)
+            .bind(contents)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query("INSERT INTO reply_revisions (replyid, contents, editorid, time) VALUES ($1, $2, $3, $4)")
+                .bind(reply_id)
+                .bind(contents)
+                .bind(editor_id)
+                .bind(edited_at)
+                .execute(&mut *tx)
+                .await?;
+
+            tx.commit().await?;
+            Ok(edited_at)
+        }
+
+        /// Every version of a post or reply, newest first. The author of an anonymous post or
+        /// reply is credited as "Anonymous" unless the viewer may see who wrote it.
+        async fn fetch_revisions(
+            pool: &PgPool,
+            target: RevisionTarget,
+            viewer_id: i32,
+        ) -> Result<Vec<Revision>, sqlx::Error> {
+            match target {
+                RevisionTarget::Post(post_id) => {
+                    sqlx::query_as(&format!(
+                        "SELECT revisionid as revision_id, post_revisions.title, post_revisions.contents,
+                            CASE WHEN post_revisions.editorid = posts.authorid AND posts.authorid <> $2
+                                AND NOT {VIEWER_SEES_AUTHOR} THEN 'Anonymous'
+                                ELSE users.firstname || ' ' || users.lastname
+                            END as editor_name,
+                            time
+                        FROM post_revisions
+                        JOIN users ON post_revisions.editorid = users.id
+                        JOIN posts ON post_revisions.postid = posts.postid
+                        WHERE post_revisions.postid = $1
+                        ORDER BY time DESC, revisionid DESC"
+                    ))
+                    .bind(post_id)
+                    .bind(viewer_id)
+                    .fetch_all(pool)
+                    .await
+                }
+                RevisionTarget::Reply(reply_id) => {
+                    sqlx::query_as(&format!(
+                        "SELECT revisionid as revision_id, NULL::TEXT as title, reply_revisions.contents,
+                            CASE WHEN reply_revisions.editorid = replies.authorid AND replies.authorid <> $2
+                                AND NOT {VIEWER_SEES_AUTHOR} THEN 'Anonymous'
+                                ELSE users.firstname || ' ' || users.lastname
+                            END as editor_name,
+                            reply_revisions.time
+                        FROM reply_revisions
+                        JOIN users ON reply_revisions.editorid = users.id
+                        JOIN replies ON reply_revisions.replyid = replies.replyid
+                        JOIN (SELECT postid, classid FROM posts) posts ON replies.postid = posts.postid
+                        WHERE reply_revisions.replyid = $1
+                        ORDER BY reply_revisions.time DESC, revisionid DESC"
+                    ))
+                    .bind(reply_id)
+                    .bind(viewer_id)
+                    .fetch_all(pool)
+                    .await
+                }
+            }
+        }
+    }
+}
+
+/**
+ * A post or reply with an edit history
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum RevisionTarget {
+    Post(i32),
+    Reply(i32),
+}
+
+/**
+ * Struct to hold one version of a post or reply. Replies have no title.
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct Revision {
+    pub revision_id: i32,
+    pub title: Option<String>,
+    pub contents: String,
+    pub editor_name: String,
+    pub time: chrono::NaiveDateTime,
+}
+
+/**
+ * Edit the contents of a reply. Only the reply's author may edit it.
+ */
+#[server(EditReply)]
+pub async fn edit_reply(
+    reply_id: i32,
+    user_id: i32,
+    contents: String,
+) -> Result<chrono::NaiveDateTime, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    if contents.is_empty() {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "Reply cannot be empty".to_string(),
+        ));
+    }
+
+    let (author_id, post_id, previous_contents): (i32, i32, String) = sqlx::query_as(
+        "SELECT authorid, postid, contents FROM replies WHERE replyid = $1 AND removed = false",
+    )
+    .bind(reply_id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| ServerFnError::<NoCustomError>::ServerError("Reply not found".to_string()))?;
+
+    if author_id != user_id {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "Only the author can edit a reply".to_string(),
+        ));
+    }
+
+    let class_id = get_post_class_id(&pool, post_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let (contents, mentioned) = prepare_authored_contents(&pool, user_id, class_id, &contents)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    let edited_at = record_reply_edit(&pool, reply_id, user_id, &contents)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    attach_inline_images(&pool, &contents, user_id, None, Some(reply_id))
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    if let Err(e) =
+        notify_mentioned_users(&pool, post_id, user_id, &mentioned, &previous_contents).await
+    {
+        leptos::logging::error!("Failed to notify users mentioned in edited reply: {:?}", e);
+    }
+
+    Ok(edited_at)
+}
+
+/**
+ * Get every version of a post or reply a user can see, newest first. Posts and replies that were
+ * never edited have no revisions.
+ */
+#[server(GetRevisions)]
+pub async fn get_revisions(
+    target: RevisionTarget,
+    user_id: i32,
+) -> Result<Vec<Revision>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let post_id = match target {
+        RevisionTarget::Post(post_id) => post_id,
+        RevisionTarget::Reply(reply_id) => {
+            sqlx::query_scalar("SELECT postid FROM replies WHERE replyid = $1")
+                .bind(reply_id)
+                .fetch_one(&pool)
+                .await
+                .map_err(|_| ServerFnError::<NoCustomError>::ServerError("Reply not found".to_string()))?
+        }
+    };
+    check_user_can_see_post(&pool, post_id, user_id).await?;
+
+    fetch_revisions(&pool, target, user_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))
+}
+
+/**
+ * Restore a post or reply to an earlier revision. The rollback is recorded as a new revision, so
+ * it can be undone the same way. Only instructors and TAs may roll back.
+ */
+#[server(RollbackRevision)]
+pub async fn rollback_revision(
+    target: RevisionTarget,
+    revision_id: i32,
+    user_id: i32,
+) -> Result<Revision, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let class_query = match target {
+        RevisionTarget::Post(post_id) => {
+            sqlx::query_as("SELECT classid FROM posts WHERE postid = $1").bind(post_id)
+        }
+        RevisionTarget::Reply(reply_id) => sqlx::query_as(
+            "SELECT classid FROM posts WHERE postid = (SELECT postid FROM replies WHERE replyid = $1)",
+        )
+        .bind(reply_id),
+    };
+    let ClassId(class_id) = class_query
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| ServerFnError::<NoCustomError>::ServerError("Post not found".to_string()))?;
+
+    if !check_user_is_instructor(user_id, class_id).await? {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "User does not have access to roll back edits".to_string(),
+        ));
+    }
+
+    let revision = fetch_revisions(&pool, target, user_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?
+        .into_iter()
+        .find(|revision| revision.revision_id == revision_id)
+        .ok_or(ServerFnError::<NoCustomError>::ServerError(
+            "Revision not found".to_string(),
+        ))?;
+
+    let edited_at = match target {
+        RevisionTarget::Post(post_id) => {
+            record_post_edit(
+                &pool,
+                post_id,
+                user_id,
+                revision.title.as_deref().unwrap_or_default(),
+                &revision.contents,
+            )
+            .await
+        }
+        RevisionTarget::Reply(reply_id) => {
+            record_reply_edit(&pool, reply_id, user_id, &revision.contents).await
+        }
+    }
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(Revision {
+        time: edited_at,
+        ..revision
+    })
+}
@@ -0,0 +1,400 @@
+/**
+ * Server functions for scheduled announcements and posts, which instructors write ahead of time to
+ * be published later, and the scheduler task publishing them once they are due
+ */
+use crate::pages::view_class_posts::create_post::AddPostInfo;
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::logging::error;
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use std::time::Duration;
+        use crate::data::database::announcement_functions::notify_announcement;
+        use crate::data::database::class_functions::check_user_is_instructor;
+        use crate::data::database::draft_functions::{discard_draft, DraftContext};
+        use crate::data::database::post_functions::{after_post_created, insert_post};
+
+        /// How often the scheduler looks for announcements and posts that are due
+        const SCHEDULER_INTERVAL: Duration = Duration::from_secs(30);
+
+        /// A scheduled announcement or post as stored
+        #[derive(sqlx::FromRow)]
+        struct ScheduledRow {
+            kind: String,
+            id: i32,
+            class_id: i32,
+            title: String,
+            contents: String,
+            markdown: bool,
+            publish_at: chrono::NaiveDateTime,
+        }
+
+        impl ScheduledRow {
+            fn into_item(self) -> Option<ScheduledItem> {
+                Some(ScheduledItem {
+                    kind: ScheduledKind::from_stored(&self.kind)?,
+                    id: self.id,
+                    class_id: self.class_id,
+                    title: self.title,
+                    contents: self.contents,
+                    markdown: self.markdown,
+                    publish_at: self.publish_at,
+                })
+            }
+        }
+
+        /// An announcement the scheduler has just published
+        #[derive(sqlx::FromRow)]
+        struct PublishedAnnouncement {
+            announcement_id: i32,
+            class_id: i32,
+            author_id: i32,
+            title: String,
+        }
+
+        /// A scheduled post that is due
+        #[derive(sqlx::FromRow)]
+        struct DuePost {
+            scheduled_post_id: i32,
+            class_id: i32,
+            author_id: i32,
+            title: String,
+            contents: String,
+            anonymous: bool,
+            fully_anonymous: bool,
+            private: bool,
+            audience: Vec<i32>,
+            audience_groups: Vec<i32>,
+            attachment_ids: Vec<i32>,
+        }
+
+        /// Publishes every scheduled announcement and post whose time has come, sending the
+        /// notifications they would have sent had they been posted then
+        pub async fn publish_due_items(pool: &PgPool) -> Result<(), sqlx::Error> {
+            let announcements: Vec<PublishedAnnouncement> = sqlx::query_as(
+                "UPDATE announcements SET time = publish_at, publish_at = NULL
+                WHERE publish_at <= CURRENT_TIMESTAMP
+                RETURNING announcementid as announcement_id, classid as class_id, authorid as author_id, title",
+            )
+            .fetch_all(pool)
+            .await?;
+            for announcement in announcements {
+                if let Err(e) = notify_announcement(
+                    pool,
+                    announcement.announcement_id,
+                    announcement.class_id,
+                    announcement.author_id,
+                    &announcement.title,
+                )
+                .await
+                {
+                    error!("Failed to notify class of scheduled announcement: {:?}", e);
+                }
+            }
+
+            // Each due post is claimed by deleting it in the transaction that publishes it, so
+            // another instance of the app can't publish it too. One that fails to publish stays
+            // scheduled and is tried again next time.
+            let mut failed: Vec<i32> = Vec::new();
+            loop {
+                let mut tx = pool.begin().await?;
+                let post: Option<DuePost> = sqlx::query_as(
+                    "DELETE FROM scheduled_posts WHERE scheduledpostid = (
+                        SELECT scheduledpostid FROM scheduled_posts
+                        WHERE publish_at <= CURRENT_TIMESTAMP AND scheduledpostid <> ALL($1)
+                        ORDER BY publish_at
+                        LIMIT 1
+                        FOR UPDATE SKIP LOCKED
+                    )
+                    RETURNING scheduledpostid as scheduled_post_id, classid as class_id, authorid as author_id,
+                        title, contents, anonymous, fully_anonymous, private, audience, audience_groups, attachment_ids",
+                )
+                .bind(&failed)
+                .fetch_optional(&mut *tx)
+                .await?;
+                let Some(post) = post else {
+                    break;
+                };
+                let post_info = AddPostInfo {
+                    title: post.title,
+                    contents: post.contents,
+                    anonymous: post.anonymous,
+                    fully_anonymous: post.fully_anonymous,
+                    limited_visibility: false,
+                    classid: post.class_id,
+                    private: post.private,
+                    audience: post.audience,
+                    audience_groups: post.audience_groups,
+                    ai_response: false,
+                    attachment_ids: post.attachment_ids,
+                    publish_at: None,
+                };
+                match insert_post(pool, &mut tx, &post_info, post.author_id).await {
+                    Ok((published, mentioned)) => {
+                        tx.commit().await?;
+                        after_post_created(pool, published.post_id, post.author_id, &mentioned)
+                            .await;
+                    }
+                    Err(e) => {
+                        error!("Failed to publish scheduled post: {:?}", e);
+                        failed.push(post.scheduled_post_id);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Starts the background task publishing scheduled announcements and posts
+        pub fn spawn_scheduler(pool: PgPool) {
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(SCHEDULER_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = publish_due_items(&pool).await {
+                        error!("Failed to publish scheduled announcements and posts: {:?}", e);
+                    }
+                }
+            });
+        }
+
+        /// The class a scheduled announcement or post belongs to, if it is still scheduled
+        async fn scheduled_item_class_id(
+            pool: &PgPool,
+            kind: ScheduledKind,
+            id: i32,
+        ) -> Result<Option<i32>, sqlx::Error> {
+            let query = match kind {
+                ScheduledKind::Announcement => {
+                    "SELECT classid FROM announcements WHERE announcementid = $1 AND publish_at IS NOT NULL"
+                }
+                ScheduledKind::Post => "SELECT classid FROM scheduled_posts WHERE scheduledpostid = $1",
+            };
+            sqlx::query_scalar(query).bind(id).fetch_optional(pool).await
+        }
+
+        /// Checks that a user may change a scheduled announcement or post
+        async fn check_can_manage(
+            pool: &PgPool,
+            user_id: i32,
+            kind: ScheduledKind,
+            id: i32,
+        ) -> Result<(), ServerFnError> {
+            let class_id = scheduled_item_class_id(pool, kind, id)
+                .await
+                .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?
+                .ok_or(ServerFnError::<NoCustomError>::ServerError(
+                    "Scheduled item not found. It may have been published already".to_string(),
+                ))?;
+            if !check_user_is_instructor(user_id, class_id).await? {
+                return Err(ServerFnError::<NoCustomError>::ServerError(
+                    "User does not have access to scheduled items".to_string(),
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+/**
+ * Whether a scheduled item is an announcement or a post
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum ScheduledKind {
+    Announcement,
+    Post,
+}
+
+impl ScheduledKind {
+    pub fn from_stored(kind: &str) -> Option<ScheduledKind> {
+        match kind {
+            "announcement" => Some(ScheduledKind::Announcement),
+            "post" => Some(ScheduledKind::Post),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Struct to hold an announcement or post waiting to be published
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ScheduledItem {
+    pub kind: ScheduledKind,
+    /// The announcement id, or the id of the scheduled post
+    pub id: i32,
+    pub class_id: i32,
+    pub title: String,
+    /// The contents as written, before they are prepared for display
+    pub contents: String,
+    /// Whether an announcement was written in Markdown
+    pub markdown: bool,
+    pub publish_at: chrono::NaiveDateTime,
+}
+
+/**
+ * Get the announcements and posts waiting to be published in a class, soonest first
+ */
+#[server(GetScheduledItems)]
+pub async fn get_scheduled_items(
+    class_id: i32,
+    user_id: i32,
+) -> Result<Vec<ScheduledItem>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    if !check_user_is_instructor(user_id, class_id).await? {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "User does not have access to scheduled items".to_string(),
+        ));
+    }
+
+    let rows: Vec<ScheduledRow> = sqlx::query_as(
+        "SELECT 'announcement' as kind, announcementid as id, classid as class_id, title, contents,
+            markdown, publish_at
+        FROM announcements WHERE classid = $1 AND publish_at IS NOT NULL
+        UNION ALL
+        SELECT 'post', scheduledpostid, classid, title, contents, false, publish_at
+        FROM scheduled_posts WHERE classid = $1
+        ORDER BY publish_at, id",
+    )
+    .bind(class_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(rows.into_iter().filter_map(ScheduledRow::into_item).collect())
+}
+
+/**
+ * Schedule an instructor's post to be published at its `publish_at` time
+ */
+#[server(SchedulePost)]
+pub async fn schedule_post(
+    new_post_info: AddPostInfo,
+    user_id: i32,
+) -> Result<ScheduledItem, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    if !check_user_is_instructor(user_id, new_post_info.classid).await? {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "Only instructors can schedule posts".to_string(),
+        ));
+    }
+    let publish_at = new_post_info
+        .publish_at
+        .filter(|publish_at| *publish_at > chrono::Utc::now().naive_utc())
+        .ok_or(ServerFnError::<NoCustomError>::ServerError(
+            "Scheduled posts need a publish time in the future".to_string(),
+        ))?;
+
+    let row: ScheduledRow = sqlx::query_as(
+        "INSERT INTO scheduled_posts (classid, authorid, title, contents, anonymous, fully_anonymous, private, audience, audience_groups, attachment_ids, publish_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING 'post' as kind, scheduledpostid as id, classid as class_id, title, contents,
+            false as markdown, publish_at",
+    )
+    .bind(new_post_info.classid)
+    .bind(user_id)
+    .bind(&new_post_info.title)
+    .bind(&new_post_info.contents)
+    .bind(new_post_info.anonymous)
+    .bind(new_post_info.anonymous && new_post_info.fully_anonymous)
+    .bind(new_post_info.private)
+    .bind(&new_post_info.audience)
+    .bind(&new_post_info.audience_groups)
+    .bind(&new_post_info.attachment_ids)
+    .bind(publish_at)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    let draft = DraftContext::NewPost { class_id: new_post_info.classid };
+    if let Err(e) = discard_draft(&pool, user_id, draft).await {
+        error!("Failed to discard draft of scheduled post: {:?}", e);
+    }
+
+    row.into_item().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))
+}
+
+/**
+ * Change the title, contents or publish time of a scheduled announcement or post
+ */
+#[server(UpdateScheduledItem)]
+pub async fn update_scheduled_item(
+    user_id: i32,
+    kind: ScheduledKind,
+    id: i32,
+    title: String,
+    contents: String,
+    publish_at: chrono::NaiveDateTime,
+) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_can_manage(&pool, user_id, kind, id).await?;
+    if publish_at <= chrono::Utc::now().naive_utc() {
+        return Err(ServerFnError::<NoCustomError>::ServerError(
+            "Scheduled items need a publish time in the future".to_string(),
+        ));
+    }
+
+    let query = match kind {
+        ScheduledKind::Announcement => {
+            "UPDATE announcements SET title = $2, contents = $3, publish_at = $4
+            WHERE announcementid = $1 AND publish_at IS NOT NULL"
+        }
+        ScheduledKind::Post => {
+            "UPDATE scheduled_posts SET title = $2, contents = $3, publish_at = $4
+            WHERE scheduledpostid = $1"
+        }
+    };
+    sqlx::query(query)
+        .bind(id)
+        .bind(title)
+        .bind(contents)
+        .bind(publish_at)
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(())
+}
+
+/**
+ * Cancel a scheduled announcement or post so that it is never published
+ */
+#[server(CancelScheduledItem)]
+pub async fn cancel_scheduled_item(
+    user_id: i32,
+    kind: ScheduledKind,
+    id: i32,
+) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    check_can_manage(&pool, user_id, kind, id).await?;
+
+    let query = match kind {
+        ScheduledKind::Announcement => {
+            "DELETE FROM announcements WHERE announcementid = $1 AND publish_at IS NOT NULL"
+        }
+        ScheduledKind::Post => "DELETE FROM scheduled_posts WHERE scheduledpostid = $1",
+    };
+    sqlx::query(query)
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(())
+}
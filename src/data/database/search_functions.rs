@@ -0,0 +1,634 @@
+/**
+ * Server functions for searching posts, replies and announcements, within one class or across
+ * every class the user belongs to.
+ *
+ * Queries are plain words plus a few operators:
+ * - `"linked list"` matches the exact phrase, `-java` excludes posts containing a word
+ * - `author:smith` or `author:"Jane Smith"` matches the post author, if the user could see who it is
+ * - `is:resolved`, `is:unresolved`, `is:private`, `is:public`, `is:endorsed`, `is:pinned`
+ *   filter on the post's state. `tag:` accepts the same values, matching the tags shown on tiles
+ * - `after:2026-09-01`, `before:2026-10-01` and `date:2026-09-01..2026-09-30` filter on when
+ *   the post was created
+ */
+use chrono::{NaiveDate, NaiveDateTime};
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use crate::data::database::post_functions::post_visible_to;
+        use std::collections::HashMap;
+        use crate::data::database::announcement_functions::announcement_visible_to;
+
+        /// Markers wrapped around matches by ts_headline. Private use characters never appear in posts.
+        const HIGHLIGHT_START: char = '\u{E000}';
+        const HIGHLIGHT_END: char = '\u{E001}';
+
+        /// ts_headline options for highlighting a short piece of text in full (e.g. a title)
+        fn headline_options_all() -> String {
+            format!("StartSel=\"{}\", StopSel=\"{}\", HighlightAll=true", HIGHLIGHT_START, HIGHLIGHT_END)
+        }
+
+        /// ts_headline options for cutting a snippet out of a longer body
+        fn headline_options_snippet() -> String {
+            format!(
+                "StartSel=\"{}\", StopSel=\"{}\", MaxFragments=2, MaxWords=25, MinWords=10, FragmentDelimiter=\" … \"",
+                HIGHLIGHT_START, HIGHLIGHT_END
+            )
+        }
+
+        /// Splits ts_headline output into plain and highlighted parts, decoding the HTML entities
+        /// left behind once tags are stripped from rich text
+        fn split_headline(headline: &str) -> Vec<HighlightedText> {
+            let mut parts = vec![];
+            let mut text = String::new();
+            for c in headline.chars() {
+                match c {
+                    HIGHLIGHT_START | HIGHLIGHT_END => {
+                        if !text.is_empty() {
+                            parts.push(HighlightedText {
+                                text: decode_entities(&text),
+                                highlighted: c == HIGHLIGHT_END,
+                            });
+                            text.clear();
+                        }
+                    }
+                    c => text.push(c),
+                }
+            }
+            if !text.is_empty() {
+                parts.push(HighlightedText { text: decode_entities(&text), highlighted: false });
+            }
+            parts
+        }
+
+        fn decode_entities(text: &str) -> String {
+            text.replace("&nbsp;", " ")
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&#39;", "'")
+                .replace("&amp;", "&")
+        }
+
+        #[derive(sqlx::FromRow)]
+        struct SearchRow {
+            post_id: i32,
+            class_id: i32,
+            class_name: String,
+            title_headline: String,
+            snippet: String,
+            matched_reply: bool,
+            resolved: bool,
+            timestamp: NaiveDateTime,
+        }
+
+        impl SearchRow {
+            fn into_result(self) -> SearchResult {
+                SearchResult {
+                    post_id: self.post_id,
+                    title: split_headline(&self.title_headline),
+                    snippet: split_headline(&self.snippet),
+                    matched_reply: self.matched_reply,
+                    resolved: self.resolved,
+                    timestamp: self.timestamp,
+                }
+            }
+        }
+
+        #[derive(sqlx::FromRow)]
+        struct AnnouncementRow {
+            announcement_id: i32,
+            class_id: i32,
+            class_name: String,
+            title_headline: String,
+            snippet: String,
+            time: NaiveDateTime,
+        }
+
+        impl AnnouncementRow {
+            fn into_result(self) -> AnnouncementSearchResult {
+                AnnouncementSearchResult {
+                    announcement_id: self.announcement_id,
+                    title: split_headline(&self.title_headline),
+                    snippet: split_headline(&self.snippet),
+                    time: self.time,
+                }
+            }
+        }
+
+        /// Searches the posts and replies the user can see, either in one class or, with no
+        /// class, every class they belong to. Best matches come first.
+        async fn search_posts(
+            pool: &PgPool,
+            class_id: Option<i32>,
+            user_id: i32,
+            query: &SearchQuery,
+        ) -> Result<Vec<SearchRow>, sqlx::Error> {
+            // websearch_to_tsquery accepts any input, so user text can't make the query fail.
+            // Matching goes through the indexed search_vector columns; HTML is only stripped for
+            // the snippets of the rows returned.
+            let rows: Vec<SearchRow> = sqlx::query_as(&format!(
+                "WITH search AS (
+                    SELECT CASE WHEN $3 = '' THEN NULL ELSE websearch_to_tsquery('english', $3) END AS query
+                ),
+                visible_posts AS (
+                    SELECT posts.postid, posts.classid, posts.title, posts.contents, posts.resolved, posts.timestamp,
+                        ts_rank(posts.search_vector, search.query) AS rank
+                    FROM posts JOIN users ON posts.authorid = users.id, search
+                    WHERE (posts.classid = $1 OR ($1::INTEGER IS NULL AND posts.classid IN (
+                        SELECT courseid FROM enrolled WHERE studentid = $2
+                        UNION SELECT classid FROM ta WHERE id = $2
+                        UNION SELECT courseid FROM instructing WHERE professorid = $2)))
+                    AND posts.removed = false
+                    AND {}
+                    AND (search.query IS NULL OR posts.postid IN (
+                        SELECT postid FROM posts WHERE search_vector @@ search.query
+                        UNION SELECT postid FROM replies WHERE removed = false AND search_vector @@ search.query))
+                    AND ($4::TEXT IS NULL OR ((posts.anonymous = false
+                            OR (posts.fully_anonymous = false AND posts.classid IN (
+                                SELECT classid FROM ta WHERE id = $2
+                                UNION SELECT courseid FROM instructing WHERE professorid = $2)))
+                        AND (users.firstname || ' ' || users.lastname ILIKE '%' || $4 || '%' OR users.username ILIKE $4)))
+                    AND ($5::BOOLEAN IS NULL OR posts.resolved = $5)
+                    AND ($6::BOOLEAN IS NULL OR posts.private = $6)
+                    AND ($7::BOOLEAN IS NULL OR posts.endorsed = $7)
+                    AND ($8::BOOLEAN IS NULL
+                        OR (posts.pinned AND (posts.pinned_until IS NULL OR posts.pinned_until > CURRENT_TIMESTAMP)) = $8)
+                    AND ($9::TIMESTAMP IS NULL OR posts.timestamp >= $9)
+                    AND ($10::TIMESTAMP IS NULL OR posts.timestamp < $10)
+                ),
+                best_replies AS (
+                    SELECT DISTINCT ON (replies.postid) replies.postid, replies.contents,
+                        ts_rank(replies.search_vector, search.query) AS rank
+                    FROM replies JOIN visible_posts ON replies.postid = visible_posts.postid, search
+                    WHERE replies.removed = false AND replies.search_vector @@ search.query
+                    ORDER BY replies.postid, rank DESC
+                ),
+                matches AS (
+                    SELECT visible_posts.*,
+                        best_replies.contents AS reply_contents,
+                        COALESCE(best_replies.rank, 0) > COALESCE(visible_posts.rank, 0) AS matched_reply,
+                        GREATEST(COALESCE(visible_posts.rank, 0), COALESCE(best_replies.rank, 0)) AS best_rank
+                    FROM visible_posts LEFT JOIN best_replies ON best_replies.postid = visible_posts.postid
+                    ORDER BY best_rank DESC, visible_posts.timestamp DESC
+                    LIMIT 50
+                )
+                SELECT matches.postid AS post_id,
+                    matches.classid AS class_id,
+                    classes.coursename AS class_name,
+                    COALESCE(ts_headline('english', matches.title, search.query, $11), matches.title) AS title_headline,
+                    CASE WHEN matches.matched_reply
+                        THEN ts_headline('english', regexp_replace(matches.reply_contents, '<[^>]*>', ' ', 'g'), search.query, $12)
+                        ELSE COALESCE(
+                            ts_headline('english', regexp_replace(matches.contents, '<[^>]*>', ' ', 'g'), search.query, $12),
+                            left(regexp_replace(matches.contents, '<[^>]*>', ' ', 'g'), 200))
+                    END AS snippet,
+                    matches.matched_reply,
+                    matches.resolved,
+                    matches.timestamp
+                FROM matches CROSS JOIN search
+                JOIN classes ON classes.courseid = matches.classid
+                ORDER BY matches.best_rank DESC, matches.timestamp DESC",
+                post_visible_to("$2")
+            ))
+            .bind(class_id)
+            .bind(user_id)
+            .bind(&query.text)
+            .bind(&query.author)
+            .bind(query.resolved)
+            .bind(query.private)
+            .bind(query.endorsed)
+            .bind(query.pinned)
+            .bind(query.after)
+            .bind(query.before)
+            .bind(headline_options_all())
+            .bind(headline_options_snippet())
+            .fetch_all(pool)
+            .await?;
+
+            Ok(rows)
+        }
+    }
+}
+
+/**
+ * A search query split into the text handed to Postgres and the operators applied as filters
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    /// Words, quoted phrases and `-exclusions`, in `websearch_to_tsquery` syntax
+    pub text: String,
+    pub author: Option<String>,
+    pub resolved: Option<bool>,
+    pub private: Option<bool>,
+    pub endorsed: Option<bool>,
+    pub pinned: Option<bool>,
+    pub after: Option<NaiveDateTime>,
+    pub before: Option<NaiveDateTime>,
+}
+
+impl SearchQuery {
+    /// Parses the search box syntax described at the top of this file
+    pub fn parse(input: &str) -> Result<SearchQuery, String> {
+        let mut query = SearchQuery::default();
+        let mut text_parts: Vec<String> = vec![];
+
+        for token in tokenize(input) {
+            let operator = token
+                .split_once(':')
+                .filter(|(key, value)| !value.is_empty() && !key.starts_with('"'));
+            match operator {
+                Some((key, value)) if key.eq_ignore_ascii_case("author") => {
+                    query.author = Some(value.trim_matches('"').to_string());
+                }
+                Some((key, value))
+                    if key.eq_ignore_ascii_case("is") || key.eq_ignore_ascii_case("tag") =>
+                {
+                    match value.trim_matches('"').to_lowercase().as_str() {
+                        "resolved" => query.resolved = Some(true),
+                        "unresolved" => query.resolved = Some(false),
+                        "private" => query.private = Some(true),
+                        "public" => query.private = Some(false),
+                        "endorsed" => query.endorsed = Some(true),
+                        "pinned" => query.pinned = Some(true),
+                        // Not a tag we know, so search for the word instead
+                        other => text_parts.push(other.to_string()),
+                    }
+                }
+                Some((key, value)) if key.eq_ignore_ascii_case("after") => {
+                    query.after = Some(start_of_day(parse_date(value)?));
+                }
+                Some((key, value)) if key.eq_ignore_ascii_case("before") => {
+                    query.before = Some(start_of_day(parse_date(value)?));
+                }
+                Some((key, value)) if key.eq_ignore_ascii_case("date") => {
+                    let (from, to) = value.split_once("..").unwrap_or((value, value));
+                    if !from.is_empty() {
+                        query.after = Some(start_of_day(parse_date(from)?));
+                    }
+                    if !to.is_empty() {
+                        let to = parse_date(to)?;
+                        query.before = Some(start_of_day(to.succ_opt().unwrap_or(to)));
+                    }
+                }
+                _ => text_parts.push(token),
+            }
+        }
+
+        if let (Some(after), Some(before)) = (query.after, query.before) {
+            if after >= before {
+                return Err("The date range is empty. Its start has to come before its end".to_string());
+            }
+        }
+
+        query.text = text_parts.join(" ");
+        Ok(query)
+    }
+
+    /// True if the query has nothing to search for or filter on
+    pub fn is_empty(&self) -> bool {
+        *self == SearchQuery::default()
+    }
+}
+
+/// Splits on whitespace, keeping quoted sections (including `key:"two words"`) together
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value.trim_matches('"'), "%Y-%m-%d")
+        .map_err(|_| format!("\"{}\" isn't a date. Use the format 2026-09-30", value))
+}
+
+fn start_of_day(date: NaiveDate) -> NaiveDateTime {
+    date.and_hms_opt(0, 0, 0).unwrap()
+}
+
+/**
+ * A run of text that is or isn't part of a search match
+ */
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct HighlightedText {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+/**
+ * Struct to hold a post matching a search, with highlighted title and snippet
+ */
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SearchResult {
+    pub post_id: i32,
+    pub title: Vec<HighlightedText>,
+    pub snippet: Vec<HighlightedText>,
+    /// True if the snippet comes from a reply rather than the post itself
+    pub matched_reply: bool,
+    pub resolved: bool,
+    pub timestamp: NaiveDateTime,
+}
+
+/**
+ * Search the posts and replies of a class the user can see, best matches first
+ */
+#[server(SearchClassPosts)]
+pub async fn search_class_posts(
+    class_id: i32,
+    user_id: i32,
+    query: String,
+) -> Result<Vec<SearchResult>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let query = SearchQuery::parse(&query).map_err(ServerFnError::<NoCustomError>::ServerError)?;
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let rows = search_posts(&pool, Some(class_id), user_id, &query)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(rows.into_iter().map(SearchRow::into_result).collect())
+}
+
+/**
+ * Struct to hold an announcement matching a search
+ */
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AnnouncementSearchResult {
+    pub announcement_id: i32,
+    pub title: Vec<HighlightedText>,
+    pub snippet: Vec<HighlightedText>,
+    pub time: NaiveDateTime,
+}
+
+/**
+ * Search results from one course
+ */
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct CourseSearchResults {
+    pub class_id: i32,
+    pub class_name: String,
+    pub posts: Vec<SearchResult>,
+    pub announcements: Vec<AnnouncementSearchResult>,
+}
+
+/**
+ * Search every class the user belongs to, grouped by course with the best matching course first
+ */
+#[server(SearchAllClasses)]
+pub async fn search_all_classes(
+    user_id: i32,
+    query: String,
+) -> Result<Vec<CourseSearchResults>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let query = SearchQuery::parse(&query).map_err(ServerFnError::<NoCustomError>::ServerError)?;
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let post_rows = search_posts(&pool, None, user_id, &query)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    // Announcements don't have a resolved, private, endorsed or pinned state to filter on
+    let announcement_rows: Vec<AnnouncementRow> = if query.resolved.is_none()
+        && query.private.is_none()
+        && query.endorsed.is_none()
+        && query.pinned.is_none()
+    {
+        sqlx::query_as(&format!(
+            "WITH search AS (
+                SELECT CASE WHEN $2 = '' THEN NULL ELSE websearch_to_tsquery('english', $2) END AS query
+            ),
+            matches AS (
+                SELECT announcements.announcementid, announcements.classid, announcements.title,
+                    announcements.contents, announcements.time,
+                    ts_rank(announcements.search_vector, search.query) AS rank
+                FROM announcements JOIN users ON announcements.authorid = users.id, search
+                WHERE announcements.classid IN (
+                    SELECT courseid FROM enrolled WHERE studentid = $1
+                    UNION SELECT classid FROM ta WHERE id = $1
+                    UNION SELECT courseid FROM instructing WHERE professorid = $1)
+                AND {}
+                AND (search.query IS NULL OR announcements.search_vector @@ search.query)
+                AND ($3::TEXT IS NULL
+                    OR users.firstname || ' ' || users.lastname ILIKE '%' || $3 || '%' OR users.username ILIKE $3)
+                AND ($4::TIMESTAMP IS NULL OR announcements.time >= $4)
+                AND ($5::TIMESTAMP IS NULL OR announcements.time < $5)
+                ORDER BY rank DESC NULLS LAST, announcements.time DESC
+                LIMIT 20
+            )
+            SELECT matches.announcementid AS announcement_id,
+                matches.classid AS class_id,
+                classes.coursename AS class_name,
+                COALESCE(ts_headline('english', matches.title, search.query, $6), matches.title) AS title_headline,
+                COALESCE(
+                    ts_headline('english', regexp_replace(matches.contents, '<[^>]*>', ' ', 'g'), search.query, $7),
+                    left(regexp_replace(matches.contents, '<[^>]*>', ' ', 'g'), 200)) AS snippet,
+                matches.time
+            FROM matches CROSS JOIN search
+            JOIN classes ON classes.courseid = matches.classid
+            ORDER BY matches.rank DESC NULLS LAST, matches.time DESC",
+            announcement_visible_to("$1")
+        ))
+        .bind(user_id)
+        .bind(&query.text)
+        .bind(&query.author)
+        .bind(query.after)
+        .bind(query.before)
+        .bind(headline_options_all())
+        .bind(headline_options_snippet())
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?
+    } else {
+        vec![]
+    };
+
+    // Courses are ordered by their best post, then by their best announcement
+    let mut courses: Vec<CourseSearchResults> = vec![];
+    let mut course_index: HashMap<i32, usize> = HashMap::new();
+    let mut course_for = |class_id: i32, class_name: &str| {
+        *course_index.entry(class_id).or_insert_with(|| {
+            courses.push(CourseSearchResults {
+                class_id,
+                class_name: class_name.to_string(),
+                posts: vec![],
+                announcements: vec![],
+            });
+            courses.len() - 1
+        })
+    };
+
+    let mut grouped_posts: Vec<(usize, SearchResult)> = vec![];
+    for row in post_rows {
+        let index = course_for(row.class_id, &row.class_name);
+        grouped_posts.push((index, row.into_result()));
+    }
+    let mut grouped_announcements: Vec<(usize, AnnouncementSearchResult)> = vec![];
+    for row in announcement_rows {
+        let index = course_for(row.class_id, &row.class_name);
+        grouped_announcements.push((index, row.into_result()));
+    }
+    for (index, post) in grouped_posts {
+        courses[index].posts.push(post);
+    }
+    for (index, announcement) in grouped_announcements {
+        courses[index].announcements.push(announcement);
+    }
+
+    Ok(courses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(date: &str) -> Option<NaiveDateTime> {
+        Some(start_of_day(NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()))
+    }
+
+    #[test]
+    fn words_phrases_and_exclusions_are_search_text() {
+        let query = SearchQuery::parse("  binary \"linked list\"   -java ").unwrap();
+        assert_eq!(query.text, "binary \"linked list\" -java");
+        assert_eq!(
+            query,
+            SearchQuery {
+                text: query.text.clone(),
+                ..SearchQuery::default()
+            }
+        );
+    }
+
+    #[test]
+    fn empty_queries_are_empty() {
+        assert!(SearchQuery::parse("").unwrap().is_empty());
+        assert!(SearchQuery::parse("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn author_accepts_a_word_or_quoted_name() {
+        assert_eq!(SearchQuery::parse("author:smith").unwrap().author.as_deref(), Some("smith"));
+        let query = SearchQuery::parse("recursion Author:\"Jane Smith\"").unwrap();
+        assert_eq!(query.author.as_deref(), Some("Jane Smith"));
+        assert_eq!(query.text, "recursion");
+    }
+
+    #[test]
+    fn is_and_tag_filter_on_state() {
+        let query = SearchQuery::parse("is:resolved tag:private IS:Endorsed is:pinned").unwrap();
+        assert_eq!(query.resolved, Some(true));
+        assert_eq!(query.private, Some(true));
+        assert_eq!(query.endorsed, Some(true));
+        assert_eq!(query.pinned, Some(true));
+        assert_eq!(query.text, "");
+
+        let query = SearchQuery::parse("is:unresolved tag:public").unwrap();
+        assert_eq!(query.resolved, Some(false));
+        assert_eq!(query.private, Some(false));
+    }
+
+    #[test]
+    fn unknown_tags_are_searched_for() {
+        let query = SearchQuery::parse("is:homework").unwrap();
+        assert_eq!(query.text, "homework");
+        assert_eq!(query.resolved, None);
+    }
+
+    #[test]
+    fn operators_need_a_value() {
+        let query = SearchQuery::parse("author: is:").unwrap();
+        assert_eq!(query.text, "author: is:");
+        assert_eq!(query.author, None);
+    }
+
+    #[test]
+    fn colons_in_phrases_are_text() {
+        let query = SearchQuery::parse("\"note: read\"").unwrap();
+        assert_eq!(query.text, "\"note: read\"");
+    }
+
+    #[test]
+    fn after_and_before_start_at_midnight() {
+        let query = SearchQuery::parse("after:2026-09-01 before:\"2026-10-01\"").unwrap();
+        assert_eq!(query.after, day("2026-09-01"));
+        assert_eq!(query.before, day("2026-10-01"));
+    }
+
+    #[test]
+    fn date_ranges_include_their_last_day() {
+        let query = SearchQuery::parse("date:2026-09-01..2026-09-30").unwrap();
+        assert_eq!(query.after, day("2026-09-01"));
+        assert_eq!(query.before, day("2026-10-01"));
+
+        let query = SearchQuery::parse("date:2026-09-15").unwrap();
+        assert_eq!(query.after, day("2026-09-15"));
+        assert_eq!(query.before, day("2026-09-16"));
+    }
+
+    #[test]
+    fn date_ranges_can_be_open_ended() {
+        let query = SearchQuery::parse("date:2026-09-01..").unwrap();
+        assert_eq!(query.after, day("2026-09-01"));
+        assert_eq!(query.before, None);
+
+        let query = SearchQuery::parse("date:..2026-09-30").unwrap();
+        assert_eq!(query.after, None);
+        assert_eq!(query.before, day("2026-10-01"));
+    }
+
+    #[test]
+    fn bad_dates_are_errors() {
+        assert!(SearchQuery::parse("after:yesterday").is_err());
+        assert!(SearchQuery::parse("before:2026-13-01").is_err());
+        assert!(SearchQuery::parse("date:2026-02-30").is_err());
+        assert!(SearchQuery::parse("date:2026-09-01..soon").is_err());
+    }
+
+    #[test]
+    fn reversed_ranges_are_errors() {
+        assert!(SearchQuery::parse("date:2026-09-30..2026-09-01").is_err());
+        assert!(SearchQuery::parse("after:2026-10-01 before:2026-09-01").is_err());
+        assert!(SearchQuery::parse("after:2026-10-01 before:2026-10-01").is_err());
+    }
+
+    #[test]
+    fn unterminated_quotes_run_to_the_end() {
+        let query = SearchQuery::parse("\"linked list").unwrap();
+        assert_eq!(query.text, "\"linked list");
+
+        let query = SearchQuery::parse("author:\"Jane Smith").unwrap();
+        assert_eq!(query.author.as_deref(), Some("Jane Smith"));
+        assert_eq!(query.text, "");
+    }
+}
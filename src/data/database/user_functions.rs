@@ -344,6 +344,55 @@ pub async fn update_user_password(user_id: i32, password: String) -> Result<(),
     Ok(())
 }
 
+/**
+ * Whether a user writes posts, replies and announcements in Markdown rather than rich text
+ */
+#[cfg(feature = "ssr")]
+pub async fn uses_markdown(pool: &sqlx::PgPool, user_id: i32) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("SELECT markdownauthoring FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map(|markdown| markdown.unwrap_or(false))
+}
+
+#[server(GetMarkdownAuthoring)]
+pub async fn get_markdown_authoring(user_id: i32) -> Result<bool, ServerFnError> {
+    use leptos::{server_fn::error::NoCustomError, use_context};
+    use sqlx::postgres::PgPool;
+
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    uses_markdown(&pool, user_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))
+}
+
+#[server(SetMarkdownAuthoring)]
+pub async fn set_markdown_authoring(user_id: i32, markdown: bool) -> Result<(), ServerFnError> {
+    use leptos::{server_fn::error::NoCustomError, use_context};
+    use sqlx::postgres::PgPool;
+
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    sqlx::query("UPDATE users SET markdownauthoring = $1 WHERE id = $2")
+        .bind(markdown)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(|_| {
+            ServerFnError::<NoCustomError>::ServerError(
+                "Unable to update editor setting".to_string(),
+            )
+        })?;
+
+    Ok(())
+}
+
 // Unused
 pub fn validate_password(password: &str) -> bool {
     let min_length = 8;
@@ -0,0 +1,136 @@
+/**
+ * Server functions for watching posts. Watchers are notified whenever a post they watch
+ * gets a new reply, is resolved or has a reply approved.
+ */
+use leptos::{server, ServerFnError};
+use serde::{Deserialize, Serialize};
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use leptos::{server_fn::error::NoCustomError, use_context};
+        use sqlx::postgres::PgPool;
+        use crate::data::database::post_functions::{check_user_can_see_post, post_visible_to};
+        use crate::data::database::notification_functions::{push_created_notifications, CreatedNotification};
+
+        /// Adds a user as a watcher of a post. Watching a post that is already watched does nothing.
+        pub async fn add_post_watcher(pool: &PgPool, post_id: i32, user_id: i32) -> Result<(), sqlx::Error> {
+            sqlx::query("INSERT INTO post_watchers (postid, userid) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(post_id)
+                .bind(user_id)
+                .execute(pool)
+                .await?;
+            Ok(())
+        }
+
+        /// Creates a notification for every watcher of a post that can still see it, skipping the
+        /// user who caused the notification. The post title is appended to `message`.
+        pub async fn notify_post_watchers(
+            pool: &PgPool,
+            post_id: i32,
+            actor_id: Option<i32>,
+            message: &str,
+        ) -> Result<(), sqlx::Error> {
+            let created: Vec<CreatedNotification> = sqlx::query_as(&format!(
+                "INSERT INTO notifications (userid, classid, postid, message)
+                SELECT post_watchers.userid, posts.classid, posts.postid, $3 || ' \"' || posts.title || '\"'
+                FROM post_watchers JOIN posts ON post_watchers.postid = posts.postid
+                WHERE posts.postid = $1
+                AND posts.removed = false
+                AND ($2::INTEGER IS NULL OR post_watchers.userid <> $2)
+                AND {}
+                RETURNING userid as user_id, notificationid as notification_id, classid as class_id, postid as post_id, message, created_at",
+                post_visible_to("post_watchers.userid")
+            ))
+            .bind(post_id)
+            .bind(actor_id)
+            .bind(message)
+            .fetch_all(pool)
+            .await?;
+
+            push_created_notifications(pool, created);
+            Ok(())
+        }
+    }
+}
+
+/**
+ * Struct to hold a post the user is watching
+ */
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "ssr", derive(sqlx::FromRow))]
+pub struct WatchedPost {
+    pub post_id: i32,
+    pub class_id: i32,
+    pub class_name: String,
+    pub title: String,
+    pub resolved: bool,
+}
+
+#[server(GetPostWatched)]
+pub async fn get_post_watched(post_id: i32, user_id: i32) -> Result<bool, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let watching: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM post_watchers WHERE postid = $1 AND userid = $2)",
+    )
+    .bind(post_id)
+    .bind(user_id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(watching)
+}
+
+#[server(SetPostWatched)]
+pub async fn set_post_watched(
+    post_id: i32,
+    user_id: i32,
+    watching: bool,
+) -> Result<(), ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    // Anyone may stop watching, but only posts the user can see can be watched
+    if watching {
+        check_user_can_see_post(&pool, post_id, user_id).await?;
+        add_post_watcher(&pool, post_id, user_id).await
+    } else {
+        sqlx::query("DELETE FROM post_watchers WHERE postid = $1 AND userid = $2")
+            .bind(post_id)
+            .bind(user_id)
+            .execute(&pool)
+            .await
+            .map(|_| ())
+    }
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(())
+}
+
+#[server(GetWatchedPosts)]
+pub async fn get_watched_posts(user_id: i32) -> Result<Vec<WatchedPost>, ServerFnError> {
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to complete Request".to_string(),
+    ))?;
+
+    let posts: Vec<WatchedPost> = sqlx::query_as(&format!(
+        "SELECT posts.postid as post_id, posts.classid as class_id, classes.coursename as class_name, posts.title, posts.resolved
+        FROM post_watchers
+        JOIN posts ON post_watchers.postid = posts.postid
+        JOIN classes ON posts.classid = classes.courseid
+        WHERE post_watchers.userid = $1 AND posts.removed = false
+        AND {}
+        ORDER BY post_watchers.created_at DESC",
+        post_visible_to("$1")
+    ))
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(posts)
+}
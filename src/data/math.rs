@@ -0,0 +1,253 @@
+/**
+ * LaTeX math in posts, replies, answers and announcements. Inline `$…$` and display `$$…$$` math is
+ * rendered to MathML on the server. Rendered math keeps its source in a `data-latex` attribute so
+ * editors can turn it back into the text that was typed.
+ */
+
+/// Class of the span around rendered inline math
+pub const INLINE_MATH_CLASS: &str = "math-inline";
+/// Class of the span around rendered display math
+pub const DISPLAY_MATH_CLASS: &str = "math-display";
+
+/// Replace rendered math in rich text HTML with the `$…$` or `$$…$$` source it was rendered from,
+/// for loading content back into an editor
+pub fn math_to_source(html: &str) -> String {
+    let mut source = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("<span class=\"math-") {
+        let span = &rest[start..];
+        // MathML has no spans of its own, so the first closing tag ends the rendered math
+        let (Some(tag_end), Some(span_end)) = (span.find('>'), span.find("</span>")) else {
+            break;
+        };
+        let tag = &span[..tag_end];
+        let Some(latex) = tag
+            .split("data-latex=\"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+        else {
+            break;
+        };
+        let delimiter = if tag.contains(DISPLAY_MATH_CLASS) { "$$" } else { "$" };
+
+        source.push_str(&rest[..start]);
+        source.push_str(delimiter);
+        // Still HTML, so the source stays escaped
+        source.push_str(latex);
+        source.push_str(delimiter);
+        rest = &span[span_end + "</span>".len()..];
+    }
+    source.push_str(rest);
+    source
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use latex2mathml::{latex_to_mathml, DisplayStyle};
+        use crate::data::content::sanitize_html;
+
+        fn escape_html(text: &str) -> String {
+            text.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+        }
+
+        fn unescape_html(text: &str) -> String {
+            text.replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&quot;", "\"")
+                .replace("&#39;", "'")
+                .replace("&nbsp;", "\u{a0}")
+                .replace("&amp;", "&")
+        }
+
+        /// MathML for some LaTeX, wrapped in a span recording its source, or None if it doesn't parse
+        pub fn render_latex(latex: &str, display: bool) -> Option<String> {
+            let (style, class) = if display {
+                (DisplayStyle::Block, DISPLAY_MATH_CLASS)
+            } else {
+                (DisplayStyle::Inline, INLINE_MATH_CLASS)
+            };
+            let mathml = latex_to_mathml(latex, style).ok()?;
+            Some(format!(
+                "<span class=\"{}\" data-latex=\"{}\">{}</span>",
+                class,
+                escape_html(latex),
+                mathml
+            ))
+        }
+
+        /// Find the next piece of math in text, returning where it starts and ends, its LaTeX and
+        /// whether it is display math. Like Pandoc, an opening `$` must not be followed by a space and
+        /// a closing `$` must not follow a space or be followed by a digit, so prices aren't math.
+        fn next_math(text: &str) -> Option<(usize, usize, &str, bool)> {
+            let bytes = text.as_bytes();
+            let mut i = 0;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\\' => i += 2,
+                    b'$' if bytes.get(i + 1) == Some(&b'$') => {
+                        let content_start = i + 2;
+                        if let Some(length) = text[content_start..].find("$$") {
+                            let latex = &text[content_start..content_start + length];
+                            if !latex.trim().is_empty() {
+                                return Some((i, content_start + length + 2, latex, true));
+                            }
+                        }
+                        i += 2;
+                    }
+                    b'$' => {
+                        let content_start = i + 1;
+                        let opens = bytes
+                            .get(content_start)
+                            .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'$');
+                        let close = opens
+                            .then(|| {
+                                let mut j = content_start;
+                                while j < bytes.len() {
+                                    match bytes[j] {
+                                        b'\\' => j += 2,
+                                        b'$' => return Some(j),
+                                        _ => j += 1,
+                                    }
+                                }
+                                None
+                            })
+                            .flatten()
+                            .filter(|&j| {
+                                !bytes[j - 1].is_ascii_whitespace()
+                                    && !bytes.get(j + 1).is_some_and(u8::is_ascii_digit)
+                            });
+                        if let Some(j) = close {
+                            return Some((i, j + 1, &text[content_start..j], false));
+                        }
+                        i += 1;
+                    }
+                    _ => i += 1,
+                }
+            }
+            None
+        }
+
+        /// Render the math in a piece of HTML-escaped text
+        fn render_text_math(text: &str) -> String {
+            let mut rendered = String::with_capacity(text.len());
+            let mut rest = text;
+            while let Some((start, end, latex, display)) = next_math(rest) {
+                rendered.push_str(&rest[..start].replace("\\$", "$"));
+                match render_latex(&unescape_html(latex), display) {
+                    Some(math) => rendered.push_str(&math),
+                    // Leave math that doesn't parse as it was typed
+                    None => rendered.push_str(&rest[start..end]),
+                }
+                rest = &rest[end..];
+            }
+            // An escaped dollar sign is just a dollar sign
+            rendered.push_str(&rest.replace("\\$", "$"));
+            rendered
+        }
+
+        /// Render the math in sanitized rich text HTML. Code and math that is already rendered are
+        /// left alone.
+        pub fn render_math(html: &str) -> String {
+            let mut rendered = String::with_capacity(html.len());
+            let mut rest = html;
+            // Depth of <code> and rendered math elements the text is inside of
+            let mut skip_depth = 0usize;
+            while !rest.is_empty() {
+                match rest.find('<') {
+                    Some(0) => {
+                        let tag_end = rest.find('>').map_or(rest.len(), |end| end + 1);
+                        let tag = &rest[..tag_end];
+                        if tag.starts_with("<code") || tag.starts_with("<math") {
+                            skip_depth += 1;
+                        } else if (tag.starts_with("</code") || tag.starts_with("</math")) && skip_depth > 0 {
+                            skip_depth -= 1;
+                        }
+                        rendered.push_str(tag);
+                        rest = &rest[tag_end..];
+                    }
+                    next_tag => {
+                        let text_end = next_tag.unwrap_or(rest.len());
+                        let text = &rest[..text_end];
+                        if skip_depth == 0 {
+                            rendered.push_str(&render_text_math(text));
+                        } else {
+                            rendered.push_str(text);
+                        }
+                        rest = &rest[text_end..];
+                    }
+                }
+            }
+            rendered
+        }
+
+        /// Plain text, such as an announcement, as HTML with its math rendered and its line breaks
+        /// kept. The MathML is sanitized like rich text, since the LaTeX is unescaped for rendering.
+        pub fn render_plain_text_math(text: &str) -> String {
+            sanitize_html(&render_text_math(&escape_html(text)).replace('\n', "<br/>"))
+        }
+
+        #[cfg(test)]
+        mod tests {
+            use super::*;
+
+            #[test]
+            fn math_is_found_between_dollar_signs() {
+                assert_eq!(next_math("a $x$ b"), Some((2, 5, "x", false)));
+                assert_eq!(next_math("a $$x + y$$ b"), Some((2, 11, "x + y", true)));
+                assert_eq!(next_math("$\\$$ y"), Some((0, 4, "\\$", false)));
+            }
+
+            #[test]
+            fn unclosed_delimiters_are_not_math() {
+                for text in ["$x", "$$x", "$$x$", "x$", "a $ b $", "$x $", "$$ $$"] {
+                    assert_eq!(next_math(text), None, "{}", text);
+                    assert_eq!(render_math(text), text);
+                }
+            }
+
+            #[test]
+            fn prices_are_not_math() {
+                assert_eq!(next_math("It costs $5 or $10 now"), None);
+                assert_eq!(next_math("$5 and $6"), None);
+            }
+
+            #[test]
+            fn escaped_dollars_are_dollar_signs() {
+                assert_eq!(next_math("\\$x\\$"), None);
+                assert_eq!(render_math("<p>\\$x\\$ costs \\$5</p>"), "<p>$x$ costs $5</p>");
+            }
+
+            #[test]
+            fn dollars_in_code_are_not_math() {
+                let html = "<p>Run <code>echo $x$</code></p><pre><code class=\"language-bash\">echo $$HOME$$</code></pre>";
+                assert_eq!(render_math(html), html);
+            }
+
+            #[test]
+            fn math_is_rendered_with_its_source() {
+                let rendered = render_math("<p>$x^2$ and $$y$$</p>");
+                assert!(rendered.contains("<span class=\"math-inline\" data-latex=\"x^2\"><math"));
+                assert!(rendered.contains("<span class=\"math-display\" data-latex=\"y\"><math"));
+                assert_eq!(math_to_source(&rendered), "<p>$x^2$ and $$y$$</p>");
+                // Rendered math isn't rendered again
+                assert_eq!(render_math(&rendered), rendered);
+            }
+
+            #[test]
+            fn latex_source_stays_escaped() {
+                let rendered = render_math("<p>$a &lt; b$</p>");
+                assert!(rendered.contains("data-latex=\"a &lt; b\""));
+            }
+
+            #[test]
+            fn plain_text_markup_is_escaped() {
+                let rendered = render_plain_text_math("<script>alert(1)</script> $\\text{<img src=x onerror=alert(1)>}$");
+                assert!(!rendered.contains("<script"), "{}", rendered);
+                assert!(!rendered.contains("onerror=\""), "{}", rendered);
+            }
+        }
+    }
+}
@@ -0,0 +1,222 @@
+/**
+ * @mentions of class members and #references to posts. The editor inserts them as `@[Name|id]` and
+ * `#[Title|id]`, which are turned into links when the content is saved. Links keep the id they
+ * point to in a `data-id` attribute so editors can turn them back into what was inserted.
+ */
+
+/// Class of the link a mention of a class member is stored as
+pub const MENTION_CLASS: &str = "mention";
+/// Class of the link a reference to a post is stored as
+pub const POST_REFERENCE_CLASS: &str = "post-reference";
+
+/// A mention or post reference as the editor inserts it, followed by a space. Brackets and bars are
+/// left out of the label so that it can't end the token early.
+pub fn mention_token(sigil: char, label: &str, id: i32) -> String {
+    let label: String = label
+        .chars()
+        .map(|c| if matches!(c, '[' | ']' | '|') { ' ' } else { c })
+        .collect();
+    format!("{}[{}|{}] ", sigil, label.trim(), id)
+}
+
+/// The mention or post reference being typed at the end of the text before the cursor, as its sigil
+/// and what has been typed after it so far
+pub fn mention_trigger(text_before_cursor: &str) -> Option<(char, String)> {
+    let start = text_before_cursor.rfind(['@', '#'])?;
+    let query = &text_before_cursor[start + 1..];
+    let at_word_start = text_before_cursor[..start]
+        .chars()
+        .next_back()
+        .map_or(true, char::is_whitespace);
+    if !at_word_start || query.chars().count() > 30 || query.contains(char::is_whitespace) {
+        return None;
+    }
+    let sigil = text_before_cursor[start..].chars().next()?;
+    Some((sigil, query.to_string()))
+}
+
+/// The sigil and id of a stored mention or post reference link tag
+fn link_of(tag: &str) -> Option<(char, i32)> {
+    let sigil = if tag.contains(&format!("class=\"{}\"", MENTION_CLASS)) {
+        '@'
+    } else if tag.contains(&format!("class=\"{}\"", POST_REFERENCE_CLASS)) {
+        '#'
+    } else {
+        return None;
+    };
+    let id = tag.split("data-id=\"").nth(1)?.split('"').next()?.parse().ok()?;
+    Some((sigil, id))
+}
+
+/// Replace stored mention and post reference links in rich text HTML with the tokens they were
+/// made from, for loading content back into an editor
+pub fn mentions_to_source(html: &str) -> String {
+    let mut source = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("<a ") {
+        let link = &rest[start..];
+        let (Some(tag_end), Some(link_end)) = (link.find('>'), link.find("</a>")) else {
+            break;
+        };
+        source.push_str(&rest[..start]);
+        match link_of(&link[..tag_end]) {
+            Some((sigil, id)) => {
+                let label = &link[tag_end + 1..link_end];
+                source.push_str(&format!("{}[{}|{}]", sigil, label.trim_start_matches(sigil), id));
+            }
+            None => source.push_str(&link[..link_end + "</a>".len()]),
+        }
+        rest = &link[link_end + "</a>".len()..];
+    }
+    source.push_str(rest);
+    source
+}
+
+/// The ids of the users mentioned in stored rich text HTML
+pub fn mentioned_user_ids(html: &str) -> Vec<i32> {
+    let mut ids: Vec<i32> = html
+        .split("<a ")
+        .skip(1)
+        .filter_map(|link| link.split('>').next())
+        .filter_map(link_of)
+        .filter_map(|(sigil, id)| (sigil == '@').then_some(id))
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "ssr")] {
+        use std::collections::HashMap;
+
+        /// A token found in text: where it starts and ends, its sigil and the id it points to
+        fn next_token(text: &str) -> Option<(usize, usize, char, i32)> {
+            let mut offset = 0;
+            while let Some(found) = text[offset..].find(['@', '#']) {
+                let start = offset + found;
+                let sigil = text[start..].chars().next()?;
+                let token = &text[start + 1..];
+                let parsed = token
+                    .strip_prefix('[')
+                    .and_then(|token| token.find(']').map(|end| &token[..end]))
+                    .and_then(|inside| inside.rsplit_once('|'))
+                    .filter(|(label, _)| !label.contains('[') && !label.contains('|'))
+                    .and_then(|(label, id)| Some((label.len() + id.len() + 3, id.parse().ok()?)));
+                match parsed {
+                    Some((length, id)) => return Some((start, start + 1 + length, sigil, id)),
+                    None => offset = start + 1,
+                }
+            }
+            None
+        }
+
+        /// The ids in the mention and post reference tokens of sanitized rich text HTML, as
+        /// (users, posts)
+        pub fn token_ids(html: &str) -> (Vec<i32>, Vec<i32>) {
+            let (mut users, mut posts) = (Vec::new(), Vec::new());
+            let mut rest = html;
+            while let Some((_, end, sigil, id)) = next_token(rest) {
+                if sigil == '@' {
+                    users.push(id);
+                } else {
+                    posts.push(id);
+                }
+                rest = &rest[end..];
+            }
+            (users, posts)
+        }
+
+        fn escape_html(text: &str) -> String {
+            text.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+        }
+
+        /// Turn the tokens in a piece of text into links. Tokens pointing at anything not in
+        /// `users` or `posts` are left as they were typed.
+        fn link_text_tokens(
+            text: &str,
+            class_id: i32,
+            users: &HashMap<i32, String>,
+            posts: &HashMap<i32, String>,
+        ) -> String {
+            let mut linked = String::with_capacity(text.len());
+            let mut rest = text;
+            while let Some((start, end, sigil, id)) = next_token(rest) {
+                linked.push_str(&rest[..start]);
+                // The label is always the current name or title, whatever the token said
+                let link = match sigil {
+                    '@' => users.get(&id).map(|name| {
+                        format!(
+                            "<a class=\"{}\" href=\"/profile/{}\" data-id=\"{}\">@{}</a>",
+                            MENTION_CLASS, id, id, escape_html(name)
+                        )
+                    }),
+                    _ => posts.get(&id).map(|title| {
+                        format!(
+                            "<a class=\"{}\" href=\"/classes/{}/{}\" data-id=\"{}\">#{}</a>",
+                            POST_REFERENCE_CLASS, class_id, id, id, escape_html(title)
+                        )
+                    }),
+                };
+                match link {
+                    Some(link) => linked.push_str(&link),
+                    None => linked.push_str(&rest[start..end]),
+                }
+                rest = &rest[end..];
+            }
+            linked.push_str(rest);
+            linked
+        }
+
+        /// Turn the mention and post reference tokens in sanitized rich text HTML into links.
+        /// Code, math and existing links are left alone.
+        pub fn link_tokens(
+            html: &str,
+            class_id: i32,
+            users: &HashMap<i32, String>,
+            posts: &HashMap<i32, String>,
+        ) -> String {
+            let mut linked = String::with_capacity(html.len());
+            let mut rest = html;
+            // Depth of <code>, math and link elements the text is inside of
+            let mut skip_depth = 0usize;
+            while !rest.is_empty() {
+                match rest.find('<') {
+                    Some(0) => {
+                        let tag_end = rest.find('>').map_or(rest.len(), |end| end + 1);
+                        let tag = &rest[..tag_end];
+                        if tag.starts_with("<code")
+                            || tag.starts_with("<math")
+                            || tag.starts_with("<a ")
+                            || tag == "<a>"
+                        {
+                            skip_depth += 1;
+                        } else if (tag.starts_with("</code")
+                            || tag.starts_with("</math")
+                            || tag.starts_with("</a>"))
+                            && skip_depth > 0
+                        {
+                            skip_depth -= 1;
+                        }
+                        linked.push_str(tag);
+                        rest = &rest[tag_end..];
+                    }
+                    next_tag => {
+                        let text_end = next_tag.unwrap_or(rest.len());
+                        let text = &rest[..text_end];
+                        if skip_depth == 0 {
+                            linked.push_str(&link_text_tokens(text, class_id, users, posts));
+                        } else {
+                            linked.push_str(text);
+                        }
+                        rest = &rest[text_end..];
+                    }
+                }
+            }
+            linked
+        }
+    }
+}
@@ -1,4 +1,12 @@
 // pages/mod.rs
+#[cfg(feature = "ssr")]
+pub mod code_highlighting;
+#[cfg(feature = "ssr")]
+pub mod content;
 pub mod database;
 pub mod generate_graphs;
 pub mod global_state;
+pub mod math;
+pub mod mentions;
+#[cfg(feature = "ssr")]
+pub mod storage;
@@ -0,0 +1,225 @@
+/**
+ * Pluggable storage for uploaded files. Files are kept on the local filesystem unless
+ * ENCAMPUS_STORAGE=s3, in which case they go to an S3-compatible bucket configured by
+ * ENCAMPUS_S3_ENDPOINT, ENCAMPUS_S3_BUCKET, ENCAMPUS_S3_REGION, ENCAMPUS_S3_ACCESS_KEY and
+ * ENCAMPUS_S3_SECRET_KEY. Buckets are addressed path-style, so a local stand-in such as MinIO
+ * works by pointing the endpoint at it.
+ */
+use async_trait::async_trait;
+use s3::creds::Credentials;
+use s3::{Bucket, Region};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("file not found")]
+    NotFound,
+    #[error("invalid storage key {0}")]
+    InvalidKey(String),
+    #[error("filesystem storage failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("S3 storage failed: {0}")]
+    S3(#[from] s3::error::S3Error),
+}
+
+#[async_trait]
+pub trait Storage: Send + Sync + std::fmt::Debug {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), StorageError>;
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError>;
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+/// Storage shared between the axum handlers
+pub type SharedStorage = Arc<dyn Storage>;
+
+/// Storage in a directory on the server's filesystem
+#[derive(Debug)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The path of a key inside the root, refusing keys that could point outside of it
+    fn path_of(&self, key: &str) -> Result<PathBuf, StorageError> {
+        let relative = Path::new(key);
+        if key.is_empty()
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(StorageError::InvalidKey(key.to_string()));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, _content_type: &str) -> Result<(), StorageError> {
+        let path = self.path_of(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, bytes).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        match tokio::fs::read(self.path_of(key)?).await {
+            Ok(bytes) => Ok(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(StorageError::NotFound),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path_of(key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Storage in a bucket of an S3-compatible object store
+#[derive(Debug)]
+pub struct S3Storage {
+    bucket: Box<Bucket>,
+}
+
+impl S3Storage {
+    pub fn new(
+        endpoint: String,
+        region: String,
+        bucket_name: &str,
+        access_key: &str,
+        secret_key: &str,
+    ) -> Result<Self, StorageError> {
+        let credentials = Credentials::new(Some(access_key), Some(secret_key), None, None, None)
+            .map_err(|e| StorageError::S3(e.into()))?;
+        let bucket = Bucket::new(bucket_name, Region::Custom { region, endpoint }, credentials)?
+            .with_path_style();
+        Ok(Self { bucket })
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, bytes: Vec<u8>, content_type: &str) -> Result<(), StorageError> {
+        self.bucket
+            .put_object_with_content_type(key, &bytes, content_type)
+            .await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        match self.bucket.get_object(key).await {
+            Ok(response) => Ok(response.bytes().to_vec()),
+            Err(s3::error::S3Error::HttpFailWithBody(404, _)) => Err(StorageError::NotFound),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.bucket.delete_object(key).await?;
+        Ok(())
+    }
+}
+
+/// The storage backend selected by the environment, see the module docs
+pub fn storage_from_env() -> SharedStorage {
+    let var = |name: &str| std::env::var(name).ok();
+
+    if var("ENCAMPUS_STORAGE").as_deref() == Some("s3") {
+        let endpoint = var("ENCAMPUS_S3_ENDPOINT").expect("ENCAMPUS_S3_ENDPOINT should be set for S3 storage");
+        let bucket = var("ENCAMPUS_S3_BUCKET").expect("ENCAMPUS_S3_BUCKET should be set for S3 storage");
+        let region = var("ENCAMPUS_S3_REGION").unwrap_or_else(|| "us-east-1".to_string());
+        let access_key = var("ENCAMPUS_S3_ACCESS_KEY").unwrap_or_default();
+        let secret_key = var("ENCAMPUS_S3_SECRET_KEY").unwrap_or_default();
+        Arc::new(
+            S3Storage::new(endpoint, region, &bucket, &access_key, &secret_key)
+                .expect("S3 storage settings should be valid"),
+        )
+    } else {
+        Arc::new(LocalStorage::new(
+            var("ENCAMPUS_UPLOAD_DIR").unwrap_or_else(|| "uploads".to_string()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory for a test's local storage
+    fn test_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("encampus-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        root
+    }
+
+    async fn round_trip(storage: &dyn Storage, key: &str) {
+        storage
+            .put(key, b"hello".to_vec(), "text/plain")
+            .await
+            .unwrap();
+        assert_eq!(storage.get(key).await.unwrap(), b"hello");
+        storage
+            .put(key, b"replaced".to_vec(), "text/plain")
+            .await
+            .unwrap();
+        assert_eq!(storage.get(key).await.unwrap(), b"replaced");
+        storage.delete(key).await.unwrap();
+        assert!(matches!(storage.get(key).await, Err(StorageError::NotFound)));
+        // Deleting a file that is already gone isn't an error
+        storage.delete(key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn local_storage_round_trip() {
+        let root = test_root("round-trip");
+        let storage = LocalStorage::new(&root);
+        round_trip(&storage, "thumbnails/upload").await;
+        assert!(matches!(storage.get("missing").await, Err(StorageError::NotFound)));
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn local_storage_stays_inside_its_root() {
+        let root = test_root("traversal");
+        let storage = LocalStorage::new(root.join("uploads"));
+        for key in ["", "../outside", "a/../../outside", "/tmp/outside", "./outside"] {
+            let put = storage.put(key, b"x".to_vec(), "text/plain").await;
+            assert!(matches!(put, Err(StorageError::InvalidKey(_))), "{}", key);
+            assert!(matches!(storage.get(key).await, Err(StorageError::InvalidKey(_))), "{}", key);
+            assert!(matches!(storage.delete(key).await, Err(StorageError::InvalidKey(_))), "{}", key);
+        }
+        assert!(!root.join("outside").exists());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    /// Runs against a real bucket when ENCAMPUS_TEST_S3_ENDPOINT is set, such as a local MinIO whose
+    /// bucket (ENCAMPUS_TEST_S3_BUCKET, `encampus-test` by default) has already been created
+    #[tokio::test]
+    async fn s3_storage_round_trip() {
+        let var = |name: &str| std::env::var(name).ok();
+        let Some(endpoint) = var("ENCAMPUS_TEST_S3_ENDPOINT") else {
+            eprintln!("ENCAMPUS_TEST_S3_ENDPOINT isn't set, skipping the S3 storage test");
+            return;
+        };
+        let storage = S3Storage::new(
+            endpoint,
+            var("ENCAMPUS_TEST_S3_REGION").unwrap_or_else(|| "us-east-1".to_string()),
+            &var("ENCAMPUS_TEST_S3_BUCKET").unwrap_or_else(|| "encampus-test".to_string()),
+            &var("ENCAMPUS_TEST_S3_ACCESS_KEY").unwrap_or_else(|| "minioadmin".to_string()),
+            &var("ENCAMPUS_TEST_S3_SECRET_KEY").unwrap_or_else(|| "minioadmin".to_string()),
+        )
+        .unwrap();
+        round_trip(&storage, &format!("storage-test-{}", std::process::id())).await;
+    }
+}
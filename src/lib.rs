@@ -6,6 +6,8 @@ pub mod data;
 pub mod fileserv;
 pub mod pages;
 pub mod resources;
+#[cfg(feature = "ssr")]
+pub mod uploads;
 
 #[cfg(feature = "hydrate")]
 #[wasm_bindgen::prelude::wasm_bindgen]
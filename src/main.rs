@@ -9,12 +9,17 @@ cfg_if::cfg_if! {
         use tower_sessions::{Expiry, MemoryStore, SessionManagerLayer};
         use axum::response::{IntoResponse, Response};
         use axum::extract::{Request, State, FromRef};
-        use axum::routing::get;
+        use axum::extract::DefaultBodyLimit;
+        use axum::routing::{get, post};
         use axum::Router;
         use encampus::app::*;
         use encampus::AuthenticationSession;
+        use encampus::data::database::attachment_functions::MAX_ATTACHMENT_BYTES;
+        use encampus::data::database::schedule_functions::spawn_scheduler;
         use encampus::data::global_state::AuthContext;
+        use encampus::data::storage::{storage_from_env, SharedStorage};
         use encampus::fileserv::file_and_error_handler;
+        use encampus::uploads::{download_handler, thumbnail_handler, upload_handler};
         use leptos::*;
         use leptos_axum::{LeptosRoutes, handle_server_fns_with_context, render_route_with_context};
         use leptos_router::RouteListing;
@@ -29,6 +34,7 @@ cfg_if::cfg_if! {
             leptos_options: LeptosOptions,
             app_routes: Vec<RouteListing>,
             pool: PgPool,
+            storage: SharedStorage,
         }
 
         /// A Axum handler specifically to wrap leptos server functions and provide them with a database connection using provide_context.
@@ -76,6 +82,15 @@ cfg_if::cfg_if! {
                 .await
                 .expect("Database at localhost:5432 should be live and accessible");
 
+            // Apply any migrations in ./migrations that haven't been run against the database yet
+            sqlx::migrate!()
+                .run(&pool)
+                .await
+                .expect("Database migrations should apply cleanly");
+
+            // Publish scheduled announcements and posts as they come due
+            spawn_scheduler(pool.clone());
+
             // Set up session management for auth
             let session_store = MemoryStore::default();
             let session_layer = SessionManagerLayer::new(session_store).with_private(Key::generate())
@@ -89,11 +104,20 @@ cfg_if::cfg_if! {
             let conf = get_configuration(None).await.unwrap();
             let leptos_options = conf.leptos_options;
             let addr = leptos_options.site_addr;
-            let state = AppState { leptos_options, app_routes: get_app_routes(), pool: pool.clone() };
+            let state = AppState {
+                leptos_options,
+                app_routes: get_app_routes(),
+                pool: pool.clone(),
+                storage: storage_from_env(),
+            };
 
             // build our application with a route
             let app = Router::new()
                 .route("/api/*fn_name", get(server_fn_handler).post(server_fn_handler))
+                // Leave room in the body limit for the multipart framing around the file
+                .route("/uploads", post(upload_handler).layer(DefaultBodyLimit::max(MAX_ATTACHMENT_BYTES + 64 * 1024)))
+                .route("/uploads/:attachment_id", get(download_handler))
+                .route("/uploads/:attachment_id/thumbnail", get(thumbnail_handler))
                 .leptos_routes_with_handler(state.app_routes.clone(), leptos_routes_handler)
                 .fallback(file_and_error_handler)
                 .with_state(state)
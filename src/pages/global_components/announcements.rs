@@ -1,14 +1,26 @@
+use crate::data::database::draft_functions::DraftContext;
+use crate::data::database::group_functions::get_class_groups;
 use crate::data::database::announcement_functions::{
-    delete_announcement, post_announcement, AddAnnouncementInfo, AnnouncementInfo,
+    delete_announcement, edit_announcement, get_announcement_page, mark_announcement_read,
+    pin_announcement, post_announcement, AddAnnouncementInfo, AnnouncementCursor,
+    AnnouncementInfo, ANNOUNCEMENTS_PAGE_SIZE,
 };
+use crate::pages::global_components::draft_autosave::{use_draft_autosave, DraftStatus};
+use crate::pages::global_components::infinite_scroll::InfiniteScroll;
+use crate::pages::global_components::local_datetime::{local_input_to_utc, utc_to_local_input};
+use crate::pages::global_components::markdown_box::{use_markdown_authoring, MarkdownBox};
+use crate::pages::global_components::scheduled_items::ScheduledItems;
 use crate::{expect_logged_in_user, on_input};
 use crate::pages::view_class_posts::class::ClassId;
 use crate::resources::images::svgs::announcement_mic::AnnouncementMic;
 use crate::resources::images::svgs::announcement_mic_2::AnnouncementMicAlt;
 use crate::resources::images::svgs::dots_icon::DotsIcon;
+use crate::resources::images::svgs::edit_post_icon::EditPostIcon;
+use crate::resources::images::svgs::pin_icon::PinIcon;
 use crate::resources::images::svgs::remove_icon::RemoveIcon;
+use crate::resources::images::svgs::unpinned_icon::UnPinIcon;
 use leptos::*;
-use leptos_router::use_params;
+use leptos_router::{use_params, use_query_map};
 
 #[component]
 pub fn AddAnnouncementModal(
@@ -25,6 +37,20 @@ pub fn AddAnnouncementModal(
 
     let (title, set_title) = create_signal(String::new());
     let (contents, set_contents) = create_signal(String::new());
+    let (publish_at_input, set_publish_at_input) = create_signal(String::new());
+    let (expires_at_input, set_expires_at_input) = create_signal(String::new());
+    // The groups the announcement is for. None picked means the whole class.
+    let group_ids = create_rw_signal(Vec::<i32>::new());
+    let groups = create_resource(
+        move || (class_id(), user().id),
+        |(class_id, user_id)| async move { get_class_groups(class_id, user_id).await.unwrap_or_default() },
+    );
+    let markdown_authoring = use_markdown_authoring();
+    let autosave = use_draft_autosave(
+        DraftContext::Announcement { class_id: class_id() },
+        Some((title, set_title)),
+        (contents, set_contents),
+    );
 
     view! {
         {move || if show.get() {
@@ -57,13 +83,78 @@ pub fn AddAnnouncementModal(
                                 </div>
                                 <div>
                                     <label class="block text-sm font-medium text-gray-700 mb-1">"Contents"</label>
-                                    <textarea
-                                        class="w-full p-2 border border-gray-300 rounded-md h-32 focus:ring-2 focus:ring-customBlue focus:border-transparent"
-                                        placeholder="Announcement Contents"
-                                        prop:value=contents
-                                        on:input=on_input!(set_contents)
-                                    ></textarea>
+                                    <Show
+                                        when=markdown_authoring
+                                        fallback=move || view! {
+                                            <textarea
+                                                class="w-full p-2 border border-gray-300 rounded-md h-32 focus:ring-2 focus:ring-customBlue focus:border-transparent"
+                                                placeholder="Announcement Contents"
+                                                prop:value=contents
+                                                on:input=on_input!(set_contents)
+                                            ></textarea>
+                                        }
+                                    >
+                                        <div class="h-48">
+                                            <MarkdownBox
+                                                id="announcement_markdown_box".to_string()
+                                                value=contents
+                                                set_value=set_contents
+                                            />
+                                        </div>
+                                    </Show>
+                                    <DraftStatus autosave />
                                 </div>
+                                <div class="flex gap-4">
+                                    <div>
+                                        <label class="block text-sm font-medium text-gray-700 mb-1">"Publish at"</label>
+                                        <input
+                                            class="p-2 border border-gray-300 rounded-md focus:ring-2 focus:ring-customBlue focus:border-transparent"
+                                            type="datetime-local"
+                                            title="Leave empty to post now"
+                                            prop:value=publish_at_input
+                                            on:input=on_input!(set_publish_at_input)
+                                        />
+                                    </div>
+                                    <div>
+                                        <label class="block text-sm font-medium text-gray-700 mb-1">"Expires at"</label>
+                                        <input
+                                            class="p-2 border border-gray-300 rounded-md focus:ring-2 focus:ring-customBlue focus:border-transparent"
+                                            type="datetime-local"
+                                            title="Leave empty to keep the announcement open"
+                                            prop:value=expires_at_input
+                                            on:input=on_input!(set_expires_at_input)
+                                        />
+                                    </div>
+                                </div>
+                                <Transition>
+                                    {move || groups().filter(|groups| !groups.is_empty()).map(|groups| view! {
+                                        <div>
+                                            <label class="block text-sm font-medium text-gray-700 mb-1">"For groups"</label>
+                                            <div class="flex flex-wrap gap-3">
+                                                {groups.into_iter().map(|group| {
+                                                    let group_id = group.group_id;
+                                                    view! {
+                                                        <label class="flex gap-1 items-center text-sm cursor-pointer select-none">
+                                                            <input
+                                                                type="checkbox"
+                                                                prop:checked=move || group_ids.with(|ids| ids.contains(&group_id))
+                                                                on:change=move |_| group_ids.update(|ids| {
+                                                                    if ids.contains(&group_id) {
+                                                                        ids.retain(|id| *id != group_id);
+                                                                    } else {
+                                                                        ids.push(group_id);
+                                                                    }
+                                                                })
+                                                            />
+                                                            {group.name}
+                                                        </label>
+                                                    }
+                                                }).collect_view()}
+                                            </div>
+                                            <p class="text-xs text-gray-500 mt-1">"Leave all unchecked to announce to the whole class"</p>
+                                        </div>
+                                    })}
+                                </Transition>
                                 <div class="flex justify-end gap-3 mt-4">
                                     <button
                                         class="px-4 py-2 text-gray-600 rounded-full hover:bg-gray-100"
@@ -78,14 +169,27 @@ pub fn AddAnnouncementModal(
                                                 title: title.get(),
                                                 contents: contents.get(),
                                                 class_id: class_id(),
+                                                publish_at: local_input_to_utc(&publish_at_input.get()),
+                                                expires_at: local_input_to_utc(&expires_at_input.get()),
+                                                group_ids: group_ids.get(),
                                             };
                                             on_submit.dispatch((user().id, new_announcement));
                                             set_show.set(false);
                                             set_title.set(String::new());
                                             set_contents.set(String::new());
+                                            set_publish_at_input.set(String::new());
+                                            set_expires_at_input.set(String::new());
+                                            group_ids.set(Vec::new());
+                                            autosave.forget();
                                         }
                                     >
-                                        "Post Announcement +"
+                                        {move || {
+                                            if publish_at_input.with(String::is_empty) {
+                                                "Post Announcement +"
+                                            } else {
+                                                "Schedule Announcement +"
+                                            }
+                                        }}
                                     </button>
                                 </div>
                             </div>
@@ -103,6 +207,9 @@ pub fn AddAnnouncementModal(
 pub fn AnnouncementDropDownMenu(
     announcement_id: i32,
     announcement_author_id: i32,
+    pinned: bool,
+    on_edit: Callback<()>,
+    pin_action: Action<(i32, bool), ()>,
     delete_action: Action<i32, ()>,
 ) -> impl IntoView {
     let (user, _) = expect_logged_in_user!();
@@ -114,6 +221,24 @@ pub fn AnnouncementDropDownMenu(
                 if is_author() {
                     view! {
                         <div class="p-1">
+                            <button
+                                class="inline-flex items-center p-2 w-full text-sm leading-tight text-gray-700 rounded-md hover:bg-gray-100"
+                                on:mousedown=move |_| on_edit.call(())
+                            >
+                                <EditPostIcon size="20px" />
+                                <span class="ml-2">Edit</span>
+                            </button>
+                            <button
+                                class="inline-flex items-center p-2 w-full text-sm leading-tight text-gray-700 rounded-md hover:bg-gray-100"
+                                on:mousedown=move |_| pin_action.dispatch((announcement_id, !pinned))
+                            >
+                                {if pinned {
+                                    view! { <UnPinIcon size="20px" /> }.into_view()
+                                } else {
+                                    view! { <PinIcon size="20px" /> }.into_view()
+                                }}
+                                <span class="ml-2">{if pinned { "Unpin" } else { "Pin" }}</span>
+                            </button>
                             <button
                                 class="inline-flex items-center p-2 w-full text-sm leading-tight text-red-500 rounded-md hover:text-red-500 hover:bg-gray-100"
                                 on:mousedown=move |_| delete_action.dispatch(announcement_id)
@@ -172,36 +297,133 @@ pub fn Announcements(
     }.into_view()
 }
 
+/// Puts pinned announcements first, then the rest newest first, as the server lists them
+fn sort_announcements(announcements: &mut [AnnouncementInfo]) {
+    announcements.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then(b.time.cmp(&a.time))
+            .then(b.announcement_id.cmp(&a.announcement_id))
+    });
+}
+
+/// How much of the class has opened an announcement, such as "Read by 12 of 40 students (30%)"
+fn read_receipt(announcement: &AnnouncementInfo) -> String {
+    if announcement.student_count == 0 {
+        return "No students to read this yet".to_string();
+    }
+    format!(
+        "Read by {} of {} students ({}%)",
+        announcement.read_count,
+        announcement.student_count,
+        announcement.read_count * 100 / announcement.student_count,
+    )
+}
+
 #[component]
 fn ExpandedAnnouncements(
     announcements: Vec<AnnouncementInfo>,
 ) -> impl IntoView {
     let (user, _) = expect_logged_in_user!();
+    let class_id = {
+        let class_params = use_params::<ClassId>();
+        move || class_params().expect("Tried to render announcements without class id").class_id
+    };
 
-    let (is_adding_post, set_is_adding_post) = create_signal(false);
+    // Drafts of announcements link to the class with ?compose=announcement to open the modal
+    let (is_adding_post, set_is_adding_post) = create_signal(use_query_map().with_untracked(|query| {
+        query.get("compose").map(String::as_str) == Some("announcement")
+    }));
     let (selected_announcement, set_selected_announcement) =
         create_signal(None::<AnnouncementInfo>);
+    let (show_scheduled, set_show_scheduled) = create_signal(false);
+    // Bumped when an announcement is scheduled so that the Scheduled list shows it
+    let (scheduled_count, set_scheduled_count) = create_signal(0);
+
+    let (editing_announcement, set_editing_announcement) =
+        create_signal(None::<AnnouncementInfo>);
+    let is_instructor = move || user().role == "Instructor";
 
     let (sorted_announcements, set_sorted_announcements) = create_signal({
         let mut clone = announcements.clone();
-        clone.sort_by(|a, b| b.time.cmp(&a.time));
+        sort_announcements(&mut clone);
         clone
     });
+    let (has_more, set_has_more) = create_signal(announcements.len() >= ANNOUNCEMENTS_PAGE_SIZE);
+
+    let load_more_action = create_action(move |class_id: &i32| {
+        let class_id = *class_id;
+        let cursor = sorted_announcements.with_untracked(|announcements| AnnouncementCursor::after(announcements));
+        let user_id = user.get_untracked().id;
+        async move {
+            match get_announcement_page(class_id, user_id, cursor).await {
+                Ok(page) => {
+                    set_has_more(page.len() >= ANNOUNCEMENTS_PAGE_SIZE);
+                    set_sorted_announcements.update(|announcements| {
+                        for announcement in page {
+                            if !announcements.iter().any(|loaded| loaded.announcement_id == announcement.announcement_id) {
+                                announcements.push(announcement);
+                            }
+                        }
+                    });
+                }
+                Err(_) => logging::error!("Failed to load more announcements"),
+            }
+        }
+    });
 
     let add_announcement_action = create_action(move |(user_id, announcement_info): &(i32, AddAnnouncementInfo)| {
         let announcement_info = announcement_info.clone();
         let user_id = *user_id;
         async move {
             match post_announcement(announcement_info, user_id).await {
+                // Scheduled announcements stay out of the list until they are published
+                Ok(announcement) if announcement.publish_at.is_some() => {
+                    set_scheduled_count.update(|count| *count += 1);
+                    set_show_scheduled.set(true);
+                }
                 Ok(announcement) => {
-                    set_sorted_announcements
-                        .update(|announcements| announcements.insert(0, announcement));
+                    set_sorted_announcements.update(|announcements| {
+                        announcements.insert(0, announcement);
+                        sort_announcements(announcements);
+                    });
                 }
                 Err(_) => logging::error!("Failed to post announcement. Please try again"),
             }
         }
     });
 
+    let pin_announcement_action = create_action(move |(announcement_id, pinned): &(i32, bool)| {
+        let (announcement_id, pinned) = (*announcement_id, *pinned);
+        async move {
+            match pin_announcement(announcement_id, user().id, pinned).await {
+                Ok(_) => {
+                    set_sorted_announcements.update(|announcements| {
+                        if let Some(announcement) = announcements
+                            .iter_mut()
+                            .find(|announcement| announcement.announcement_id == announcement_id)
+                        {
+                            announcement.pinned = pinned;
+                        }
+                        sort_announcements(announcements);
+                    });
+                }
+                Err(_) => logging::error!("Failed to pin announcement"),
+            }
+        }
+    });
+    let on_announcement_edited = Callback::new(move |edited: AnnouncementInfo| {
+        set_sorted_announcements.update(|announcements| {
+            if let Some(announcement) = announcements
+                .iter_mut()
+                .find(|announcement| announcement.announcement_id == edited.announcement_id)
+            {
+                *announcement = edited;
+            }
+        });
+        set_editing_announcement.set(None);
+    });
+
     let delete_announcement_action = create_action(move |announcement_id: &i32| {
         let announcement_id = *announcement_id;
         async move {
@@ -221,7 +443,13 @@ fn ExpandedAnnouncements(
             {move || 
                 (user().role == "Instructor").then(move ||
                     view! {
-                        <div class="flex justify-end px-4 pt-2">
+                        <div class="flex gap-2 justify-end px-4 pt-2">
+                            <button
+                                class="px-3 py-1 text-gray-700 rounded-full border border-gray-300 hover:bg-gray-100"
+                                on:click=move |_| set_show_scheduled.update(|show| *show = !*show)
+                            >
+                                "Scheduled"
+                            </button>
                             <button
                                 class="px-3 py-1 text-white rounded-full bg-customBlue hover:bg-customBlue-HOVER"
                                 on:click=move |_| set_is_adding_post.set(true)
@@ -229,6 +457,9 @@ fn ExpandedAnnouncements(
                                 "Add New Announcement +"
                             </button>
                         </div>
+                        <Show when=show_scheduled>
+                            <ScheduledItems class_id=class_id() refresh=scheduled_count />
+                        </Show>
                         <AddAnnouncementModal
                             show=is_adding_post
                             set_show=set_is_adding_post
@@ -239,7 +470,12 @@ fn ExpandedAnnouncements(
             }
         <div class="h-[290px] overflow-y-auto">
             <div class="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-4 gap-4 p-4 bg-white">
-                <For each=sorted_announcements key=|announcement| announcement.announcement_id let:announcement>
+                // Keyed on what can change so that edited and pinned announcements are redrawn
+                <For
+                    each=sorted_announcements
+                    key=|announcement| (announcement.announcement_id, announcement.edited_at, announcement.pinned)
+                    let:announcement
+                >
                     <div class="relative">
                         <div
                             class="cursor-pointer"
@@ -248,32 +484,72 @@ fn ExpandedAnnouncements(
                                 move |_| set_selected_announcement.set(Some(announcement.clone()))
                             }
                         >
-                            <div class="flex overflow-hidden relative flex-col justify-between p-6 h-60 bg-card-header rounded-lg shadow-lg transition-transform duration-300 hover:bg-gray-100 hover:shadow-xl hover:scale-105">
+                            <div class={
+                                if announcement.expired {
+                                    "flex overflow-hidden relative flex-col justify-between p-6 bg-card-header rounded-lg shadow-lg opacity-60 transition-transform duration-300 hover:bg-gray-100 hover:shadow-xl"
+                                } else {
+                                    "flex overflow-hidden relative flex-col justify-between p-6 h-60 bg-card-header rounded-lg shadow-lg transition-transform duration-300 hover:bg-gray-100 hover:shadow-xl hover:scale-105"
+                                }
+                            }>
                                 {
+                                    let on_edit = {
+                                        let announcement = announcement.clone();
+                                        Callback::new(move |_| set_editing_announcement.set(Some(announcement.clone())))
+                                    };
                                     move || (user().id == announcement.author_id).then(move || 
                                         view! {
                                             <AnnouncementDotsMenu
                                                 announcement_id=announcement.announcement_id
                                                 announcement_author_id=announcement.author_id
+                                                pinned=announcement.pinned
+                                                on_edit
+                                                pin_action=pin_announcement_action
                                                 delete_action=delete_announcement_action
                                             />
                                         }
                                     )
                                 }
                                 <div class="flex-1">
-                                    <div class="flex items-center mb-3">
+                                    <div class="flex gap-1 items-center mb-3">
+                                        {announcement.pinned.then(|| view! { <PinIcon size="1em" /> })}
                                         <h4 class="text-lg mt-2 font-semibold  text-customBlue">{announcement.title.clone()}</h4>
                                     </div>
-                                    <p class="text-sm text-gray-600 line-clamp-3">{announcement.contents.clone()}</p>
+                                    // Expired announcements collapse to their title
+                                    {(!announcement.expired).then(|| view! {
+                                        <div
+                                            class="text-sm text-gray-600 line-clamp-3"
+                                            inner_html=announcement.rendered_contents.clone()
+                                        ></div>
+                                    })}
                                 </div>
                                 <p class="text-xs text-gray-500 mt-2 pl-2">
                                     {announcement.time.format("%Y-%m-%d %H:%M:%S").to_string()}
+                                    {announcement.edited_at.is_some().then_some(" · Edited")}
+                                    {announcement.expired.then_some(" · Expired")}
+                                    {announcement.targeted.then(|| {
+                                        if announcement.group_names.is_empty() {
+                                            " · For: deleted groups".to_string()
+                                        } else {
+                                            format!(" · For: {}", announcement.group_names.join(", "))
+                                        }
+                                    })}
                                 </p>
+                                {
+                                    let receipt = read_receipt(&announcement);
+                                    move || is_instructor().then(|| view! {
+                                        <p class="text-xs text-gray-500 pl-2">{receipt.clone()}</p>
+                                    })
+                                }
                             </div>
                         </div>
                     </div>
                 </For>
             </div>
+            <InfiniteScroll
+                has_more=has_more
+                loading=load_more_action.pending()
+                on_load_more=move || load_more_action.dispatch(class_id())
+            />
         </div>
 
         {move || 
@@ -286,6 +562,17 @@ fn ExpandedAnnouncements(
                 }
             ) 
         }
+        {move ||
+            editing_announcement().map(move |announcement|
+                view! {
+                    <EditAnnouncementModal
+                        announcement
+                        on_close=move || set_editing_announcement.set(None)
+                        on_saved=on_announcement_edited
+                    />
+                }
+            )
+        }
     }.into_view()
 }
 
@@ -293,6 +580,9 @@ fn ExpandedAnnouncements(
 fn AnnouncementDotsMenu(
     announcement_id: i32,
     announcement_author_id: i32,
+    pinned: bool,
+    on_edit: Callback<()>,
+    pin_action: Action<(i32, bool), ()>,
     delete_action: Action<i32, ()>,
 ) -> impl IntoView {
     let (menu_visible, set_menu_visible) = create_signal(false);
@@ -316,6 +606,9 @@ fn AnnouncementDotsMenu(
                         <AnnouncementDropDownMenu
                             announcement_id
                             announcement_author_id
+                            pinned
+                            on_edit
+                            pin_action
                             delete_action
                         />
                     </div>
@@ -330,6 +623,18 @@ pub fn ViewAnnouncementModal(
     close_modal_callback: impl Fn() + 'static + Copy,
     announcement: AnnouncementInfo,
 ) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    // Opening an announcement counts towards its read receipts
+    let announcement_id = announcement.announcement_id;
+    create_effect(move |_| {
+        let user_id = user().id;
+        spawn_local(async move {
+            if let Err(e) = mark_announcement_read(announcement_id, user_id).await {
+                logging::error!("Failed to mark announcement as read: {:?}", e);
+            }
+        });
+    });
+
     view! {
         <div class="fixed inset-0 z-50 overflow-auto bg-black/50 flex items-center justify-center">
             <div class="relative bg-white rounded-xl shadow-2xl max-w-3xl w-full m-4">
@@ -366,15 +671,30 @@ pub fn ViewAnnouncementModal(
                         <span>
                             {"Posted on "}
                             {announcement.time.format("%B %d, %Y at %I:%M %p").to_string()}
+                            {announcement
+                                .edited_at
+                                .map(|edited_at| {
+                                    format!(" · Edited {}", edited_at.format("%B %d, %Y at %I:%M %p"))
+                                })}
+                            {announcement
+                                .expires_at
+                                .map(|expires_at| {
+                                    format!(
+                                        " · {} {}",
+                                        if announcement.expired { "Expired" } else { "Expires" },
+                                        expires_at.format("%B %d, %Y at %I:%M %p"),
+                                    )
+                                })}
                         </span>
                     </div>
 
 
                     <div class="bg-gray-50 rounded-lg p-6">
                         <div class="prose max-w-none">
-                            <p class="whitespace-pre-wrap text-gray-700 leading-relaxed">
-                                {announcement.contents.clone()}
-                            </p>
+                            <div
+                                class="text-gray-700 leading-relaxed"
+                                inner_html=announcement.rendered_contents.clone()
+                            ></div>
                         </div>
                     </div>
                 </div>
@@ -392,3 +712,105 @@ pub fn ViewAnnouncementModal(
         </div>
     }
 }
+
+/**
+ * Modal for the author of an announcement to change its title, contents and expiry
+ */
+#[component]
+fn EditAnnouncementModal<F>(
+    announcement: AnnouncementInfo,
+    on_close: F,
+    on_saved: Callback<AnnouncementInfo>,
+) -> impl IntoView
+where
+    F: Fn() + 'static + Copy,
+{
+    let (user, _) = expect_logged_in_user!();
+    let announcement_id = announcement.announcement_id;
+    let (title, set_title) = create_signal(announcement.title.clone());
+    let (contents, set_contents) = create_signal(announcement.contents.clone());
+    let (expires_at_input, set_expires_at_input) =
+        create_signal(announcement.expires_at.map(utc_to_local_input).unwrap_or_default());
+
+    let save_action = create_action(move |(title, contents, expires_at): &(String, String, Option<chrono::NaiveDateTime>)| {
+        let (title, contents, expires_at) = (title.clone(), contents.clone(), *expires_at);
+        async move {
+            match edit_announcement(announcement_id, user().id, title, contents, expires_at).await {
+                Ok(announcement) => on_saved.call(announcement),
+                Err(_) => logging::error!("Failed to edit announcement. Please try again"),
+            }
+        }
+    });
+
+    // Contents are edited the way the announcement was written
+    let contents_editor = if announcement.markdown {
+        view! {
+            <div class="h-48">
+                <MarkdownBox
+                    id=format!("edit_announcement_markdown_box_{}", announcement_id)
+                    value=contents
+                    set_value=set_contents
+                />
+            </div>
+        }
+        .into_view()
+    } else {
+        view! {
+            <textarea
+                class="w-full p-2 border border-gray-300 rounded-md h-32 focus:ring-2 focus:ring-customBlue focus:border-transparent"
+                prop:value=contents
+                on:input=on_input!(set_contents)
+            ></textarea>
+        }
+        .into_view()
+    };
+
+    view! {
+        <div class="fixed inset-0 z-50 overflow-auto bg-black/50 flex items-center justify-center">
+            <div class="relative bg-white rounded-lg shadow-xl max-w-2xl w-full m-4 p-6">
+                <h2 class="text-2xl font-bold mb-6">"Edit Announcement"</h2>
+                <div class="flex flex-col gap-4">
+                    <div>
+                        <label class="block text-sm font-medium text-gray-700 mb-1">"Title"</label>
+                        <input
+                            class="w-full p-2 border border-gray-300 rounded-md focus:ring-2 focus:ring-customBlue focus:border-transparent"
+                            type="text"
+                            prop:value=title
+                            on:input=on_input!(set_title)
+                        />
+                    </div>
+                    <div>
+                        <label class="block text-sm font-medium text-gray-700 mb-1">"Contents"</label>
+                        {contents_editor}
+                    </div>
+                    <div>
+                        <label class="block text-sm font-medium text-gray-700 mb-1">"Expires at"</label>
+                        <input
+                            class="p-2 border border-gray-300 rounded-md focus:ring-2 focus:ring-customBlue focus:border-transparent"
+                            type="datetime-local"
+                            title="Leave empty to keep the announcement open"
+                            prop:value=expires_at_input
+                            on:input=on_input!(set_expires_at_input)
+                        />
+                    </div>
+                    <div class="flex justify-end gap-3 mt-4">
+                        <button
+                            class="px-4 py-2 text-gray-600 rounded-full hover:bg-gray-100"
+                            on:click=move |_| on_close()
+                        >
+                            "Cancel"
+                        </button>
+                        <button
+                            class="px-4 py-2 text-white rounded-full bg-customBlue hover:bg-customBlue-HOVER"
+                            on:click=move |_| {
+                                save_action.dispatch((title.get(), contents.get(), local_input_to_utc(&expires_at_input.get())));
+                            }
+                        >
+                            "Save Changes"
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,147 @@
+/**
+ * Autosaving of what is being written in a composer, and the DraftStatus component showing whether
+ * it has been saved
+ */
+use crate::app::expect_auth_context;
+use crate::data::database::draft_functions::{get_draft, save_draft, DraftContext};
+use leptos::*;
+use std::time::Duration;
+
+/// How often changes are saved
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How far a composer's draft has got
+#[derive(Clone, Copy, PartialEq)]
+pub enum DraftState {
+    /// Nothing has been saved since the composer was opened
+    Unsaved,
+    /// A saved draft was put back into the composer
+    Restored,
+    Saved,
+}
+
+/**
+ * The draft of a composer, from `use_draft_autosave`
+ */
+#[derive(Clone, Copy)]
+pub struct DraftAutosave {
+    /// Whether any saved draft has been restored, so the composer's editor can be shown with it
+    pub ready: ReadSignal<bool>,
+    pub state: ReadSignal<DraftState>,
+    set_state: WriteSignal<DraftState>,
+    title: Option<ReadSignal<String>>,
+    contents: ReadSignal<String>,
+    /// The title and contents as last saved
+    saved: StoredValue<(String, String)>,
+}
+
+impl DraftAutosave {
+    fn current(&self) -> (String, String) {
+        (
+            self.title.map(|title| title.get_untracked()).unwrap_or_default(),
+            self.contents.get_untracked(),
+        )
+    }
+
+    /// Stop saving what has been written until it changes. Call this when it is submitted, since
+    /// submitting discards the draft on the server.
+    pub fn forget(&self) {
+        self.saved.set_value(self.current());
+        self.set_state.set(DraftState::Unsaved);
+    }
+}
+
+/**
+ * Restore the user's draft for `context` into a composer's title and contents, then save them as
+ * the draft every few seconds while they change. Composers without a title pass None for it.
+ */
+pub fn use_draft_autosave(
+    context: DraftContext,
+    title: Option<(ReadSignal<String>, WriteSignal<String>)>,
+    contents: (ReadSignal<String>, WriteSignal<String>),
+) -> DraftAutosave {
+    let auth_context = expect_auth_context();
+    let user_id = auth_context.with_untracked(|auth| auth.get_user().map(|user| user.id));
+    let (ready, set_ready) = create_signal(false);
+    let (state, set_state) = create_signal(DraftState::Unsaved);
+    let autosave = DraftAutosave {
+        ready,
+        state,
+        set_state,
+        title: title.map(|(title, _)| title),
+        contents: contents.0,
+        saved: store_value((String::new(), String::new())),
+    };
+
+    let draft = create_local_resource(
+        move || user_id,
+        move |user_id| async move {
+            match user_id {
+                Some(user_id) => get_draft(user_id, context).await.ok().flatten(),
+                None => None,
+            }
+        },
+    );
+    create_effect(move |_| {
+        let Some(draft) = draft.get() else {
+            return;
+        };
+        if ready.get_untracked() {
+            return;
+        }
+        if let Some(draft) = draft {
+            if let Some((_, set_title)) = title {
+                set_title.set(draft.title);
+            }
+            contents.1.set(draft.contents);
+            set_state.set(DraftState::Restored);
+        }
+        autosave.saved.set_value(autosave.current());
+        set_ready.set(true);
+    });
+
+    // Effects only run in the browser, so the server never starts saving
+    create_effect(move |_| {
+        let Some(user_id) = user_id else {
+            return;
+        };
+        let handle = set_interval_with_handle(
+            move || {
+                let current = autosave.current();
+                if !ready.get_untracked() || autosave.saved.with_value(|saved| *saved == current) {
+                    return;
+                }
+                autosave.saved.set_value(current.clone());
+                spawn_local(async move {
+                    let (title, contents) = current;
+                    match save_draft(user_id, context, title, contents).await {
+                        Ok(Some(_)) => set_state.set(DraftState::Saved),
+                        Ok(None) => set_state.set(DraftState::Unsaved),
+                        Err(e) => logging::error!("Failed to save draft: {:?}", e),
+                    }
+                });
+            },
+            AUTOSAVE_INTERVAL,
+        );
+        if let Ok(handle) = handle {
+            on_cleanup(move || handle.clear());
+        }
+    });
+
+    autosave
+}
+
+/**
+ * A note saying whether a composer's draft has been saved or restored
+ */
+#[component]
+pub fn DraftStatus(autosave: DraftAutosave) -> impl IntoView {
+    move || {
+        let message = match autosave.state.get() {
+            DraftState::Unsaved => "",
+            DraftState::Restored => "Restored your draft",
+            DraftState::Saved => "Draft saved",
+        };
+        view! { <span class="text-xs text-gray-500">{message}</span> }
+    }
+}
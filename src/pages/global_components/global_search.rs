@@ -0,0 +1,144 @@
+/**
+ * Search box in the header covering every class the user belongs to, with results grouped by course
+ */
+use crate::data::database::search_functions::search_all_classes;
+use crate::expect_logged_in_user;
+use crate::pages::view_class_posts::search_results::Highlighted;
+use crate::resources::images::svgs::magnifying_glass::MagnifyingGlass;
+use leptos::*;
+use leptos_router::A;
+
+#[component]
+pub fn GlobalSearch() -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    let (results_visible, set_results_visible) = create_signal(false);
+
+    let search_action = create_action(move |query: &String| {
+        let query = query.clone();
+        async move { search_all_classes(user().id, query).await }
+    });
+
+    let search = move |query: String| {
+        if query.trim().is_empty() {
+            set_results_visible(false);
+        } else {
+            search_action.dispatch(query);
+            set_results_visible(true);
+        }
+    };
+
+    view! {
+      <div class="relative mr-4">
+        <div class="flex items-center py-1 px-3 bg-white rounded-full border border-gray-300 focus-within:border-blue-500">
+          <input
+            type="text"
+            placeholder="Search all classes..."
+            class="py-1 w-56 text-base bg-white border-none focus:outline-none"
+            on:keydown=move |ev: web_sys::KeyboardEvent| {
+              match ev.key().as_str() {
+                "Enter" => search(event_target_value(&ev)),
+                "Escape" => set_results_visible(false),
+                _ => {}
+              }
+            }
+          />
+          <MagnifyingGlass size="1.3em" />
+        </div>
+        <div class=move || {
+          if results_visible() {
+            "absolute right-0 top-full z-50 mt-2 w-[32rem] max-h-[32rem] overflow-y-auto bg-white rounded-lg shadow-md"
+          } else {
+            "hidden"
+          }
+        }>
+          <div class="flex justify-end px-4 pt-2">
+            <button
+              class="text-xs text-gray-500 hover:underline"
+              on:click=move |_| set_results_visible(false)
+            >
+              "Close"
+            </button>
+          </div>
+          {move || {
+            if search_action.pending()() {
+              return view! { <p class="py-2 px-4">"Searching..."</p> }.into_view();
+            }
+            match search_action.value()() {
+              None => ().into_view(),
+              Some(Err(e)) => view! { <p class="py-2 px-4 text-red-600">{e.to_string()}</p> }.into_view(),
+              Some(Ok(courses)) if courses.is_empty() => {
+                view! { <p class="py-2 px-4 text-gray-500">"Nothing matches your search."</p> }
+                  .into_view()
+              }
+              Some(Ok(courses)) => {
+                courses
+                  .into_iter()
+                  .map(|course| {
+                    let class_id = course.class_id;
+                    view! {
+                      <div class="py-2 px-4 border-b last:border-b-0">
+                        <p class="mb-1 font-bold text-gray-800">{course.class_name}</p>
+                        <ul class="flex flex-col gap-1">
+                          {course
+                            .announcements
+                            .into_iter()
+                            .map(|announcement| {
+                              view! {
+                                <li on:click=move |_| set_results_visible(false)>
+                                  <A
+                                    href=format!(
+                                      "/classes/{}/announcement/{}",
+                                      class_id,
+                                      announcement.announcement_id,
+                                    )
+                                    class="block p-1 text-sm rounded hover:bg-gray-100"
+                                  >
+                                    <p class="font-semibold">
+                                      <span class="mr-1 text-xs text-gray-500">"Announcement:"</span>
+                                      <Highlighted parts=announcement.title />
+                                    </p>
+                                    <p class="text-gray-600">
+                                      <Highlighted parts=announcement.snippet />
+                                    </p>
+                                  </A>
+                                </li>
+                              }
+                            })
+                            .collect_view()}
+                          {course
+                            .posts
+                            .into_iter()
+                            .map(|post| {
+                              view! {
+                                <li on:click=move |_| set_results_visible(false)>
+                                  <A
+                                    href=format!("/classes/{}/{}", class_id, post.post_id)
+                                    class="block p-1 text-sm rounded hover:bg-gray-100"
+                                  >
+                                    <p class="font-semibold">
+                                      <Highlighted parts=post.title />
+                                    </p>
+                                    <p class="text-gray-600">
+                                      {post
+                                        .matched_reply
+                                        .then(|| view! { <span class="mr-1 font-semibold">"In a reply:"</span> })}
+                                      <Highlighted parts=post.snippet />
+                                    </p>
+                                  </A>
+                                </li>
+                              }
+                            })
+                            .collect_view()}
+                        </ul>
+                      </div>
+                    }
+                  })
+                  .collect_view()
+              }
+            }
+          }}
+        </div>
+      </div>
+    }
+    .into_view()
+}
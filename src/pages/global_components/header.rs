@@ -1,4 +1,6 @@
-use super::push_notifications::get_authenticated_user;
+use super::global_search::GlobalSearch;
+use super::notifications_menu::NotificationsMenu;
+use super::push_notifications::{get_authenticated_user, unregister_push_subscription};
 use crate::data::database::class_functions::{get_class_name, get_users_classes};
 /**
  * Component view and logic for the header at the top of the page of the site
@@ -16,7 +18,7 @@ use crate::{
     app::expect_auth_context, data::database::announcement_functions::get_announcement_list,
 };
 use leptos::*;
-use leptos_router::{use_params, ActionForm, A};
+use leptos_router::{use_params, A};
 
 #[component]
 pub fn Header() -> impl IntoView {
@@ -48,6 +50,18 @@ pub fn Header() -> impl IntoView {
     let header_text_href = move || class_id().map(|id| format!("/classes/{}", id));
 
     let logout_action = create_server_action::<Logout>();
+    // The browser stops getting the user's pushes before the session ends
+    let on_logout = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        spawn_local(async move {
+            if let Some(window) = web_sys::window() {
+                if let Err(err) = unregister_push_subscription(&window).await {
+                    logging::log!("Unable to unsubscribe from push notifications: {:?}", err);
+                }
+            }
+            logout_action.dispatch(Logout {});
+        });
+    };
 
     let header_title = create_resource(class_id, |class_id| async move  {
       let title = match class_id {
@@ -79,7 +93,9 @@ pub fn Header() -> impl IntoView {
         </div>
 
         <div class="flex items-center">
+          <GlobalSearch />
           <span class="flex items-center mr-4 text-xl font-bold">{first_name}</span>
+          <NotificationsMenu />
           // {move || {
           //   class_id()
           //     .map(|_| {
@@ -142,7 +158,7 @@ pub fn Header() -> impl IntoView {
                   </a>
                 </li>
                 <li class="py-1 px-4 cursor-pointer hover:bg-gray-100">
-                  <ActionForm action=logout_action class="w-full">
+                  <form class="w-full" on:submit=on_logout>
                     <div class="flex gap-2 items-center">
                       <LogoutIcon size="1em" />
                       <input
@@ -151,7 +167,7 @@ pub fn Header() -> impl IntoView {
                         value="Logout"
                       />
                     </div>
-                  </ActionForm>
+                  </form>
                 </li>
               </ul>
             </div>
@@ -250,7 +266,7 @@ pub async fn get_x_newest_announcements_for_user() -> Result<Vec<(i32, i32, Stri
     let mut all_announcements: Vec<(i32, i32, String)> = Vec::new();
 
     for class in classes {
-        let announcements = get_announcement_list(class.id).await?;
+        let announcements = get_announcement_list(class.id, user.id).await?;
 
         for announcement in announcements {
             all_announcements.push((
@@ -0,0 +1,67 @@
+/**
+ * InfiniteScroll component, placed after a paginated list to load the next page once the end of
+ * the list scrolls into view. Also shows a button in case the observer doesn't fire.
+ */
+use leptos::*;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{IntersectionObserver, IntersectionObserverEntry};
+
+#[component]
+pub fn InfiniteScroll<F>(
+    /// Whether there is another page to load
+    #[prop(into)]
+    has_more: Signal<bool>,
+    /// Whether a page is currently being loaded
+    #[prop(into)]
+    loading: Signal<bool>,
+    on_load_more: F,
+) -> impl IntoView
+where
+    F: Fn() + Copy + 'static,
+{
+    let sentinel = create_node_ref::<html::Div>();
+
+    sentinel.on_load(move |element| {
+        let callback = Closure::<dyn FnMut(js_sys::Array)>::new(move |entries: js_sys::Array| {
+            let visible = entries
+                .iter()
+                .any(|entry| entry.unchecked_into::<IntersectionObserverEntry>().is_intersecting());
+            if visible && has_more.get_untracked() && !loading.get_untracked() {
+                on_load_more();
+            }
+        });
+        match IntersectionObserver::new(callback.as_ref().unchecked_ref()) {
+            Ok(observer) => {
+                observer.observe(&element);
+                on_cleanup(move || {
+                    observer.disconnect();
+                    drop(callback);
+                });
+            }
+            Err(e) => logging::error!("Unable to observe end of list: {:?}", e),
+        }
+    });
+
+    view! {
+      <div node_ref=sentinel class="flex col-span-full justify-center py-2">
+        {move || {
+          if loading() {
+            view! { <p class="text-sm text-gray-500">"Loading..."</p> }.into_view()
+          } else if has_more() {
+            view! {
+              <button
+                class="py-1 px-3 text-sm text-gray-700 bg-white rounded-full shadow hover:bg-gray-100"
+                on:click=move |_| on_load_more()
+              >
+                "Load more"
+              </button>
+            }
+              .into_view()
+          } else {
+            ().into_view()
+          }
+        }}
+      </div>
+    }
+}
@@ -0,0 +1,49 @@
+/**
+ * Helpers for `<input type="datetime-local">`, whose values are in the browser's local time
+ * while the database stores UTC
+ */
+use chrono::NaiveDateTime;
+
+/// Converts a datetime-local input value (e.g. "2026-10-19T14:30") to a UTC timestamp.
+/// Returns None for an empty or unparseable value. Only call this in the browser.
+pub fn local_input_to_utc(value: &str) -> Option<NaiveDateTime> {
+    if value.is_empty() {
+        return None;
+    }
+    let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_str(value));
+    if date.get_time().is_nan() {
+        return None;
+    }
+    let iso: String = date.to_iso_string().into();
+    chrono::DateTime::parse_from_rfc3339(&iso)
+        .ok()
+        .map(|date| date.naive_utc())
+}
+
+/// Converts a UTC timestamp to a datetime-local input value in the browser's local time, for
+/// editing a time that was entered with `local_input_to_utc`. Only call this in the browser.
+pub fn utc_to_local_input(time: NaiveDateTime) -> String {
+    let millis = time.and_utc().timestamp_millis() as f64;
+    let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(millis));
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}",
+        date.get_full_year(),
+        date.get_month() + 1,
+        date.get_date(),
+        date.get_hours(),
+        date.get_minutes(),
+    )
+}
+
+/// Formats a UTC timestamp in the browser's local time. When rendering on the server, where the
+/// browser's time zone isn't known, it is formatted in UTC until the page hydrates.
+pub fn format_local(time: NaiveDateTime, format: &str) -> String {
+    #[cfg(target_arch = "wasm32")]
+    let time = {
+        let millis = time.and_utc().timestamp_millis() as f64;
+        let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(millis));
+        // Minutes local time is behind UTC at that moment
+        time - chrono::Duration::minutes(date.get_timezone_offset() as i64)
+    };
+    time.format(format).to_string()
+}
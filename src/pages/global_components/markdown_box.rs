@@ -0,0 +1,129 @@
+/**
+ * MarkdownBox component for users who would rather write Markdown than use the rich text editor.
+ * The Markdown is converted on the server when saved, so its preview comes from the server too.
+ */
+use crate::app::expect_auth_context;
+use crate::data::database::markdown_functions::preview_markdown;
+use crate::data::database::user_functions::get_markdown_authoring;
+use crate::data::mentions::mention_trigger;
+use crate::pages::global_components::mention_suggestions::MentionSuggestions;
+use leptos::*;
+
+/// Whether the logged in user writes in Markdown, which is false until their setting has loaded
+pub fn use_markdown_authoring() -> Memo<bool> {
+    let auth_context = expect_auth_context();
+    let setting = create_resource(
+        move || auth_context.with(|auth| auth.get_user().map(|user| user.id)),
+        |user_id| async move {
+            match user_id {
+                Some(user_id) => get_markdown_authoring(user_id).await.unwrap_or(false),
+                None => false,
+            }
+        },
+    );
+    create_memo(move |_| setting().unwrap_or(false))
+}
+
+/**
+ * A Markdown text area with a tab previewing the contents as they will be posted
+ */
+#[component]
+pub fn MarkdownBox(
+    id: String,
+    value: ReadSignal<String>,
+    set_value: WriteSignal<String>,
+) -> impl IntoView {
+    let (previewing, set_previewing) = create_signal(false);
+    let textarea = create_node_ref::<html::Textarea>();
+
+    // The text before the cursor, whose index the browser counts in UTF-16 code units
+    let text_before_cursor = move || -> Option<String> {
+        let textarea = textarea.get_untracked()?;
+        let cursor = textarea.selection_start().ok().flatten()? as usize;
+        let before: Vec<u16> = textarea.value().encode_utf16().take(cursor).collect();
+        String::from_utf16(&before).ok()
+    };
+    let (mention, set_mention) = create_signal(None::<(char, String)>);
+    let refresh_mention =
+        move || set_mention(text_before_cursor().and_then(|text| mention_trigger(&text)));
+    let pick_mention = Callback::new(move |token: String| {
+        let (Some(textarea), Some(before)) = (textarea.get_untracked(), text_before_cursor()) else {
+            return;
+        };
+        let Some(start) = before.rfind(['@', '#']) else {
+            return;
+        };
+        let text = textarea.value();
+        set_value(format!("{}{}{}", &before[..start], token, &text[before.len()..]));
+        set_mention(None);
+        let cursor = (before[..start].encode_utf16().count() + token.encode_utf16().count()) as u32;
+        let _ = textarea.focus();
+        let _ = textarea.set_selection_range(cursor, cursor);
+    });
+
+    let preview = create_resource(
+        move || previewing().then(value),
+        |markdown| async move {
+            match markdown {
+                Some(markdown) => preview_markdown(markdown).await.unwrap_or_default(),
+                None => String::new(),
+            }
+        },
+    );
+
+    let tab = move |label: &'static str, preview_tab: bool| {
+        view! {
+            <button
+                class="py-1 px-3 text-sm rounded-t border-b-2"
+                class=("border-customBlue", move || previewing() == preview_tab)
+                class=("font-semibold", move || previewing() == preview_tab)
+                class=("border-transparent", move || previewing() != preview_tab)
+                on:click=move |_| set_previewing(preview_tab)
+            >
+                {label}
+            </button>
+        }
+    };
+
+    view! {
+        <div class="bg-white rounded-lg border border-slate-400 h-full flex flex-col">
+            <div class="flex flex-row gap-1 px-2 pt-2 border-b border-slate-400">
+                {tab("Write", false)}
+                {tab("Preview", true)}
+                <span class="self-center ml-auto text-xs text-gray-500">"Markdown"</span>
+            </div>
+            <MentionSuggestions
+                trigger=Signal::derive(move || mention().filter(|_| !previewing()))
+                on_pick=pick_mention
+            />
+            <Show
+                when=previewing
+                fallback=move || {
+                    view! {
+                        <textarea
+                            id=id.clone()
+                            class="flex-1 p-2 min-h-[8rem] font-mono text-sm rounded-b-lg resize-none focus:outline-none"
+                            placeholder="Write in Markdown. $…$ and $$…$$ are math."
+                            prop:value=value
+                            node_ref=textarea
+                            on:input=move |ev| {
+                                set_value(event_target_value(&ev));
+                                refresh_mention();
+                            }
+                            on:click=move |_| refresh_mention()
+                        ></textarea>
+                    }
+                }
+            >
+                <Transition fallback=|| view! { <p class="p-2 text-sm text-gray-500">"Loading preview..."</p> }>
+                    {move || {
+                        preview()
+                            .map(|html| {
+                                view! { <div class="overflow-y-auto flex-1 p-2 tiptap" inner_html=html></div> }
+                            })
+                    }}
+                </Transition>
+            </Show>
+        </div>
+    }
+}
@@ -0,0 +1,107 @@
+/**
+ * MentionSuggestions component, the list of class members or posts offered while an @mention or
+ * #reference is typed in an editor
+ */
+use crate::app::expect_auth_context;
+use crate::data::database::class_functions::get_users_enrolled_in_class;
+use crate::data::database::mention_functions::{search_post_references, MENTION_SUGGESTIONS};
+use crate::data::mentions::mention_token;
+use crate::pages::view_class_posts::class::ClassId;
+use leptos::*;
+use leptos_router::use_params;
+
+/**
+ * Suggestions for the mention or post reference being typed, given as its sigil and the text typed
+ * after it. Picking one calls `on_pick` with the token to put in place of what was typed.
+ */
+#[component]
+pub fn MentionSuggestions(
+    trigger: Signal<Option<(char, String)>>,
+    on_pick: Callback<String>,
+) -> impl IntoView {
+    let auth_context = expect_auth_context();
+    // Mentions only mean something inside a class
+    let class_id = use_params::<ClassId>()
+        .get_untracked()
+        .ok()
+        .map(|params| params.class_id);
+
+    let members = create_resource(
+        move || class_id,
+        |class_id| async move {
+            match class_id {
+                Some(class_id) => get_users_enrolled_in_class(class_id).await.unwrap_or_default(),
+                None => Vec::new(),
+            }
+        },
+    );
+    let posts = create_resource(
+        move || trigger().filter(|(sigil, _)| *sigil == '#').map(|(_, query)| query),
+        move |query| async move {
+            let user_id = auth_context.with_untracked(|auth| auth.get_user().map(|user| user.id));
+            match (class_id, user_id, query) {
+                (Some(class_id), Some(user_id), Some(query)) => {
+                    search_post_references(class_id, user_id, query).await.unwrap_or_default()
+                }
+                _ => Vec::new(),
+            }
+        },
+    );
+
+    // Each suggestion as the label shown and the token it inserts
+    let suggestions = move || -> Vec<(String, String)> {
+        match trigger() {
+            Some(('@', query)) => {
+                let query = query.to_lowercase();
+                members
+                    .get()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|member| member.full_name.to_lowercase().contains(&query))
+                    .take(MENTION_SUGGESTIONS as usize)
+                    .map(|member| {
+                        (
+                            format!("@{} ({})", member.full_name, member.role),
+                            mention_token('@', &member.full_name, member.user_id),
+                        )
+                    })
+                    .collect()
+            }
+            Some(_) => posts
+                .get()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|post| (format!("#{}", post.title), mention_token('#', &post.title, post.post_id)))
+                .collect(),
+            None => Vec::new(),
+        }
+    };
+
+    view! {
+        <Show when=move || class_id.is_some() && !suggestions().is_empty() fallback=|| ()>
+            <ul class="flex flex-col py-1 border-b border-slate-400">
+                {move || {
+                    suggestions()
+                        .into_iter()
+                        .map(|(label, token)| {
+                            view! {
+                                <li>
+                                    <button
+                                        class="py-1 px-3 w-full text-sm text-left truncate hover:bg-gray-100"
+                                        // Keep the editor focused so the token goes where the cursor is
+                                        on:mousedown=move |ev| {
+                                            ev.prevent_default();
+                                            on_pick.call(token.clone());
+                                        }
+                                    >
+                                        {label}
+                                    </button>
+                                </li>
+                            }
+                        })
+                        .collect_view()
+                }}
+            </ul>
+        </Show>
+    }
+}
@@ -1,11 +1,20 @@
 // components/mod.rs
 pub mod alerts;
 pub mod announcements;
+pub mod draft_autosave;
 pub mod error_template;
+pub mod global_search;
 pub mod header;
+pub mod infinite_scroll;
 pub mod live_poll_sidebar;
+pub mod local_datetime;
+pub mod markdown_box;
+pub mod mention_suggestions;
 pub(crate) mod notification;
+pub mod notifications_menu;
 pub mod page;
 pub mod push_notifications;
 pub mod rich_text_box;
+pub mod scheduled_items;
 pub mod sidebar;
+pub mod upload;
@@ -0,0 +1,130 @@
+/**
+ * Notification bell in the header listing the user's unread notifications
+ */
+use crate::data::database::notification_functions::{
+    get_unread_notifications, mark_all_notifications_read, mark_notification_read,
+};
+use crate::expect_logged_in_user;
+use crate::resources::images::svgs::announcement_bell::AnnouncementBell;
+use leptos::*;
+use leptos_router::use_navigate;
+
+#[component]
+pub fn NotificationsMenu() -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    let (menu_visible, set_menu_visible) = create_signal(false);
+
+    let notifications = create_resource(
+        move || user().id,
+        |user_id| async move { get_unread_notifications(user_id).await.unwrap_or_default() },
+    );
+
+    let open_notification_action =
+        create_action(move |(notification_id, href): &(i32, String)| {
+            let notification_id = *notification_id;
+            let href = href.clone();
+            async move {
+                if mark_notification_read(notification_id, user().id).await.is_err() {
+                    logging::error!("Failed to mark notification as read");
+                }
+                notifications.update(|notifications| {
+                    if let Some(notifications) = notifications.as_mut() {
+                        notifications.retain(|n| n.notification_id != notification_id);
+                    }
+                });
+                let navigate = use_navigate();
+                navigate(href.as_str(), Default::default());
+            }
+        });
+
+    let mark_all_read_action = create_action(move |user_id: &i32| {
+        let user_id = *user_id;
+        async move {
+            match mark_all_notifications_read(user_id).await {
+                Ok(_) => notifications.set(vec![]),
+                Err(_) => logging::error!("Failed to mark notifications as read"),
+            }
+        }
+    });
+
+    let unread_count = move || notifications.get().map(|n| n.len()).unwrap_or_default();
+
+    view! {
+      <div class="flex relative items-center mr-2">
+        <button
+          class="relative p-2 bg-white rounded-md hover:bg-gray-100 focus:outline-none"
+          on:click=move |_| {
+            if !menu_visible() {
+              notifications.refetch();
+            }
+            set_menu_visible(!menu_visible());
+          }
+        >
+          <AnnouncementBell size="1.3rem" />
+          {move || {
+            (unread_count() > 0)
+              .then(|| {
+                view! {
+                  <span class="flex absolute top-0 right-0 justify-center items-center w-4 h-4 text-xs text-white bg-red-500 rounded-full">
+                    {unread_count()}
+                  </span>
+                }
+              })
+          }}
+        </button>
+        <div class=move || {
+          if menu_visible() {
+            "absolute right-0 top-full z-50 w-80 bg-white rounded-lg shadow-md"
+          } else {
+            "hidden"
+          }
+        }>
+          <div class="flex justify-between items-center py-2 px-4 border-b">
+            <span class="font-bold">"Notifications"</span>
+            <button
+              class="text-xs text-customBlue hover:underline"
+              on:click=move |_| mark_all_read_action.dispatch(user().id)
+            >
+              "Mark all read"
+            </button>
+          </div>
+          <Transition fallback=move || view! { <p class="py-2 px-4">"Loading..."</p> }>
+            <ul class="overflow-y-auto max-h-80">
+              {move || {
+                notifications()
+                  .map(|notifications| {
+                    if notifications.is_empty() {
+                      view! { <li class="py-2 px-4 text-sm text-gray-500">"You're all caught up"</li> }
+                        .into_view()
+                    } else {
+                      notifications
+                        .into_iter()
+                        .map(|notification| {
+                          let href = notification.href();
+                          let notification_id = notification.notification_id;
+                          view! {
+                            <li
+                              class="py-2 px-4 text-sm cursor-pointer hover:bg-gray-100"
+                              on:click=move |_| {
+                                set_menu_visible(false);
+                                open_notification_action.dispatch((notification_id, href.clone()));
+                              }
+                            >
+                              <p>{notification.message}</p>
+                              <p class="text-xs text-gray-500">
+                                {notification.created_at.format("%b %-d at %l:%M %p").to_string()}
+                              </p>
+                            </li>
+                          }
+                        })
+                        .collect_view()
+                    }
+                  })
+              }}
+            </ul>
+          </Transition>
+        </div>
+      </div>
+    }
+    .into_view()
+}
@@ -4,12 +4,19 @@ use js_sys::Reflect;
 use leptos::{logging, ServerFnError, SignalGetUntracked};
 use once_cell::sync::Lazy;
 use std::sync::Mutex;
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{Notification, NotificationOptions, Window};
 
 use crate::data::{
-    database::{announcement_functions::get_announcement_list, class_functions::get_users_classes},
+    database::{
+        announcement_functions::get_announcement_list,
+        class_functions::get_users_classes,
+        push_functions::{
+            delete_push_subscription, get_vapid_public_key, save_push_subscription,
+            PushSubscriptionInfo,
+        },
+    },
     global_state::User,
 };
 
@@ -85,6 +92,52 @@ pub async fn configure_notifications(window: &Window) -> Result<(), JsValue> {
     }
 }
 
+/// Subscribes this browser to server-sent Web Push and stores the subscription for the user.
+/// Fails if the server has no VAPID keys configured or the browser doesn't support push,
+/// in which case the in-tab announcement polling is used instead.
+pub async fn register_push_subscription(window: &Window) -> Result<(), JsValue> {
+    get_authenticated_user()?;
+
+    let public_key = get_vapid_public_key()
+        .await
+        .map_err(|err| JsValue::from_str(&err.to_string()))?
+        .ok_or_else(|| JsValue::from_str("Web Push is not configured on the server"))?;
+
+    // Defined in /js/push.js
+    let subscribe = Reflect::get(window, &JsValue::from_str("encampusSubscribeToPush"))?
+        .dyn_into::<js_sys::Function>()?;
+    let promise = subscribe
+        .call1(window, &JsValue::from_str(&public_key))?
+        .dyn_into::<js_sys::Promise>()?;
+    let subscription_json = JsFuture::from(promise)
+        .await?
+        .as_string()
+        .ok_or_else(|| JsValue::from_str("Push subscription was not a string"))?;
+
+    let subscription: PushSubscriptionInfo = serde_json::from_str(&subscription_json)
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+
+    save_push_subscription(subscription)
+        .await
+        .map_err(|err| JsValue::from_str(&err.to_string()))
+}
+
+/// Unsubscribes this browser from Web Push and forgets its subscription on the server, so that
+/// whoever logs in next on it doesn't get the user's pushes. Must run before the session ends.
+pub async fn unregister_push_subscription(window: &Window) -> Result<(), JsValue> {
+    // Defined in /js/push.js
+    let unsubscribe = Reflect::get(window, &JsValue::from_str("encampusUnsubscribeFromPush"))?
+        .dyn_into::<js_sys::Function>()?;
+    let promise = unsubscribe.call0(window)?.dyn_into::<js_sys::Promise>()?;
+    let Some(endpoint) = JsFuture::from(promise).await?.as_string() else {
+        return Ok(());
+    };
+
+    delete_push_subscription(endpoint)
+        .await
+        .map_err(|err| JsValue::from_str(&err.to_string()))
+}
+
 /// Submits a push notification with a title and body
 pub fn create_push_notification(title: &str, body: &str) -> Result<(), JsValue> {
     let options = NotificationOptions::new();
@@ -112,7 +165,7 @@ pub async fn get_newest_announcement_for_user(
     let mut latest_announcement: Option<(NaiveDateTime, String, String)> = None;
 
     for class in classes {
-        let announcements = get_announcement_list(class.id).await?;
+        let announcements = get_announcement_list(class.id, user_id).await?;
 
         for announcement in announcements {
             if latest_announcement.is_none()
@@ -1,11 +1,47 @@
 use crate::resources::images::svgs::{
     aligncenter_icon::AlignCenterIcon, alignjustify_icon::AlignJustifyIcon,
     alignleft_icon::AlignLeftIcon, alignright_icon::AlignRightIcon, bold_icon::BoldIcon,
-    enlargetext_icon::EnlargeTextIcon, highlighter_icon::HighlighterIcon, italic_icon::ItalicIcon,
+    code_block_icon::CodeBlockIcon, enlargetext_icon::EnlargeTextIcon, highlighter_icon::HighlighterIcon, italic_icon::ItalicIcon,
     strikethrough_icon::StrikethroughIcon,
 };
+use crate::data::database::math_functions::preview_math;
+use crate::data::mentions::mention_trigger;
+use crate::pages::global_components::markdown_box::{use_markdown_authoring, MarkdownBox};
+use crate::pages::global_components::mention_suggestions::MentionSuggestions;
+use crate::pages::global_components::upload::upload_file;
+use crate::pages::view_class_posts::class::ClassId;
+use leptoaster::*;
 use leptos::*;
+use leptos_router::use_params;
 use leptos_tiptap::*;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::FileList;
+
+/// Languages that code blocks can be highlighted in, by the name stored in the content and the
+/// name shown in the editor
+pub const CODE_LANGUAGES: [(&str, &str); 6] = [
+    ("rust", "Rust"),
+    ("python", "Python"),
+    ("c", "C"),
+    ("cpp", "C++"),
+    ("java", "Java"),
+    ("javascript", "JavaScript"),
+];
+
+/// Run one of the editor commands defined in /js/editor.js on the editor with the given id
+fn editor_command(name: &str, editor_id: &str, argument: Option<&str>) -> Option<JsValue> {
+    let window = web_sys::window()?;
+    let command = js_sys::Reflect::get(&window, &JsValue::from_str(name))
+        .ok()?
+        .dyn_into::<js_sys::Function>()
+        .ok()?;
+    let editor_id = JsValue::from_str(editor_id);
+    match argument {
+        Some(argument) => command.call2(&window, &editor_id, &JsValue::from_str(argument)),
+        None => command.call1(&window, &editor_id),
+    }
+    .ok()
+}
 
 #[component]
 fn StyleButton(
@@ -34,14 +70,128 @@ fn StyleButton(
     }
 }
 
+/**
+ * The editor for posts, replies and answers: the rich text editor, or a Markdown box for users who
+ * have chosen to write in Markdown. Either way `value` holds what was written.
+ */
 #[component]
 pub fn RichTextBox(
     id: String,
     value: ReadSignal<String>,
     set_value: WriteSignal<String>,
+) -> impl IntoView {
+    let markdown_authoring = use_markdown_authoring();
+
+    move || {
+        let id = id.clone();
+        if markdown_authoring() {
+            view! { <MarkdownBox id=id value=value set_value=set_value /> }.into_view()
+        } else {
+            view! { <WysiwygBox id=id value=value set_value=set_value /> }.into_view()
+        }
+    }
+}
+
+#[component]
+fn WysiwygBox(
+    id: String,
+    value: ReadSignal<String>,
+    set_value: WriteSignal<String>,
 ) -> impl IntoView {
     let (msg, set_msg) = create_signal(TiptapInstanceMsg::Noop);
     let (selection, set_selection) = create_signal(TiptapSelectionState::default());
+    let (uploading, set_uploading) = create_signal(0usize);
+    let toaster = expect_toaster();
+    let editor_id = store_value(id.clone());
+
+    // The language new code blocks get, and the language of the code block the cursor is in if any
+    let (code_language, set_code_language) = create_signal(CODE_LANGUAGES[0].0.to_string());
+    let (current_code_block, set_current_code_block) = create_signal(None::<String>);
+    // The equation being written in the equation panel, previewed as it is typed
+    let (show_equation, set_show_equation) = create_signal(false);
+    let (equation, set_equation) = create_signal(String::new());
+    let (display_equation, set_display_equation) = create_signal(false);
+    let equation_preview = create_resource(
+        move || (equation(), display_equation()),
+        |(latex, display)| async move {
+            if latex.trim().is_empty() {
+                return Ok(String::new());
+            }
+            preview_math(latex, display).await.map_err(|_| ())
+        },
+    );
+    let insert_equation = move || {
+        let delimiter = if display_equation.get_untracked() { "$$" } else { "$" };
+        editor_command(
+            "encampusInsertText",
+            &editor_id.get_value(),
+            Some(&format!("{}{}{}", delimiter, equation.get_untracked().trim(), delimiter)),
+        );
+        set_equation(String::new());
+        set_show_equation(false);
+    };
+
+    // The @mention or #reference being typed, if any
+    let (mention, set_mention) = create_signal(None::<(char, String)>);
+    let refresh_mention = move || {
+        set_mention(
+            editor_command("encampusTextBeforeCursor", &editor_id.get_value(), None)
+                .and_then(|text| text.as_string())
+                .and_then(|text| mention_trigger(&text)),
+        )
+    };
+    let pick_mention = Callback::new(move |token: String| {
+        editor_command("encampusReplaceMentionQuery", &editor_id.get_value(), Some(&token));
+        set_mention(None);
+    });
+
+    let refresh_code_block = move || {
+        set_current_code_block(
+            editor_command("encampusCodeBlockLanguage", &editor_id.get_value(), None)
+                .and_then(|language| language.as_string()),
+        )
+    };
+
+    // Images can only be uploaded to a class, so outside of one pasting and dropping them is left alone
+    let class_id = use_params::<ClassId>()
+        .get_untracked()
+        .ok()
+        .map(|params| params.class_id);
+
+    // Upload any images among pasted or dropped files and insert them once stored. Returns whether
+    // there were any, in which case the browser's own handling of the event should be prevented.
+    let insert_images = move |files: Option<FileList>| -> bool {
+        let (Some(class_id), Some(files)) = (class_id, files) else {
+            return false;
+        };
+        let images: Vec<_> = (0..files.length())
+            .filter_map(|index| files.get(index))
+            .filter(|file| file.type_().starts_with("image/"))
+            .collect();
+        for image in images.iter().cloned() {
+            set_uploading.update(|count| *count += 1);
+            let toaster = toaster.clone();
+            spawn_local(async move {
+                match upload_file(class_id, image).await {
+                    Ok(attachment) => set_msg(TiptapInstanceMsg::SetImage(TiptapImageResource {
+                        title: attachment.filename.clone(),
+                        alt: attachment.filename.clone(),
+                        url: attachment.url(),
+                    })),
+                    Err(message) => toaster.toast(
+                        ToastBuilder::new(format!("Failed to upload image: {}", message))
+                            .with_level(ToastLevel::Error)
+                            .with_dismissable(true)
+                            .with_expiry(Some(4_000))
+                            .with_progress(false)
+                            .with_position(ToastPosition::BottomRight),
+                    ),
+                }
+                set_uploading.update(|count| *count -= 1);
+            });
+        }
+        !images.is_empty()
+    };
 
     let spacer = view! { <div class="w-3"></div> };
 
@@ -93,6 +243,78 @@ pub fn RichTextBox(
                     <HighlighterIcon size="2em"/>
                 </StyleButton>
 
+                // Code block
+                <button
+                    class="rounded border self-center flex justify-center hover:bg-gray-100 transition-colors duration-200"
+                    class=("shadow-lg", move || current_code_block.with(Option::is_none))
+                    class=("shadow-inner", move || current_code_block.with(Option::is_some))
+                    class=("bg-slate-400", move || current_code_block.with(Option::is_some))
+                    title="Code block"
+                    on:click=move |_| {
+                        editor_command(
+                            "encampusToggleCodeBlock",
+                            &editor_id.get_value(),
+                            Some(&code_language.get_untracked()),
+                        );
+                        refresh_code_block();
+                    }
+                >
+                    <CodeBlockIcon size="2em"/>
+                </button>
+                <select
+                    class="self-center py-1 px-1 text-sm bg-white rounded border border-slate-400"
+                    title="Code block language"
+                    on:change=move |ev| {
+                        let language = event_target_value(&ev);
+                        if current_code_block.get_untracked().is_some() {
+                            editor_command(
+                                "encampusSetCodeBlockLanguage",
+                                &editor_id.get_value(),
+                                Some(&language),
+                            );
+                            refresh_code_block();
+                        }
+                        set_code_language(language);
+                    }
+                >
+                    <option
+                        value=""
+                        selected=move || {
+                            current_code_block().unwrap_or_else(|| code_language.get()).is_empty()
+                        }
+                    >
+                        "Plain text"
+                    </option>
+                    {CODE_LANGUAGES
+                        .into_iter()
+                        .map(|(language, label)| {
+                            view! {
+                                <option
+                                    value=language
+                                    selected=move || {
+                                        current_code_block().unwrap_or_else(|| code_language.get())
+                                            == language
+                                    }
+                                >
+                                    {label}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+
+                // Equation
+                <button
+                    class="rounded border self-center flex justify-center items-center w-8 h-8 text-xl font-serif hover:bg-gray-100 transition-colors duration-200"
+                    class=("shadow-lg", move || !show_equation())
+                    class=("shadow-inner", show_equation)
+                    class=("bg-slate-400", show_equation)
+                    title="Equation"
+                    on:click=move |_| set_show_equation.update(|show| *show = !*show)
+                >
+                    "∑"
+                </button>
+
                 // Align Left
                 {spacer}
                 <StyleButton
@@ -130,26 +352,100 @@ pub fn RichTextBox(
                     <AlignJustifyIcon size="2em"/>
                 </StyleButton>
 
+                <Show when=move || { uploading() > 0 } fallback=|| ()>
+                    <span class="self-center ml-auto text-sm text-gray-500">"Uploading image..."</span>
+                </Show>
             </div>
 
-            <TiptapInstance
-                id=id
-                msg=msg
-                disabled=false
-                value=value
-                set_value=move |v| {
-                    set_value
-                        .set(
-                            match v {
-                                TiptapContent::Html(content) => content,
-                                TiptapContent::Json(content) => content,
-                            },
-                        )
+            <Show when=show_equation fallback=|| ()>
+                <div class="flex flex-col gap-2 p-2 border-b border-slate-400">
+                    <div class="flex gap-2 items-center">
+                        <input
+                            type="text"
+                            class="flex-1 py-1 px-2 font-mono text-sm rounded border border-slate-400"
+                            placeholder="LaTeX, e.g. \\sum_{i=1}^n i = \\frac{n(n+1)}{2}"
+                            prop:value=equation
+                            on:input=move |ev| set_equation(event_target_value(&ev))
+                            on:keydown=move |ev: web_sys::KeyboardEvent| {
+                                if ev.key() == "Enter" {
+                                    ev.prevent_default();
+                                    insert_equation();
+                                }
+                            }
+                        />
+                        <label class="flex gap-1 items-center text-sm">
+                            <input
+                                type="checkbox"
+                                prop:checked=display_equation
+                                on:change=move |_| set_display_equation.update(|display| *display = !*display)
+                            />
+                            "On its own line"
+                        </label>
+                        <button
+                            class="py-1 px-3 text-sm text-white rounded-full bg-customBlue hover:bg-customBlue-HOVER"
+                            on:click=move |_| insert_equation()
+                        >
+                            "Insert"
+                        </button>
+                    </div>
+                    <Transition fallback=|| ()>
+                        {move || {
+                            equation_preview()
+                                .map(|preview| match preview {
+                                    Ok(math) => view! { <div class="min-h-[1.5rem]" inner_html=math></div> }.into_view(),
+                                    Err(_) => view! {
+                                        <p class="text-sm text-red-600">"This LaTeX can't be rendered yet"</p>
+                                    }
+                                        .into_view(),
+                                })
+                        }}
+                    </Transition>
+                </div>
+            </Show>
+
+            <MentionSuggestions trigger=mention.into() on_pick=pick_mention />
+
+            <div
+                class="flex flex-1 flex-col"
+                on:paste={
+                    let insert_images = insert_images.clone();
+                    move |ev| {
+                        if insert_images(ev.clipboard_data().and_then(|data| data.files())) {
+                            ev.prevent_default();
+                        }
+                    }
+                }
+                // Allowing the drop here keeps the browser from opening a dropped image itself
+                on:dragover=move |ev| ev.prevent_default()
+                on:drop=move |ev| {
+                    if insert_images(ev.data_transfer().and_then(|data| data.files())) {
+                        ev.prevent_default();
+                    }
                 }
+            >
+                <TiptapInstance
+                    id=id
+                    msg=msg
+                    disabled=false
+                    value=value
+                    set_value=move |v| {
+                        set_value
+                            .set(
+                                match v {
+                                    TiptapContent::Html(content) => content,
+                                    TiptapContent::Json(content) => content,
+                                },
+                            )
+                    }
 
-                on_selection_change=move |state| set_selection.set(state)
-                class="flex flex-1 flex-col p-1"
-            />
+                    on_selection_change=move |state| {
+                        set_selection.set(state);
+                        refresh_code_block();
+                        refresh_mention();
+                    }
+                    class="flex flex-1 flex-col p-1"
+                />
+            </div>
         </div>
     }
 }
@@ -0,0 +1,242 @@
+/**
+ * The Scheduled list instructors see with a class's announcements, where announcements and posts
+ * waiting to be published can be edited or cancelled
+ */
+use crate::data::database::schedule_functions::{
+    cancel_scheduled_item, get_scheduled_items, update_scheduled_item, ScheduledItem,
+    ScheduledKind,
+};
+use crate::pages::global_components::local_datetime::{
+    format_local, local_input_to_utc, utc_to_local_input,
+};
+use crate::pages::global_components::markdown_box::MarkdownBox;
+use crate::pages::global_components::rich_text_box::RichTextBox;
+use crate::{expect_logged_in_user, on_input};
+use leptos::*;
+
+#[component]
+pub fn ScheduledItems(
+    class_id: i32,
+    /// Changes whenever something new has been scheduled, so the list is fetched again
+    #[prop(into)]
+    refresh: Signal<usize>,
+) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    let items = create_local_resource(
+        move || (class_id, user().id, refresh.get()),
+        |(class_id, user_id, _)| async move {
+            get_scheduled_items(class_id, user_id).await.unwrap_or_default()
+        },
+    );
+    let (editing, set_editing) = create_signal(None::<(ScheduledKind, i32)>);
+
+    let cancel_action = create_action(move |(kind, id): &(ScheduledKind, i32)| {
+        let (kind, id) = (*kind, *id);
+        async move {
+            match cancel_scheduled_item(user().id, kind, id).await {
+                Ok(_) => items.update(|items| {
+                    if let Some(items) = items.as_mut() {
+                        items.retain(|item| (item.kind, item.id) != (kind, id));
+                    }
+                }),
+                Err(_) => logging::error!("Attempt to cancel scheduled item failed. Please try again"),
+            }
+        }
+    });
+    let on_saved = Callback::new(move |saved: ScheduledItem| {
+        items.update(|items| {
+            if let Some(items) = items.as_mut() {
+                if let Some(item) =
+                    items.iter_mut().find(|item| (item.kind, item.id) == (saved.kind, saved.id))
+                {
+                    *item = saved;
+                }
+                items.sort_by_key(|item| item.publish_at);
+            }
+        });
+        set_editing(None);
+    });
+
+    view! {
+        <div class="p-4 mx-4 mt-2 bg-gray-50 rounded-lg border border-gray-200">
+            <h4 class="mb-2 font-semibold text-gray-700">"Scheduled"</h4>
+            <Transition fallback=move || view! { <p>"Loading scheduled items..."</p> }>
+                {move || {
+                    items()
+                        .map(|items| {
+                            if items.is_empty() {
+                                return view! {
+                                    <p class="text-sm text-gray-500">
+                                        "Nothing is scheduled. Pick a publish time when writing an announcement or post to schedule it."
+                                    </p>
+                                }
+                                    .into_view();
+                            }
+                            items
+                                .into_iter()
+                                .map(|item| {
+                                    let key = (item.kind, item.id);
+                                    if editing() == Some(key) {
+                                        return view! {
+                                            <ScheduledItemEditor
+                                                item
+                                                on_saved
+                                                on_close=move || set_editing(None)
+                                            />
+                                        }
+                                            .into_view();
+                                    }
+                                    view! {
+                                        <div class="flex justify-between items-center py-2 border-b border-gray-200 last:border-b-0">
+                                            <div class="flex flex-col">
+                                                <span class="font-semibold">{item.title.clone()}</span>
+                                                <span class="text-xs text-gray-500">
+                                                    {match item.kind {
+                                                        ScheduledKind::Announcement => "Announcement",
+                                                        ScheduledKind::Post => "Post",
+                                                    }}
+                                                    {format!(
+                                                        " · Publishes {}",
+                                                        format_local(item.publish_at, "%b %-d at %l:%M %p"),
+                                                    )}
+                                                </span>
+                                            </div>
+                                            <div class="flex gap-2">
+                                                <button
+                                                    class="py-1 px-3 text-sm text-gray-700 rounded-full hover:bg-gray-100"
+                                                    on:click=move |_| set_editing(Some(key))
+                                                >
+                                                    "Edit"
+                                                </button>
+                                                <button
+                                                    class="py-1 px-3 text-sm text-red-500 rounded-full hover:bg-gray-100"
+                                                    on:click=move |_| cancel_action.dispatch(key)
+                                                >
+                                                    "Cancel"
+                                                </button>
+                                            </div>
+                                        </div>
+                                    }
+                                        .into_view()
+                                })
+                                .collect_view()
+                        })
+                }}
+            </Transition>
+        </div>
+    }
+}
+
+/**
+ * Form replacing a scheduled item in the list while it is edited
+ */
+#[component]
+fn ScheduledItemEditor<F>(
+    item: ScheduledItem,
+    on_saved: Callback<ScheduledItem>,
+    on_close: F,
+) -> impl IntoView
+where
+    F: Fn() + 'static + Copy,
+{
+    let (user, _) = expect_logged_in_user!();
+    let (title, set_title) = create_signal(item.title.clone());
+    let (contents, set_contents) = create_signal(item.contents.clone());
+    let (publish_at_input, set_publish_at_input) = create_signal(utc_to_local_input(item.publish_at));
+
+    let save_action = create_action(move |item: &ScheduledItem| {
+        let item = item.clone();
+        async move {
+            match update_scheduled_item(
+                user().id,
+                item.kind,
+                item.id,
+                item.title.clone(),
+                item.contents.clone(),
+                item.publish_at,
+            )
+            .await
+            {
+                Ok(_) => on_saved.call(item),
+                Err(_) => logging::error!("Attempt to update scheduled item failed. Please try again"),
+            }
+        }
+    });
+
+    let contents_editor = match item.kind {
+        ScheduledKind::Post => view! {
+            <div class="h-72">
+                <RichTextBox
+                    id=format!("scheduled_post_rich_text_box_{}", item.id)
+                    value=contents
+                    set_value=set_contents
+                />
+            </div>
+        }
+        .into_view(),
+        ScheduledKind::Announcement if item.markdown => view! {
+            <div class="h-48">
+                <MarkdownBox
+                    id=format!("scheduled_announcement_markdown_box_{}", item.id)
+                    value=contents
+                    set_value=set_contents
+                />
+            </div>
+        }
+        .into_view(),
+        ScheduledKind::Announcement => view! {
+            <textarea
+                class="p-2 w-full h-32 rounded-md border border-gray-300"
+                prop:value=contents
+                on:input=on_input!(set_contents)
+            ></textarea>
+        }
+        .into_view(),
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 py-2 border-b border-gray-200 last:border-b-0">
+            <input
+                class="p-2 w-full rounded-md border border-gray-300"
+                type="text"
+                prop:value=title
+                on:input=on_input!(set_title)
+            />
+            {contents_editor}
+            <div class="flex gap-3 justify-end items-center">
+                <label class="flex gap-1 items-center text-sm text-gray-600">
+                    "Publish at"
+                    <input
+                        type="datetime-local"
+                        class="py-0.5 px-1 rounded border border-gray-300"
+                        prop:value=publish_at_input
+                        on:input=move |ev| set_publish_at_input(event_target_value(&ev))
+                    />
+                </label>
+                <button
+                    class="py-1 px-3 text-sm text-gray-600 rounded-full hover:bg-gray-100"
+                    on:click=move |_| on_close()
+                >
+                    "Discard changes"
+                </button>
+                <button
+                    class="py-1 px-3 text-sm text-white rounded-full bg-customBlue hover:bg-customBlue-HOVER"
+                    on:click=move |_| {
+                        let Some(publish_at) = local_input_to_utc(&publish_at_input()) else {
+                            return;
+                        };
+                        save_action
+                            .dispatch(ScheduledItem {
+                                title: title(),
+                                contents: contents(),
+                                publish_at,
+                                ..item.clone()
+                            });
+                    }
+                >
+                    "Save"
+                </button>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,53 @@
+// Uploads files to the server's `/uploads` route from the browser
+use crate::data::database::attachment_functions::{Attachment, MAX_ATTACHMENT_BYTES};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{File, FormData, RequestInit, Response};
+
+/// Turn a JS error into a message that can be shown to the user
+fn js_error(error: JsValue) -> String {
+    error
+        .as_string()
+        .unwrap_or_else(|| "Upload failed. Please try again".to_string())
+}
+
+/// Upload a file for a class, returning the stored attachment or a message to show the user
+pub async fn upload_file(class_id: i32, file: File) -> Result<Attachment, String> {
+    // Checked by the server too, but this saves sending a file that would be rejected
+    if file.size() as usize > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "{} is larger than the {} MB limit",
+            file.name(),
+            MAX_ATTACHMENT_BYTES / (1024 * 1024)
+        ));
+    }
+
+    let form = FormData::new().map_err(js_error)?;
+    form.append_with_blob_and_filename("file", &file, &file.name())
+        .map_err(js_error)?;
+
+    let init = RequestInit::new();
+    init.set_method("POST");
+    init.set_body(&form);
+
+    let window = web_sys::window().ok_or("Uploading needs a browser window")?;
+    let response: Response = JsFuture::from(
+        window.fetch_with_str_and_init(&format!("/uploads?class_id={}", class_id), &init),
+    )
+    .await
+    .map_err(js_error)?
+    .dyn_into()
+    .map_err(js_error)?;
+
+    let body = JsFuture::from(response.text().map_err(js_error)?)
+        .await
+        .map_err(js_error)?
+        .as_string()
+        .unwrap_or_default();
+
+    // Failed uploads respond with a plain text message
+    if !response.ok() {
+        return Err(body);
+    }
+    serde_json::from_str(&body).map_err(|e| e.to_string())
+}
@@ -1,4 +1,5 @@
 use crate::data::database::class_functions::check_user_is_instructor;
+use crate::data::database::group_functions::get_class_groups;
 use crate::data::database::live_poll_functions::*;
 use crate::expect_logged_in_user;
 use crate::pages::global_components::live_poll_sidebar::Sidebar;
@@ -66,10 +67,10 @@ pub fn LivePoll() -> impl IntoView {
         }
     });
 
-    let create_new_poll = move |question: String, answers: Vec<String>| {
+    let create_new_poll = move |question: String, answers: Vec<String>, group_ids: Vec<i32>| {
         spawn_local(async move {
             let course_id = class_id();
-            if (create_poll(question, course_id, answers).await).is_ok() {
+            if (create_poll(question, course_id, answers, group_ids).await).is_ok() {
                 polls_resource.refetch();
             }
             set_show_modal.set(false);
@@ -114,6 +115,7 @@ pub fn LivePoll() -> impl IntoView {
           </Transition>
           <PollCreationModal
             is_visible=show_modal.into()
+            class_id=Signal::derive(class_id)
             on_close=Box::new(move |_| set_show_modal.update(|v| *v = false))
             on_create=Box::new(create_new_poll)
           />
@@ -377,9 +379,17 @@ struct AnswerField {
 #[component]
 pub fn PollCreationModal(
     is_visible: Signal<bool>,
+    class_id: Signal<i32>,
     on_close: Box<dyn Fn(MouseEvent) + 'static>,
-    on_create: Box<dyn Fn(String, Vec<String>) + 'static>,
+    on_create: Box<dyn Fn(String, Vec<String>, Vec<i32>) + 'static>,
 ) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    // The groups the poll is for. None picked means the whole class.
+    let group_ids = create_rw_signal(Vec::<i32>::new());
+    let groups = create_resource(
+        move || (class_id(), user().id),
+        |(class_id, user_id)| async move { get_class_groups(class_id, user_id).await.unwrap_or_default() },
+    );
     let (question, set_question) = create_signal(String::new());
     let (counter, set_counter) = create_signal(0);
     let (answers, set_answers) = create_signal(vec![AnswerField {
@@ -413,8 +423,9 @@ pub fn PollCreationModal(
             .filter(|a| !a.trim().is_empty())
             .collect::<Vec<_>>();
         if !question_text.is_empty() && !answers_list.is_empty() {
-            on_create(question_text, answers_list);
+            on_create(question_text, answers_list, group_ids.get_untracked());
             set_question.set(String::new());
+            group_ids.set(Vec::new());
             set_answers.set(vec![AnswerField {
                 content: RwSignal::new(String::new()),
                 key: {
@@ -469,6 +480,47 @@ pub fn PollCreationModal(
           >
             "+ Add Answer Option"
           </button>
+          <Transition>
+            {move || {
+              groups()
+                .filter(|groups| !groups.is_empty())
+                .map(|groups| {
+                  view! {
+                    <div class="mt-4">
+                      <p class="mb-1 text-sm font-medium text-gray-700">"For groups"</p>
+                      <div class="flex flex-wrap gap-3">
+                        {groups
+                          .into_iter()
+                          .map(|group| {
+                            let group_id = group.group_id;
+                            view! {
+                              <label class="flex gap-1 items-center text-sm cursor-pointer select-none">
+                                <input
+                                  type="checkbox"
+                                  prop:checked=move || group_ids.with(|ids| ids.contains(&group_id))
+                                  on:change=move |_| {
+                                    group_ids
+                                      .update(|ids| {
+                                        if ids.contains(&group_id) {
+                                          ids.retain(|id| *id != group_id);
+                                        } else {
+                                          ids.push(group_id);
+                                        }
+                                      })
+                                  }
+                                />
+                                {group.name}
+                              </label>
+                            }
+                          })
+                          .collect_view()}
+                      </div>
+                      <p class="mt-1 text-xs text-gray-500">"Leave all unchecked to poll the whole class"</p>
+                    </div>
+                  }
+                })
+            }}
+          </Transition>
           <div class="flex justify-end mt-4 space-x-4">
             <button class="py-2 px-4 bg-gray-300 rounded-lg hover:bg-gray-400" on:click=on_close>
               "Cancel"
@@ -0,0 +1,114 @@
+/**
+ * Component listing the user's unsent drafts, shown on the profile page
+ */
+use crate::data::database::draft_functions::{delete_draft, get_drafts, DraftContext, DraftSummary};
+use crate::expect_logged_in_user;
+use crate::resources::images::svgs::edit_post_icon::EditPostIcon;
+use leptos::*;
+use leptos_router::A;
+
+/// Longest preview shown of a draft without a title
+const PREVIEW_LENGTH: usize = 80;
+
+/// The draft's title, or the start of its contents as plain text if it has none
+fn preview(draft: &DraftSummary) -> String {
+    if !draft.title.trim().is_empty() {
+        return draft.title.clone();
+    }
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in draft.contents.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > PREVIEW_LENGTH {
+        format!("{}…", text.chars().take(PREVIEW_LENGTH).collect::<String>())
+    } else {
+        text
+    }
+}
+
+#[component]
+pub fn DraftsList() -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+
+    let drafts = create_resource(
+        move || user().id,
+        |user_id| async move { get_drafts(user_id).await.unwrap_or_default() },
+    );
+
+    let delete_action = create_action(move |(context, user_id): &(DraftContext, i32)| {
+        let context = *context;
+        let user_id = *user_id;
+        async move {
+            match delete_draft(user_id, context).await {
+                Ok(_) => drafts.update(|drafts| {
+                    if let Some(drafts) = drafts.as_mut() {
+                        drafts.retain(|draft| draft.context != context);
+                    }
+                }),
+                Err(_) => logging::error!("Attempt to delete draft failed. Please try again"),
+            }
+        }
+    });
+
+    view! {
+      <div class="p-6 mx-auto mt-8 max-w-2xl bg-white rounded-lg shadow-md">
+        <h2 class="flex gap-2 items-center mb-4 text-2xl font-bold text-gray-800">
+          <EditPostIcon size="1em" />
+          "Drafts"
+        </h2>
+        <Transition fallback=move || view! { <p>"Loading drafts..."</p> }>
+          {move || {
+            drafts()
+              .map(|drafts| {
+                if drafts.is_empty() {
+                  view! {
+                    <p class="text-sm text-gray-500">
+                      "You don't have any drafts. Posts, replies and announcements are saved here while you write them."
+                    </p>
+                  }
+                    .into_view()
+                } else {
+                  view! {
+                    <ul class="divide-y divide-gray-200">
+                      <For each=move || drafts.clone() key=|draft| draft.draft_id let:draft>
+                        <li class="flex justify-between items-center py-2">
+                          <A href=draft.href() class="flex flex-col hover:underline">
+                            <span class="font-semibold">{preview(&draft)}</span>
+                            <span class="text-xs text-gray-500">
+                              {format!(
+                                "{} · {} · Saved {}",
+                                draft.description(),
+                                draft.class_name,
+                                draft.updated_at.format("%Y-%m-%d %H:%M"),
+                              )}
+                            </span>
+                          </A>
+                          <button
+                            class="py-1 px-3 text-sm text-gray-700 rounded-full hover:bg-gray-100"
+                            on:click=move |_| delete_action.dispatch((draft.context, user().id))
+                          >
+                            "Delete"
+                          </button>
+                        </li>
+                      </For>
+                    </ul>
+                  }
+                    .into_view()
+                }
+              })
+          }}
+        </Transition>
+      </div>
+    }
+    .into_view()
+}
@@ -0,0 +1,63 @@
+use crate::data::database::user_functions::get_user_by_id;
+use crate::pages::global_components::header::Header;
+use crate::pages::global_components::sidebar::Sidebar;
+use leptos::*;
+use leptos_router::{use_params, Params};
+
+#[derive(Params, PartialEq, Clone)]
+pub struct MemberId {
+    pub user_id: i32,
+}
+
+/// Renders another user's profile, which @mentions link to
+#[component]
+pub fn MemberProfile() -> impl IntoView {
+    let user_id = {
+        let params = use_params::<MemberId>();
+        move || params().map(|params| params.user_id).ok()
+    };
+    let member = create_resource(user_id, |user_id| async move {
+        match user_id {
+            Some(user_id) => get_user_by_id(user_id).await.ok(),
+            None => None,
+        }
+    });
+
+    view! {
+      <div class="flex">
+        <Sidebar />
+        <div class="flex-1">
+          <Header />
+          <div class="p-6 mx-auto mt-8 max-w-2xl bg-white rounded-lg shadow-md">
+            <Suspense fallback=|| view! { <p>"Loading profile..."</p> }>
+              {move || {
+                member()
+                  .map(|member| match member {
+                    Some(member) => {
+                      view! {
+                        <div class="flex gap-6 items-center">
+                          <img
+                            src="/images/user_profile/UserProfileIcon.svg"
+                            alt="User Profile Icon"
+                            class="w-24 h-24 rounded-full bg-customBlue"
+                          />
+                          <div class="flex flex-col gap-1">
+                            <h2 class="text-2xl font-bold text-gray-800">
+                              {format!("{} {}", member.firstname, member.lastname)}
+                            </h2>
+                            <p class="text-gray-500">{member.username.clone()}</p>
+                            <p class="text-sm font-semibold text-customBlue">{member.role.clone()}</p>
+                          </div>
+                        </div>
+                      }
+                        .into_view()
+                    }
+                    None => view! { <p>"This user could not be found."</p> }.into_view(),
+                  })
+              }}
+            </Suspense>
+          </div>
+        </div>
+      </div>
+    }
+}
@@ -1 +1,4 @@
+pub mod drafts;
+pub mod member_profile;
 pub mod user_profile_page;
+pub mod watched_posts;
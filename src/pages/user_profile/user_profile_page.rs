@@ -5,6 +5,8 @@ use crate::pages::global_components::notification::{
     NotificationComponent, NotificationDetails, NotificationType,
 };
 use crate::pages::global_components::sidebar::Sidebar;
+use crate::pages::user_profile::drafts::DraftsList;
+use crate::pages::user_profile::watched_posts::WatchedPostsList;
 use crate::{expect_logged_in_user, on_input};
 use leptos::ev::SubmitEvent;
 use leptos::{component, create_action, create_signal, view, IntoView};
@@ -129,6 +131,8 @@ pub fn UserProfile() -> impl IntoView {
               {notification_view}
             </form>
           </div>
+          <DraftsList />
+          <WatchedPostsList />
         </div>
       </div>
     }.into_view()
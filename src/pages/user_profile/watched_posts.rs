@@ -0,0 +1,84 @@
+/**
+ * Component listing the posts the user is watching, shown on the profile page
+ */
+use crate::data::database::watch_functions::{get_watched_posts, set_post_watched};
+use crate::expect_logged_in_user;
+use crate::resources::images::svgs::watch_icon::WatchIcon;
+use leptos::*;
+use leptos_router::A;
+
+#[component]
+pub fn WatchedPostsList() -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+
+    let watched_posts = create_resource(
+        move || user().id,
+        |user_id| async move { get_watched_posts(user_id).await.unwrap_or_default() },
+    );
+
+    let unwatch_action = create_action(move |(post_id, user_id): &(i32, i32)| {
+        let post_id = *post_id;
+        let user_id = *user_id;
+        async move {
+            match set_post_watched(post_id, user_id, false).await {
+                Ok(_) => watched_posts.update(|posts| {
+                    if let Some(posts) = posts.as_mut() {
+                        posts.retain(|post| post.post_id != post_id);
+                    }
+                }),
+                Err(_) => logging::error!("Attempt to unwatch post failed. Please try again"),
+            }
+        }
+    });
+
+    view! {
+      <div class="p-6 mx-auto mt-8 max-w-2xl bg-white rounded-lg shadow-md">
+        <h2 class="flex gap-2 items-center mb-4 text-2xl font-bold text-gray-800">
+          <WatchIcon size="1em" />
+          "Watching"
+        </h2>
+        <Transition fallback=move || view! { <p>"Loading watched posts..."</p> }>
+          {move || {
+            watched_posts()
+              .map(|posts| {
+                if posts.is_empty() {
+                  view! {
+                    <p class="text-sm text-gray-500">
+                      "You aren't watching any posts yet. Posts you create or reply to are watched automatically."
+                    </p>
+                  }
+                    .into_view()
+                } else {
+                  view! {
+                    <ul class="divide-y divide-gray-200">
+                      <For each=move || posts.clone() key=|post| post.post_id let:post>
+                        <li class="flex justify-between items-center py-2">
+                          <A
+                            href=format!("/classes/{}/{}", post.class_id, post.post_id)
+                            class="flex flex-col hover:underline"
+                          >
+                            <span class="font-semibold">{post.title.clone()}</span>
+                            <span class="text-xs text-gray-500">
+                              {post.class_name.clone()}
+                              {if post.resolved { " · Resolved" } else { "" }}
+                            </span>
+                          </A>
+                          <button
+                            class="py-1 px-3 text-sm text-gray-700 rounded-full hover:bg-gray-100"
+                            on:click=move |_| unwatch_action.dispatch((post.post_id, user().id))
+                          >
+                            "Unwatch"
+                          </button>
+                        </li>
+                      </For>
+                    </ul>
+                  }
+                    .into_view()
+                }
+              })
+          }}
+        </Transition>
+      </div>
+    }
+    .into_view()
+}
@@ -1,4 +1,7 @@
-use crate::data::database::user_functions::{update_user_password, update_user_without_password};
+use crate::data::database::user_functions::{
+    get_markdown_authoring, set_markdown_authoring, update_user_password,
+    update_user_without_password,
+};
 use crate::data::global_state::User;
 use crate::expect_logged_in_user;
 use crate::pages::global_components::header::Header;
@@ -8,8 +11,8 @@ use crate::pages::global_components::notification::{
 use crate::pages::global_components::sidebar::Sidebar;
 use leptos::ev::SubmitEvent;
 use leptos::{
-    component, create_action, create_effect, create_signal, view, IntoView,
-    SignalGetUntracked,
+    component, create_action, create_effect, create_resource, create_signal, view, IntoView,
+    SignalGetUntracked, SignalSet, Transition,
 };
 use leptos_router::use_navigate;
 use wasm_bindgen::JsCast;
@@ -24,6 +27,16 @@ pub fn UserSettings() -> impl IntoView {
     let (confirm_password, set_confirm_password) = create_signal(String::new());
     let (update_error, set_update_error) = create_signal(None::<NotificationDetails>);
 
+    let markdown_authoring = create_resource(move || user().id, get_markdown_authoring);
+    let set_markdown_action = create_action(move |markdown: &bool| {
+        let markdown = *markdown;
+        async move {
+            if set_markdown_authoring(user.get_untracked().id, markdown).await.is_ok() {
+                markdown_authoring.set(Ok(markdown));
+            }
+        }
+    });
+
     let update_user_action = create_action(
         move |(updated_user, new_password): &(User, Option<String>)| {
             let updated_user = updated_user.clone();
@@ -160,6 +173,31 @@ pub fn UserSettings() -> impl IntoView {
               {notification_view}
             </form>
           </div>
+          <div class="p-6 mx-auto my-8 max-w-2xl bg-white rounded-lg shadow-md">
+            <h2 class="mb-4 text-2xl font-bold text-gray-800">Editor</h2>
+            <Transition fallback=|| ()>
+              {move || {
+                markdown_authoring()
+                  .map(|markdown| {
+                    let markdown = markdown.unwrap_or(false);
+                    view! {
+                      <label class="flex gap-3 items-center text-sm font-semibold text-gray-700">
+                        <input
+                          type="checkbox"
+                          class="w-4 h-4"
+                          prop:checked=markdown
+                          on:change=move |_| set_markdown_action.dispatch(!markdown)
+                        />
+                        "Write posts, replies and announcements in Markdown"
+                      </label>
+                      <p class="mt-2 text-sm text-gray-500">
+                        "Markdown is turned into the same formatting as the rich text editor, so readers see no difference."
+                      </p>
+                    }
+                  })
+              }}
+            </Transition>
+          </div>
         </div>
       </div>
     }.into_view()
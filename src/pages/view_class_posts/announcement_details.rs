@@ -1,4 +1,5 @@
-use crate::data::database::announcement_functions::get_announcement_by_id;
+use crate::data::database::announcement_functions::{get_announcement_by_id, mark_announcement_read};
+use crate::expect_logged_in_user;
 use leptos::*;
 use leptos_router::{use_params, Params};
 
@@ -15,27 +16,42 @@ pub fn AnnouncementDetails() -> impl IntoView {
         move || announcement_id_result().expect("Tried to render announcement details page without announcement id").announcement_id
     };
 
+    let (user, _) = expect_logged_in_user!();
+    // Announcements for groups the user isn't in aren't found
     let announcement = create_resource(
-        announcement_id,
-        |announcement_id| async move {get_announcement_by_id(announcement_id).await.unwrap()}
+        move || (announcement_id(), user().id),
+        |(announcement_id, user_id)| async move {get_announcement_by_id(announcement_id, user_id).await.ok()}
     );
 
+    // Opening an announcement counts towards its read receipts
+    create_effect(move |_| {
+        let (announcement_id, user_id) = (announcement_id(), user().id);
+        spawn_local(async move {
+            if let Err(e) = mark_announcement_read(announcement_id, user_id).await {
+                logging::error!("Failed to mark announcement as read: {:?}", e);
+            }
+        });
+    });
+
     view! {
       <div class="announcement-details">
         <div class=format!("bg-[#EEEEEE] rounded-xl")>
           <Suspense fallback=|| {
             view! { <p>{"Loading announcement..."}</p> }
           }>
-            {move || announcement().map(|announcement_details| 
-              view! {
+            {move || announcement().map(|announcement_details| match announcement_details {
+              None => view! { <p class="p-2">"Announcement not found"</p> }.into_view(),
+              Some(announcement_details) => view! {
                 <div class="p-2 border-b border-gray-300">
                   <h4 class="font-bold">{announcement_details.title.clone()}</h4>
-                  <p class="text-sm">{announcement_details.contents.clone()}</p>
+                  <div class="text-sm" inner_html=announcement_details.rendered_contents.clone()></div>
                   <p class="text-xs text-gray-500">
                     {announcement_details.time.format("%Y-%m-%d %H:%M:%S").to_string()}
+                    {announcement_details.edited_at.is_some().then_some(" · Edited")}
                   </p>
                 </div>
-              })
+              }.into_view(),
+            })
             }
           </Suspense>
         </div>
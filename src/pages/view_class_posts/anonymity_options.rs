@@ -0,0 +1,47 @@
+/**
+ * The second anonymity choice shown under an "anonymous" toggle: anonymous posts and replies are
+ * hidden from classmates, and also from instructors when the author picks it and the class allows it
+ */
+use crate::data::database::class_functions::get_class_allows_full_anonymity;
+use leptos::*;
+
+#[component]
+pub fn AnonymityOptions(
+    class_id: i32,
+    #[prop(into)] anonymous: Signal<bool>,
+    fully_anonymous: RwSignal<bool>,
+) -> impl IntoView {
+    let allows_full_anonymity = create_resource(
+        move || class_id,
+        |class_id| async move {
+            get_class_allows_full_anonymity(class_id).await.unwrap_or(false)
+        },
+    );
+    // A post that is already hidden from instructors can stay that way after the class stops allowing it
+    let already_hidden = fully_anonymous.get_untracked();
+
+    view! {
+      <Show when=anonymous>
+        {move || {
+            if already_hidden || allows_full_anonymity().unwrap_or(false) {
+                view! {
+                  <label class="flex gap-1 items-center text-sm text-gray-600 cursor-pointer select-none">
+                    <input
+                      type="checkbox"
+                      prop:checked=fully_anonymous
+                      on:change=move |_| fully_anonymous.update(|hidden| *hidden = !*hidden)
+                    />
+                    "Hide my name from instructors too"
+                  </label>
+                }
+                    .into_view()
+            } else {
+                view! {
+                  <span class="text-sm text-gray-500">"Instructors can still see your name"</span>
+                }
+                    .into_view()
+            }
+        }}
+      </Show>
+    }
+}
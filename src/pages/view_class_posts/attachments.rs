@@ -0,0 +1,152 @@
+/**
+ * AttachmentUploader component for adding files to a post or reply before it is submitted, and
+ * AttachmentList for showing the files attached to one
+ */
+use crate::data::database::attachment_functions::Attachment;
+use crate::pages::global_components::upload::upload_file;
+use leptoaster::*;
+use leptos::*;
+use web_sys::HtmlInputElement;
+
+/// A file size in the largest unit that keeps it above 1
+fn format_size(size_bytes: i64) -> String {
+    match size_bytes {
+        size if size < 1024 => format!("{} B", size),
+        size if size < 1024 * 1024 => format!("{:.1} KB", size as f64 / 1024.0),
+        size => format!("{:.1} MB", size as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+/**
+ * A file picker that uploads files as soon as they are chosen. Finished uploads are added to
+ * `attachments`, whose ids should be sent along when the post or reply is submitted.
+ */
+#[component]
+pub fn AttachmentUploader(class_id: i32, attachments: RwSignal<Vec<Attachment>>) -> impl IntoView {
+    let toaster = expect_toaster();
+    let (uploading, set_uploading) = create_signal(0usize);
+
+    let on_change = move |ev: ev::Event| {
+        let input: HtmlInputElement = event_target(&ev);
+        let Some(files) = input.files() else {
+            return;
+        };
+        for index in 0..files.length() {
+            let Some(file) = files.get(index) else {
+                continue;
+            };
+            set_uploading.update(|count| *count += 1);
+            let toaster = toaster.clone();
+            spawn_local(async move {
+                match upload_file(class_id, file).await {
+                    Ok(attachment) => attachments.update(|attachments| attachments.push(attachment)),
+                    Err(message) => toaster.toast(
+                        ToastBuilder::new(format!("Failed to upload file: {}", message))
+                            .with_level(ToastLevel::Error)
+                            .with_dismissable(true)
+                            .with_expiry(Some(4_000))
+                            .with_progress(false)
+                            .with_position(ToastPosition::BottomRight),
+                    ),
+                }
+                set_uploading.update(|count| *count -= 1);
+            });
+        }
+        // Let the same file be picked again after removing it
+        input.set_value("");
+    };
+
+    view! {
+      <div class="flex flex-col gap-2">
+        <div class="flex gap-2 items-center">
+          <label class="py-1 px-3 text-sm bg-white rounded-full border border-gray-300 cursor-pointer hover:bg-gray-100">
+            "Attach files"
+            <input
+              type="file"
+              multiple
+              class="hidden"
+              accept="image/png,image/jpeg,image/gif,image/webp,application/pdf,text/plain,application/zip"
+              on:change=on_change
+            />
+          </label>
+          <Show when=move || { uploading() > 0 } fallback=|| ()>
+            <span class="text-sm text-gray-500">"Uploading..."</span>
+          </Show>
+        </div>
+        <div class="flex flex-wrap gap-2">
+          <For
+            each=attachments
+            key=|attachment| attachment.attachment_id
+            children=move |attachment| {
+              let attachment_id = attachment.attachment_id;
+              view! {
+                <span class="inline-flex gap-1 items-center py-0.5 px-2 text-sm bg-gray-100 rounded-full">
+                  {attachment.filename.clone()}
+                  <button
+                    class="text-gray-500 hover:text-red-600"
+                    title="Remove"
+                    on:click=move |_| {
+                      attachments
+                        .update(|attachments| {
+                          attachments.retain(|attachment| attachment.attachment_id != attachment_id)
+                        })
+                    }
+                  >
+                    "×"
+                  </button>
+                </span>
+              }
+            }
+          />
+        </div>
+      </div>
+    }
+}
+
+/**
+ * The files attached to a post or reply: thumbnails for images and download links for everything else
+ */
+#[component]
+pub fn AttachmentList(attachments: Vec<Attachment>) -> impl IntoView {
+    let (images, files): (Vec<Attachment>, Vec<Attachment>) =
+        attachments.into_iter().partition(Attachment::is_image);
+    let has_images = !images.is_empty();
+
+    view! {
+      <div class="flex flex-col gap-2">
+        <Show when=move || has_images fallback=|| ()>
+          <div class="flex flex-wrap gap-2">
+            {images
+              .iter()
+              .map(|image| {
+                view! {
+                  <a href=image.url() target="_blank" title=image.filename.clone()>
+                    <img
+                      class="object-cover w-32 h-32 rounded-lg border border-gray-300"
+                      src=image.thumbnail_url().unwrap_or_else(|| image.url())
+                      alt=image.filename.clone()
+                    />
+                  </a>
+                }
+              })
+              .collect_view()}
+          </div>
+        </Show>
+        {files
+          .into_iter()
+          .map(|file| {
+            view! {
+              <a
+                href=file.url()
+                class="inline-flex gap-2 items-center text-sm text-customBlue hover:underline"
+              >
+                "📎"
+                {file.filename.clone()}
+                <span class="text-gray-500">{format_size(file.size_bytes)}</span>
+              </a>
+            }
+          })
+          .collect_view()}
+      </div>
+    }
+}
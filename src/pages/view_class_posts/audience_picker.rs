@@ -0,0 +1,162 @@
+/**
+ * AudiencePicker component, choosing the classmates and groups a private post is shared with. The
+ * instructor can always see private posts, so only the other members of the class are offered.
+ */
+use crate::data::database::class_functions::get_users_enrolled_in_class;
+use crate::data::database::group_functions::get_class_groups;
+use crate::expect_logged_in_user;
+use leptos::*;
+
+/// Number of class members and groups offered while a name is typed
+const AUDIENCE_SUGGESTIONS: usize = 6;
+
+#[derive(Clone, Copy, PartialEq)]
+enum AudienceChoice {
+    Member(i32),
+    Group(i32),
+}
+
+#[component]
+pub fn AudiencePicker(
+    class_id: i32,
+    audience: RwSignal<Vec<i32>>,
+    audience_groups: RwSignal<Vec<i32>>,
+) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    let members = create_resource(
+        move || class_id,
+        |class_id| async move { get_users_enrolled_in_class(class_id).await.unwrap_or_default() },
+    );
+    let groups = create_resource(
+        move || (class_id, user().id),
+        |(class_id, user_id)| async move { get_class_groups(class_id, user_id).await.unwrap_or_default() },
+    );
+    let (query, set_query) = create_signal(String::new());
+
+    let choice_name = move |choice: AudienceChoice| match choice {
+        AudienceChoice::Member(user_id) => members
+            .get()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|member| member.user_id == user_id)
+            .map(|member| member.full_name)
+            .unwrap_or_default(),
+        AudienceChoice::Group(group_id) => groups
+            .get()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|group| group.group_id == group_id)
+            .map(|group| format!("{} (group)", group.name))
+            .unwrap_or_default(),
+    };
+    let chosen = move || {
+        audience()
+            .into_iter()
+            .map(AudienceChoice::Member)
+            .chain(audience_groups().into_iter().map(AudienceChoice::Group))
+            .collect::<Vec<_>>()
+    };
+    let remove = move |choice: AudienceChoice| match choice {
+        AudienceChoice::Member(user_id) => audience.update(|audience| audience.retain(|id| *id != user_id)),
+        AudienceChoice::Group(group_id) => {
+            audience_groups.update(|groups| groups.retain(|id| *id != group_id))
+        }
+    };
+    let add = move |choice: AudienceChoice| match choice {
+        AudienceChoice::Member(user_id) => audience.update(|audience| audience.push(user_id)),
+        AudienceChoice::Group(group_id) => audience_groups.update(|groups| groups.push(group_id)),
+    };
+    let suggestions = move || {
+        let query = query().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let matching_groups = groups
+            .get()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|group| {
+                !audience_groups.with(|groups| groups.contains(&group.group_id))
+                    && group.name.to_lowercase().contains(&query)
+            })
+            .map(|group| (format!("{} (group)", group.name), AudienceChoice::Group(group.group_id)));
+        let matching_members = members
+            .get()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|member| {
+                member.role != "Instructor"
+                    && member.user_id != user().id
+                    && !audience.with(|audience| audience.contains(&member.user_id))
+                    && member.full_name.to_lowercase().contains(&query)
+            })
+            .map(|member| {
+                (
+                    format!("{} ({})", member.full_name, member.role),
+                    AudienceChoice::Member(member.user_id),
+                )
+            });
+        matching_groups
+            .chain(matching_members)
+            .take(AUDIENCE_SUGGESTIONS)
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+      <div class="flex flex-col gap-1">
+        <div class="flex flex-wrap gap-2 items-center">
+          <span class="text-sm text-gray-600">"Shared with the instructor"</span>
+          {move || {
+              chosen()
+                  .into_iter()
+                  .map(|choice| {
+                      view! {
+                        <span class="flex gap-1 items-center py-0.5 px-2 text-sm bg-white rounded-full border border-gray-300">
+                          {move || choice_name(choice)}
+                          <button
+                            class="text-gray-500 hover:text-red-500"
+                            title="Stop sharing"
+                            on:click=move |_| remove(choice)
+                          >
+                            "×"
+                          </button>
+                        </span>
+                      }
+                  })
+                  .collect_view()
+          }}
+        </div>
+        <div class="relative">
+          <input
+            class="py-1 px-2 w-64 text-sm rounded-md border border-gray-300"
+            type="text"
+            placeholder="Also share with a classmate or group..."
+            prop:value=query
+            on:input=move |ev| set_query(event_target_value(&ev))
+          />
+          <Show when=move || !suggestions().is_empty()>
+            <ul class="absolute z-10 w-64 bg-white rounded-md border border-gray-300 shadow-md">
+              {move || {
+                  suggestions()
+                      .into_iter()
+                      .map(|(label, choice)| {
+                          view! {
+                            <li
+                              class="py-1 px-2 text-sm cursor-pointer hover:bg-gray-100"
+                              on:mousedown=move |_| {
+                                  add(choice);
+                                  set_query(String::new());
+                              }
+                            >
+                              {label}
+                            </li>
+                          }
+                      })
+                      .collect_view()
+              }}
+            </ul>
+          </Show>
+        </div>
+      </div>
+    }
+}
@@ -1,30 +1,31 @@
-use std::collections::HashSet;
+use super::post_filter_bar::{post_query_from_params, PostFilterBar};
 use super::question_tile::QuestionTile;
-use crate::data::database::announcement_functions::get_announcement_list;
+use super::search_results::SearchResults;
+use crate::data::database::announcement_functions::get_announcement_page;
 use crate::data::database::post_functions::get_posts;
-use crate::data::database::post_functions::get_search_posts;
+use crate::data::database::post_functions::PostCursor;
+use crate::data::database::post_functions::POSTS_PAGE_SIZE;
 use crate::data::database::post_functions::Post;
 use crate::data::database::post_functions::PostFetcher;
+use crate::data::database::post_view_functions::mark_class_read;
+use crate::data::database::search_functions::search_class_posts;
 use crate::expect_logged_in_user;
 use crate::pages::global_components::announcements::Announcements;
 use crate::pages::global_components::header::Header;
+use crate::pages::global_components::infinite_scroll::InfiniteScroll;
 use crate::pages::global_components::sidebar::Sidebar;
 use crate::pages::view_class_posts::create_post::CreatePost;
 use crate::resources::images::svgs::cancel_icon::CancelIcon;
 use crate::resources::images::svgs::magnifying_glass::MagnifyingGlass;
+use crate::resources::images::svgs::pin_icon::PinIcon;
 use leptos::*;
-use leptos_router::{use_params, Outlet, Params};
+use leptos_router::{use_params, use_query_map, Outlet, Params};
 
 #[derive(Params, PartialEq, Clone, Default)]
 pub struct ClassId {
     pub class_id: i32,
 }
 
-#[derive(Params, PartialEq, Clone)]
-pub struct FilterKeywords {
-    keywords: String,
-}
-
 /**
  * Page getting and displaying all posts in a class
  */
@@ -38,44 +39,109 @@ pub fn ClassPage() -> impl IntoView {
       move || class_params().expect("Tried to render class page without class id").class_id
     };
      
-    let (is_visible, set_is_visible) = create_signal(false);
+    // Filters and sort order live in the URL query string, see PostFilterBar
+    let query_map = use_query_map();
 
-    let filter_input_node: NodeRef<html::Input> = NodeRef::new();
+    // Drafts of new posts link here with ?compose=post to open the composer
+    let (is_visible, set_is_visible) = create_signal(
+        query_map.with_untracked(|query| query.get("compose").map(String::as_str) == Some("post")),
+    );
 
+    let filter_input_node: NodeRef<html::Input> = NodeRef::new();
     let post_data = move || PostFetcher {
         class_id: class_id(),
         user_id: user().id,
+        query: query_map.with(post_query_from_params),
     };
     let posts = create_resource(
       post_data,
         |post_data| async move {
-            get_posts(post_data.class_id, post_data.user_id)
+            get_posts(post_data.class_id, post_data.user_id, post_data.query, None)
                 .await
                 .unwrap_or_default()
         },
     );
     provide_context(posts); // This is not great because resources should be very carefully managed and used in <suspense> or <transition> tags
 
-    let filtered_post_ids_action = create_action(|(filter_string, class_id, user_id): &(String, i32, i32)| {
-      let filter_keywords = filter_string.clone();
+    // Set once a page comes back short, reset whenever the first page is fetched again
+    let (reached_last_page, set_reached_last_page) = create_signal(false);
+    create_effect(move |_| {
+      post_data();
+      set_reached_last_page(false);
+    });
+    let has_more_posts = move || {
+      !reached_last_page()
+        && posts
+          .get()
+          .is_some_and(|posts| posts.iter().filter(|post| !post.pinned).count() >= POSTS_PAGE_SIZE)
+    };
+
+    let load_more_posts_action = create_action(move |post_data: &PostFetcher| {
+      let post_data = *post_data;
+      let cursor = posts.with_untracked(|posts| posts.as_deref().and_then(|posts| PostCursor::after(posts, post_data.query.sort)));
+      async move {
+        match get_posts(post_data.class_id, post_data.user_id, post_data.query, cursor).await {
+          Ok(page) => {
+            if page.len() < POSTS_PAGE_SIZE {
+              set_reached_last_page(true);
+            }
+            posts.update(|posts| {
+              if let Some(posts) = posts.as_mut() {
+                for post in page {
+                  if !posts.iter().any(|loaded| loaded.post_id == post.post_id) {
+                    posts.push(post);
+                  }
+                }
+              }
+            });
+          }
+          Err(_) => logging::error!("Attempt to load more posts failed. Please try again"),
+        }
+      }
+    });
+
+    let search_action = create_action(|(query, class_id, user_id): &(String, i32, i32)| {
+      let query = query.clone();
       let class_id = *class_id;
       let user_id = *user_id;
       async move {
-        if filter_keywords.is_empty() {
+        if query.trim().is_empty() {
             return None;
         }
-        get_search_posts(
-            class_id,
-            user_id,
-            filter_keywords,
-        )
-        .await.ok()
+        Some(search_class_posts(class_id, user_id, query).await)
     }});
+    let is_searching = move || search_action.value()().flatten().is_some();
+
+    let mark_all_read_action = create_action(move |(class_id, user_id): &(i32, i32)| {
+      let class_id = *class_id;
+      let user_id = *user_id;
+      async move {
+        match mark_class_read(class_id, user_id).await {
+          Ok(_) => posts.update(|posts| {
+            if let Some(posts) = posts.as_mut() {
+              for post in posts.iter_mut() {
+                post.unread = false;
+                post.new_replies = 0;
+              }
+            }
+          }),
+          Err(_) => logging::error!("Attempt to mark class as read failed. Please try again"),
+        }
+      }
+    });
+
+    let pinned_posts = move || {
+      posts()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|post| post.pinned)
+        .collect::<Vec<Post>>()
+    };
 
     let announcements = create_resource(
-        class_id,
-        |class_id| async move {
-            get_announcement_list(class_id)
+        move || (class_id(), user().id),
+        |(class_id, user_id)| async move {
+            get_announcement_page(class_id, user_id, None)
                 .await
                 .unwrap_or_else(|_| vec![])
         },
@@ -96,16 +162,17 @@ pub fn ClassPage() -> impl IntoView {
               <div class="flex relative items-center p-2 bg-white rounded-full border border-gray-300 shadow-xl focus-within:border-blue-500 w-[30rem]">
                 <input
                   type="text"
-                  placeholder="Search posts by keywords..."
+                  placeholder="Search posts and replies..."
+                  title="Use \"quotes\" for phrases, -word to exclude, author:name, is:resolved, is:private, tag:pinned, after:2026-09-01, before:2026-10-01"
                   class="py-1.5 pr-16 pl-4 w-full bg-white border-none focus:outline-none"
                   node_ref=filter_input_node
                   on:keydown=move |ev: web_sys::KeyboardEvent| {
-                    let filter_keywords = event_target_value(&ev);
+                    let query = event_target_value(&ev);
                     if ev.key() == "Enter" {
-                      if filter_keywords.is_empty() {
-                        filtered_post_ids_action.value().set(None);
+                      if query.is_empty() {
+                        search_action.value().set(None);
                       } else {
-                        filtered_post_ids_action.dispatch((filter_keywords, class_id(), user().id));
+                        search_action.dispatch((query, class_id(), user().id));
                       }
                     }
                   }
@@ -113,7 +180,7 @@ pub fn ClassPage() -> impl IntoView {
                 <button
                   class="flex absolute top-0 right-0 bottom-0 justify-center items-center bg-gradient-to-r rounded-r-full transition-all duration-200 w-[4rem] bg-[#AAAA] hover:bg-[#999999]"
                   on:click=move |_| {
-                  filtered_post_ids_action.dispatch((filter_input_node.get().expect("filter input above this button should exist").value(), class_id(), user().id));
+                  search_action.dispatch((filter_input_node.get().expect("filter input above this button should exist").value(), class_id(), user().id));
                   }
                 >
                   <MagnifyingGlass size="2em" />
@@ -162,14 +229,74 @@ pub fn ClassPage() -> impl IntoView {
                 })
               }
             </Transition>
-            <div class="grid grid-cols-3 gap-4">
-              <Transition fallback=move || view! { <p>"Loading..."</p> }>
-                <FilteredPostsGrid
-                  unfiltered_posts=(move || posts().unwrap_or_default()).into_signal()
-                  filtered_ids=(move || filtered_post_ids_action.value()().flatten()).into_signal()
-                />
-              </Transition>
+            <div class="flex gap-4 justify-between items-center">
+              <PostFilterBar />
+              <button
+                class="py-1 px-3 text-sm text-gray-700 bg-white rounded-full shadow hover:bg-gray-100"
+                on:click=move |_| mark_all_read_action.dispatch((class_id(), user().id))
+              >
+                "Mark all read"
+              </button>
             </div>
+            // Search results replace the grid until the search is cleared
+            {move || {
+              search_action
+                .value()()
+                .flatten()
+                .map(|results| {
+                  view! {
+                    <div class="flex justify-between items-center">
+                      <h2 class="text-xl font-bold">"Search results"</h2>
+                      <button
+                        class="py-1 px-3 text-sm text-gray-700 bg-white rounded-full shadow hover:bg-gray-100"
+                        on:click=move |_| {
+                          if let Some(input) = filter_input_node.get() {
+                            input.set_value("");
+                          }
+                          search_action.value().set(None);
+                        }
+                      >
+                        "Clear search"
+                      </button>
+                    </div>
+                    {match results {
+                      Ok(results) => view! { <SearchResults class_id=class_id() results /> }.into_view(),
+                      Err(e) => view! { <p class="text-red-600">{e.to_string()}</p> }.into_view(),
+                    }}
+                  }
+                })
+            }}
+            <Show when=move || !is_searching() fallback=|| ()>
+              // Pinned posts get their own section above the rest
+              <Transition fallback=|| ()>
+                <Show when=move || !pinned_posts().is_empty() fallback=|| ()>
+                  <h2 class="flex gap-2 items-center text-xl font-bold">
+                    <PinIcon size="1em" />
+                    "Pinned"
+                  </h2>
+                  <div class="grid grid-cols-3 gap-4">
+                    <FilteredPostsGrid
+                      unfiltered_posts=pinned_posts.into_signal()
+                    />
+                  </div>
+                </Show>
+              </Transition>
+              <div class="grid grid-cols-3 gap-4">
+                <Transition fallback=move || view! { <p>"Loading..."</p> }>
+                  <FilteredPostsGrid
+                    unfiltered_posts=(move || {
+                        posts().unwrap_or_default().into_iter().filter(|post| !post.pinned).collect()
+                      })
+                      .into_signal()
+                  />
+                </Transition>
+                <InfiniteScroll
+                  has_more=Signal::derive(has_more_posts)
+                  loading=load_more_posts_action.pending()
+                  on_load_more=move || load_more_posts_action.dispatch(post_data())
+                />
+              </div>
+            </Show>
           </div>
         </div>
       </div>
@@ -177,18 +304,14 @@ pub fn ClassPage() -> impl IntoView {
 }
 
 #[component]
-fn FilteredPostsGrid(
-    unfiltered_posts: Signal<Vec<Post>>, 
-    filtered_ids: Signal<Option<HashSet<i32>>>,
-) -> impl IntoView {
-  let filtered_posts = move || 
-    match filtered_ids.get() {
-      Some(ids) => unfiltered_posts().into_iter().filter(|post| ids.contains(&post.post_id)).collect(),
-      None => unfiltered_posts()
-    };
-
+fn FilteredPostsGrid(unfiltered_posts: Signal<Vec<Post>>) -> impl IntoView {
   view! {
-    <For each=filtered_posts key=|post| post.post_id let:post>
+    // Keyed on the unread and pinned state too so tiles re-render when those change
+    <For
+      each=unfiltered_posts
+      key=|post| (post.post_id, post.unread, post.new_replies, post.pinned)
+      let:post
+    >
       {
         let private = post.private;
         post
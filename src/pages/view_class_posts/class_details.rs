@@ -1,7 +1,12 @@
-use crate::data::database::class_functions::{get_class_name, get_users_enrolled_in_class};
+use crate::data::database::class_functions::{
+    check_user_is_instructor, get_class_allows_full_anonymity, get_class_name,
+    get_users_enrolled_in_class, set_class_allows_full_anonymity,
+};
 use crate::data::database::post_functions::{get_resolved_questions, get_total_questions};
 use crate::data::generate_graphs::generate_answered_unanswered_histogram;
+use crate::expect_logged_in_user;
 use crate::pages::view_class_posts::class::ClassId;
+use crate::pages::view_class_posts::class_groups::ClassGroups;
 use leptos::*;
 use leptos_router::{use_navigate, use_params};
 
@@ -27,6 +32,26 @@ pub fn ClassDetails() -> impl IntoView {
             .unwrap_or_default()
     });
 
+    // Instructors decide whether anonymous posts can be hidden from them too
+    let (user, _) = expect_logged_in_user!();
+    let is_instructor = create_local_resource(class_id, move |class_id| {
+        let user_id = user().id;
+        async move { check_user_is_instructor(user_id, class_id).await.unwrap_or(false) }
+    });
+    let allows_full_anonymity = create_local_resource(class_id, |class_id| async move {
+        get_class_allows_full_anonymity(class_id).await.unwrap_or(false)
+    });
+    let full_anonymity_action = create_action(move |allow: &bool| {
+        let allow = *allow;
+        let (class_id, user_id) = (class_id(), user().id);
+        async move {
+            match set_class_allows_full_anonymity(class_id, user_id, allow).await {
+                Ok(_) => allows_full_anonymity.set(allow),
+                Err(_) => logging::error!("Attempt to change anonymity setting failed. Please try again"),
+            }
+        }
+    });
+
     // Canvas ID
     let canvas_id = "question-resolution-chart";
 
@@ -87,6 +112,39 @@ pub fn ClassDetails() -> impl IntoView {
                         </div>
                     </div>
 
+                    // Groups Section
+                    <div class="bg-gray-50 rounded-lg shadow-sm border border-gray-100">
+                        <div class="p-6">
+                            <h2 class="text-xl font-semibold text-customBlue mb-6">"Groups"</h2>
+                            <ClassGroups
+                                class_id=Signal::derive(class_id)
+                                is_instructor=Signal::derive(move || is_instructor().unwrap_or(false))
+                            />
+                        </div>
+                    </div>
+
+                    // Anonymity Section
+                    <Show when=move || is_instructor().unwrap_or(false)>
+                        <div class="bg-gray-50 rounded-lg shadow-sm border border-gray-100">
+                            <div class="p-6">
+                                <h2 class="text-xl font-semibold text-customBlue mb-4">"Anonymity"</h2>
+                                <p class="text-sm text-gray-600 mb-4">
+                                    "Anonymous posts and replies are hidden from classmates, but instructors and TAs can still see who wrote them."
+                                </p>
+                                <label class="flex gap-2 items-center cursor-pointer select-none">
+                                    <input
+                                        type="checkbox"
+                                        prop:checked=move || allows_full_anonymity().unwrap_or(false)
+                                        on:change=move |_| {
+                                            full_anonymity_action.dispatch(!allows_full_anonymity().unwrap_or(false))
+                                        }
+                                    />
+                                    <span class="text-gray-800">"Let students hide their names from instructors too"</span>
+                                </label>
+                            </div>
+                        </div>
+                    </Show>
+
                     // Course Statistics Section
                     <div class="bg-gray-50 rounded-lg shadow-sm border border-gray-100">
                         <div class="p-6">
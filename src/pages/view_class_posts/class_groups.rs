@@ -0,0 +1,271 @@
+/**
+ * ClassGroups component, listing a class's groups and their members. Instructors can create and
+ * delete groups, move students between them by hand, import them from CSV, or shuffle the class
+ * into them.
+ */
+use crate::data::database::class_functions::get_users_enrolled_in_class;
+use crate::data::database::group_functions::{
+    balance_groups, create_class_group, delete_class_group, get_class_groups, import_groups_csv,
+    set_group_members, ClassGroup,
+};
+use crate::expect_logged_in_user;
+use leptos::*;
+
+#[component]
+pub fn ClassGroups(
+    #[prop(into)] class_id: Signal<i32>,
+    #[prop(into)] is_instructor: Signal<bool>,
+) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    let groups = create_local_resource(
+        move || (class_id(), user().id),
+        |(class_id, user_id)| async move { get_class_groups(class_id, user_id).await.unwrap_or_default() },
+    );
+    let members = create_local_resource(class_id, |class_id| async move {
+        get_users_enrolled_in_class(class_id).await.unwrap_or_default()
+    });
+    let member_name = move |user_id: i32| {
+        members()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|member| member.user_id == user_id)
+            .map(|member| member.full_name)
+            .unwrap_or_else(|| "Unknown user".to_string())
+    };
+
+    let (new_group_name, set_new_group_name) = create_signal(String::new());
+    let (csv, set_csv) = create_signal(String::new());
+    let (message, set_message) = create_signal(None::<String>);
+
+    let create_group_action = create_action(move |name: &String| {
+        let (name, class_id, user_id) = (name.clone(), class_id(), user().id);
+        async move {
+            match create_class_group(class_id, user_id, name).await {
+                Ok(_) => {
+                    set_new_group_name(String::new());
+                    groups.refetch();
+                }
+                Err(e) => set_message(Some(e.to_string())),
+            }
+        }
+    });
+    let delete_group_action = create_action(move |group_id: &i32| {
+        let (group_id, user_id) = (*group_id, user().id);
+        async move {
+            match delete_class_group(group_id, user_id).await {
+                Ok(_) => groups.refetch(),
+                Err(e) => set_message(Some(e.to_string())),
+            }
+        }
+    });
+    let set_members_action = create_action(move |(group_id, member_ids): &(i32, Vec<i32>)| {
+        let (group_id, member_ids, user_id) = (*group_id, member_ids.clone(), user().id);
+        async move {
+            match set_group_members(group_id, user_id, member_ids).await {
+                Ok(_) => groups.refetch(),
+                Err(e) => set_message(Some(e.to_string())),
+            }
+        }
+    });
+    let import_action = create_action(move |csv: &String| {
+        let (csv, class_id, user_id) = (csv.clone(), class_id(), user().id);
+        async move {
+            match import_groups_csv(class_id, user_id, csv).await {
+                Ok(import) => {
+                    set_csv(String::new());
+                    groups.set(import.groups);
+                    set_message(Some(if import.skipped_rows.is_empty() {
+                        format!("Assigned {} students", import.assigned)
+                    } else {
+                        format!(
+                            "Assigned {} students. Skipped rows: {}",
+                            import.assigned,
+                            import.skipped_rows.join("; ")
+                        )
+                    }));
+                }
+                Err(e) => set_message(Some(e.to_string())),
+            }
+        }
+    });
+    let balance_action = create_action(move |group_ids: &Vec<i32>| {
+        let (group_ids, class_id, user_id) = (group_ids.clone(), class_id(), user().id);
+        async move {
+            match balance_groups(class_id, user_id, group_ids).await {
+                Ok(balanced) => {
+                    groups.set(balanced);
+                    set_message(None);
+                }
+                Err(e) => set_message(Some(e.to_string())),
+            }
+        }
+    });
+
+    let group_view = move |group: ClassGroup| {
+        let group_id = group.group_id;
+        let member_ids = group.member_ids.clone();
+        let addable = move || {
+            members()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|member| member.role != "Instructor" && !member_ids.contains(&member.user_id))
+                .collect::<Vec<_>>()
+        };
+        view! {
+          <li class="p-3 bg-white rounded border border-gray-100">
+            <div class="flex justify-between items-center mb-2">
+              <span class="font-medium text-gray-800">
+                {group.name.clone()}
+                <span class="ml-2 text-sm text-gray-500">
+                  {format!("{} members", group.member_ids.len())}
+                </span>
+              </span>
+              <Show when=is_instructor>
+                <button
+                  class="text-sm text-red-500 hover:text-red-700"
+                  on:click=move |_| delete_group_action.dispatch(group_id)
+                >
+                  "Delete"
+                </button>
+              </Show>
+            </div>
+            <div class="flex flex-wrap gap-2 items-center">
+              {group
+                  .member_ids
+                  .iter()
+                  .map(|member_id| {
+                      let member_id = *member_id;
+                      let remaining = group
+                          .member_ids
+                          .iter()
+                          .copied()
+                          .filter(|id| *id != member_id)
+                          .collect::<Vec<_>>();
+                      view! {
+                        <span class="flex gap-1 items-center py-0.5 px-2 text-sm bg-gray-100 rounded-full">
+                          {move || member_name(member_id)}
+                          <Show when=is_instructor>
+                            {
+                                let remaining = remaining.clone();
+                                view! {
+                                  <button
+                                    class="text-gray-500 hover:text-red-500"
+                                    title="Remove from group"
+                                    on:click=move |_| {
+                                        set_members_action.dispatch((group_id, remaining.clone()))
+                                    }
+                                  >
+                                    "×"
+                                  </button>
+                                }
+                            }
+                          </Show>
+                        </span>
+                      }
+                  })
+                  .collect_view()}
+              <Show when=is_instructor>
+                {
+                    let (addable, member_ids) = (addable.clone(), group.member_ids.clone());
+                    view! {
+                      <select
+                        class="py-0.5 px-2 text-sm rounded-md border border-gray-300"
+                        on:change=move |ev| {
+                            if let Ok(user_id) = event_target_value(&ev).parse::<i32>() {
+                                let mut member_ids = member_ids.clone();
+                                member_ids.push(user_id);
+                                set_members_action.dispatch((group_id, member_ids));
+                            }
+                        }
+                      >
+                        <option value="" selected>"Add member..."</option>
+                        {move || {
+                            addable()
+                                .into_iter()
+                                .map(|member| {
+                                    view! {
+                                      <option value=member.user_id.to_string()>{member.full_name}</option>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+                      </select>
+                    }
+                }
+              </Show>
+            </div>
+          </li>
+        }
+    };
+
+    view! {
+      <div class="flex flex-col gap-4">
+        <Suspense fallback=|| view! { <div class="h-20 bg-gray-200 rounded animate-pulse"></div> }>
+          {move || {
+              let groups = groups().unwrap_or_default();
+              if groups.is_empty() {
+                  view! { <p class="text-sm text-gray-500">"This class has no groups yet."</p> }
+                      .into_view()
+              } else {
+                  view! {
+                    <ul class="space-y-3">{groups.into_iter().map(group_view).collect_view()}</ul>
+                  }
+                      .into_view()
+              }
+          }}
+        </Suspense>
+        {move || message().map(|message| view! { <p class="text-sm text-gray-600">{message}</p> })}
+        <Show when=is_instructor>
+          <div class="flex gap-2 items-center">
+            <input
+              class="p-2 rounded-md border border-gray-300"
+              type="text"
+              placeholder="New group name"
+              prop:value=new_group_name
+              on:input=move |ev| set_new_group_name(event_target_value(&ev))
+            />
+            <button
+              class="py-2 px-4 text-white rounded-full bg-customBlue hover:bg-customBlue-HOVER"
+              prop:disabled=move || new_group_name.with(|name| name.trim().is_empty())
+              on:click=move |_| create_group_action.dispatch(new_group_name.get())
+            >
+              "Create Group"
+            </button>
+            <button
+              class="py-2 px-4 rounded-full border border-gray-300 hover:bg-gray-100"
+              title="Shuffle every student into the groups so they are the same size"
+              prop:disabled=move || groups().unwrap_or_default().is_empty()
+              on:click=move |_| {
+                  let group_ids = groups()
+                      .unwrap_or_default()
+                      .into_iter()
+                      .map(|group| group.group_id)
+                      .collect();
+                  balance_action.dispatch(group_ids)
+              }
+            >
+              "Randomly Balance Students"
+            </button>
+          </div>
+          <div class="flex flex-col gap-2">
+            <label class="text-sm font-medium text-gray-700">
+              "Import from CSV, one \"group,username\" row per student"
+            </label>
+            <textarea
+              class="p-2 h-24 font-mono text-sm rounded-md border border-gray-300"
+              placeholder="Team 1,jsmith"
+              prop:value=csv
+              on:input=move |ev| set_csv(event_target_value(&ev))
+            ></textarea>
+            <button
+              class="self-start py-2 px-4 rounded-full border border-gray-300 hover:bg-gray-100"
+              prop:disabled=move || csv.with(|csv| csv.trim().is_empty())
+              on:click=move |_| import_action.dispatch(csv.get())
+            >
+              "Import"
+            </button>
+          </div>
+        </Show>
+      </div>
+    }
+}
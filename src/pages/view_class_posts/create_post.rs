@@ -2,13 +2,25 @@
  * This file contains the CreatePost component, which is a form that allows
  * users to create a new post.
  */
+use super::anonymity_options::AnonymityOptions;
+use super::attachments::AttachmentUploader;
+use super::audience_picker::AudiencePicker;
 use super::class::ClassId;
+use super::duplicate_questions::SimilarPostSuggestions;
 use crate::on_input;
 use crate::resources::images::svgs::create_post_icon::CreatePostIcon;
 use crate::{
-    data::database::post_functions::{add_post, Post, PostFetcher},
+    data::database::{
+        draft_functions::DraftContext,
+        post_functions::{add_post, Post, PostFetcher},
+        schedule_functions::schedule_post,
+    },
     expect_logged_in_user,
-    pages::global_components::rich_text_box::RichTextBox,
+    pages::global_components::{
+        draft_autosave::{use_draft_autosave, DraftStatus},
+        local_datetime::local_input_to_utc,
+        rich_text_box::RichTextBox,
+    },
 };
 use leptoaster::*;
 use leptos::*;
@@ -20,10 +32,25 @@ pub struct AddPostInfo {
     pub title: String,
     pub contents: String,
     pub anonymous: bool,
+    /// Whether an anonymous post is hidden from instructors too. Only kept when the class allows it.
+    #[serde(default)]
+    pub fully_anonymous: bool,
     pub limited_visibility: bool,
     pub classid: i32,
     pub private: bool,
     pub ai_response: bool,
+    /// Classmates a private post is shared with, besides the instructor
+    #[serde(default)]
+    pub audience: Vec<i32>,
+    /// Groups of the class a private post is shared with
+    #[serde(default)]
+    pub audience_groups: Vec<i32>,
+    /// Uploads to attach to the post
+    #[serde(default)]
+    pub attachment_ids: Vec<i32>,
+    /// When an instructor's post should be published, if it is scheduled. See `schedule_post`.
+    #[serde(default)]
+    pub publish_at: Option<chrono::NaiveDateTime>,
 }
 
 #[component]
@@ -37,11 +64,23 @@ pub fn CreatePost(on_new_post: impl Fn() + 'static) -> impl IntoView {
     let posts = expect_context::<Resource<PostFetcher, Vec<Post>>>();
 
     let (anonymous_state, set_anonymous_state) = create_signal(false);
+    let fully_anonymous = create_rw_signal(false);
     let (private_state, set_private_state) = create_signal(false);
+    let audience = create_rw_signal(Vec::<i32>::new());
+    let audience_groups = create_rw_signal(Vec::<i32>::new());
     let (post_title, set_post_title) = create_signal("".to_string());
     let (post_contents, set_post_contents) = create_signal("".to_string());
     let (ai_response, set_ai_response) = create_signal(false);
+    let attachments = create_rw_signal(Vec::new());
+    // Instructors can schedule a post to be published later
+    let is_instructor = move || user().role == "Instructor";
+    let (publish_at_input, set_publish_at_input) = create_signal(String::new());
     let toaster = expect_toaster(); // post submission confirmation.
+    let autosave = use_draft_autosave(
+        DraftContext::NewPost { class_id: class_id() },
+        Some((post_title, set_post_title)),
+        (post_contents, set_post_contents),
+    );
 
     let add_post_action = create_action(move |(postInfo, user_id): &(AddPostInfo, i32)| {
         let postInfo = postInfo.clone();
@@ -72,6 +111,16 @@ pub fn CreatePost(on_new_post: impl Fn() + 'static) -> impl IntoView {
         }
     });
 
+    let schedule_post_action = create_action(move |(post_info, user_id): &(AddPostInfo, i32)| {
+        let post_info = post_info.clone();
+        let user_id = *user_id;
+        async move {
+            if let Err(e) = schedule_post(post_info, user_id).await {
+                logging::error!("Attempt to schedule post failed: {:?}", e);
+            }
+        }
+    });
+
     view! {
       <DarkenedCard class="flex flex-col gap-2 p-5">
         <div class="flex gap-2 items-center px-3">
@@ -88,13 +137,23 @@ pub fn CreatePost(on_new_post: impl Fn() + 'static) -> impl IntoView {
               prop:value=post_title
             ></textarea>
           </div>
+          <SimilarPostSuggestions class_id=class_id() title=post_title contents=post_contents />
           <p>"Contents:"</p>
           <div class="p-2 h-96">
-            <RichTextBox
-              id="create_post_rich_text_box".to_string()
-              set_value=set_post_contents
-              value=post_contents
-            />
+            // The editor only takes its value when it is created, so wait for any draft
+            <Show when=autosave.ready>
+              <RichTextBox
+                id="create_post_rich_text_box".to_string()
+                set_value=set_post_contents
+                value=post_contents
+              />
+            </Show>
+          </div>
+          <div class="px-2">
+            <DraftStatus autosave />
+          </div>
+          <div class="p-2">
+            <AttachmentUploader class_id=class_id() attachments />
           </div>
         </div>
         // AI assistant
@@ -118,7 +177,7 @@ pub fn CreatePost(on_new_post: impl Fn() + 'static) -> impl IntoView {
           </label>
           // Private
           <label for="privateToggle" class="flex items-center cursor-pointer select-none">
-            <span class="mx-2">"Private to Instructors:"</span>
+            <span class="mx-2">"Private:"</span>
             <div class="relative">
               <input
                 type="checkbox"
@@ -134,6 +193,22 @@ pub fn CreatePost(on_new_post: impl Fn() + 'static) -> impl IntoView {
               <div class="absolute top-1 left-1 w-6 h-6 bg-white rounded-full transition peer-checked:translate-x-full peer-checked:bg-primary"></div>
             </div>
           </label>
+          <Show when=private_state>
+            <AudiencePicker class_id=class_id() audience audience_groups />
+          </Show>
+          // Publish time, for instructors
+          <Show when=is_instructor>
+            <label class="flex gap-1 items-center">
+              <span class="mx-2">"Publish at:"</span>
+              <input
+                type="datetime-local"
+                class="py-0.5 px-1 rounded border border-gray-300"
+                title="Leave empty to post now"
+                prop:value=publish_at_input
+                on:input=move |ev| set_publish_at_input(event_target_value(&ev))
+              />
+            </label>
+          </Show>
           // Anonymous
           <label for="anonymousToggle" class="flex items-center cursor-pointer select-none">
             <span class="mx-2">"Post Anonymously:"</span>
@@ -152,6 +227,7 @@ pub fn CreatePost(on_new_post: impl Fn() + 'static) -> impl IntoView {
               <div class="absolute top-1 left-1 w-6 h-6 bg-white rounded-full transition peer-checked:translate-x-full peer-checked:bg-primary"></div>
             </div>
           </label>
+          <AnonymityOptions class_id=class_id() anonymous=anonymous_state fully_anonymous />
           <button
             type="submit"
             class="py-2 px-4 text-white rounded-full focus:ring-2 focus:ring-offset-2 focus:outline-none bg-customBlue hover:bg-customBlue-HOVER focus:ring-offset-customBlue"
@@ -159,19 +235,39 @@ pub fn CreatePost(on_new_post: impl Fn() + 'static) -> impl IntoView {
               if post_title().is_empty() || post_contents().is_empty() {
                 return;
               }
-              add_post_action
-                .dispatch((AddPostInfo {
-                  title: post_title(),
-                  contents: post_contents(),
-                  anonymous: anonymous_state(),
-                  limited_visibility: false,
-                  classid: class_id(),
-                  private: private_state(),
-                  ai_response: ai_response(),
-                }, user().id));
+              autosave.forget();
+              let publish_at = if is_instructor() {
+                local_input_to_utc(&publish_at_input())
+              } else {
+                None
+              };
+              let post_info = AddPostInfo {
+                title: post_title(),
+                contents: post_contents(),
+                anonymous: anonymous_state(),
+                fully_anonymous: fully_anonymous(),
+                limited_visibility: false,
+                classid: class_id(),
+                private: private_state(),
+                audience: if private_state() { audience() } else { Vec::new() },
+                audience_groups: if private_state() { audience_groups() } else { Vec::new() },
+                ai_response: ai_response(),
+                attachment_ids: attachments
+                  .with(|attachments| {
+                    attachments.iter().map(|attachment| attachment.attachment_id).collect()
+                  }),
+                publish_at,
+              };
+              let message = if publish_at.is_some() {
+                schedule_post_action.dispatch((post_info, user().id));
+                "Post Scheduled! Find it under Scheduled in the announcements."
+              } else {
+                add_post_action.dispatch((post_info, user().id));
+                "Post Created Successfully!"
+              };
               toaster
                 .toast(
-                  ToastBuilder::new("Post Created Successfully!")
+                  ToastBuilder::new(message)
                     .with_level(ToastLevel::Success)
                     .with_dismissable(true)
                     .with_expiry(Some(4_000))
@@ -181,7 +277,7 @@ pub fn CreatePost(on_new_post: impl Fn() + 'static) -> impl IntoView {
               on_new_post();
             }
           >
-            "Post +"
+            {move || if publish_at_input.with(String::is_empty) { "Post +" } else { "Schedule +" }}
           </button>
         </div>
       </DarkenedCard>
@@ -0,0 +1,169 @@
+/**
+ * Components for steering students toward questions that have already been asked: suggestions
+ * shown while composing a post, and the instructor control for marking a post as a duplicate
+ */
+use crate::data::database::duplicate_functions::{get_similar_posts, mark_post_duplicate};
+use crate::data::database::post_functions::{Post, PostFetcher};
+use crate::expect_logged_in_user;
+use leptos::*;
+use leptos_router::A;
+use std::time::Duration;
+
+/// Titles shorter than this don't say enough to find a match
+const MIN_TITLE_LENGTH: usize = 8;
+
+#[component]
+pub fn SimilarPostSuggestions(
+    class_id: i32,
+    title: ReadSignal<String>,
+    contents: ReadSignal<String>,
+) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+
+    let similar_posts_action = create_action(
+        move |(user_id, title, contents): &(i32, String, String)| {
+            let user_id = *user_id;
+            let title = title.clone();
+            let contents = contents.clone();
+            async move {
+                get_similar_posts(class_id, user_id, title, contents)
+                    .await
+                    .unwrap_or_default()
+            }
+        },
+    );
+
+    // Wait for a pause in typing before searching
+    let pending_search = store_value(None::<TimeoutHandle>);
+    create_effect(move |_| {
+        let title = title();
+        let contents = contents();
+        let user_id = user().id;
+        if let Some(handle) = pending_search.get_value() {
+            handle.clear();
+        }
+        if title.trim().len() < MIN_TITLE_LENGTH {
+            similar_posts_action.value().set(None);
+            return;
+        }
+        pending_search.set_value(
+            set_timeout_with_handle(
+                move || similar_posts_action.dispatch((user_id, title, contents)),
+                Duration::from_millis(400),
+            )
+            .ok(),
+        );
+    });
+
+    let similar_posts = move || similar_posts_action.value()().unwrap_or_default();
+
+    view! {
+      <Show when=move || !similar_posts().is_empty() fallback=|| ()>
+        <div class="p-3 mx-2 text-sm bg-blue-50 rounded-lg border border-blue-200">
+          <p class="mb-1 font-semibold">"Did you mean…?"</p>
+          <ul class="flex flex-col gap-1">
+            <For each=similar_posts key=|post| post.post_id let:post>
+              <li>
+                <A
+                  href=format!("/classes/{}/{}", class_id, post.post_id)
+                  class="text-customBlue hover:underline"
+                >
+                  {post.title.clone()}
+                </A>
+                {post.resolved.then(|| view! { <span class="ml-2 text-xs text-green-700">"Resolved"</span> })}
+              </li>
+            </For>
+          </ul>
+        </div>
+      </Show>
+    }
+    .into_view()
+}
+
+#[component]
+pub fn DuplicateControls(
+    class_id: i32,
+    post_id: i32,
+    duplicate_of: Option<i32>,
+) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    let posts = expect_context::<Resource<PostFetcher, Vec<Post>>>();
+    let (duplicate_of, set_duplicate_of) = create_signal(duplicate_of);
+    let (selected_original, set_selected_original) = create_signal(None::<i32>);
+
+    let mark_duplicate_action = create_action(
+        move |(user_id, original_post_id): &(i32, Option<i32>)| {
+            let user_id = *user_id;
+            let original_post_id = *original_post_id;
+            async move {
+                match mark_post_duplicate(post_id, original_post_id, user_id).await {
+                    Ok(_) => set_duplicate_of(original_post_id),
+                    Err(_) => logging::error!("Attempt to mark duplicate failed. Please try again"),
+                }
+            }
+        },
+    );
+
+    let post_title = move |post_id: i32| {
+        posts
+            .get()
+            .and_then(|posts| posts.into_iter().find(|post| post.post_id == post_id))
+            .map(|post| post.title)
+            .unwrap_or_else(|| format!("Post #{}", post_id))
+    };
+
+    view! {
+      <div class="flex gap-2 items-center text-sm">
+        {move || match duplicate_of() {
+          Some(original_post_id) => {
+            view! {
+              <span class="text-gray-600">
+                "Duplicate of "
+                <A
+                  href=format!("/classes/{}/{}", class_id, original_post_id)
+                  class="text-customBlue hover:underline"
+                >
+                  {post_title(original_post_id)}
+                </A>
+              </span>
+              <button
+                class="py-1 px-3 text-gray-700 bg-white rounded-full hover:text-black hover:bg-gray-100"
+                on:click=move |_| mark_duplicate_action.dispatch((user().id, None))
+              >
+                "Not a duplicate"
+              </button>
+            }
+              .into_view()
+          }
+          None => {
+            view! {
+              <select
+                class="py-1 px-2 max-w-xs rounded border border-gray-300"
+                on:change=move |ev| set_selected_original(event_target_value(&ev).parse().ok())
+              >
+                <option value="" selected=true>
+                  "Duplicate of…"
+                </option>
+                {posts
+                  .get()
+                  .unwrap_or_default()
+                  .into_iter()
+                  .filter(|post| post.post_id != post_id)
+                  .map(|post| view! { <option value=post.post_id>{post.title}</option> })
+                  .collect_view()}
+              </select>
+              <button
+                class="py-1 px-3 text-gray-700 bg-white rounded-full hover:text-black hover:bg-gray-100 disabled:opacity-50"
+                disabled=move || selected_original().is_none()
+                on:click=move |_| mark_duplicate_action.dispatch((user().id, selected_original()))
+              >
+                "Mark duplicate"
+              </button>
+            }
+              .into_view()
+          }
+        }}
+      </div>
+    }
+    .into_view()
+}
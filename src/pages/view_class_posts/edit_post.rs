@@ -1,7 +1,12 @@
-use super::{class::ClassId, focused_post::PostDetails};
+use super::{
+    anonymity_options::AnonymityOptions, audience_picker::AudiencePicker, class::ClassId,
+    focused_post::PostDetails,
+};
 use crate::{
-    data::database::post_functions::edit_post, expect_logged_in_user, on_input, pages::{global_components::rich_text_box::RichTextBox, view_class_posts::focused_post::{get_post_details, PostId}}, resources::images::svgs::{cancel_icon::CancelIcon, save_icon::SaveIcon}
+    data::database::{draft_functions::DraftContext, post_functions::edit_post}, expect_logged_in_user, on_input, pages::{global_components::{draft_autosave::{use_draft_autosave, DraftStatus}, rich_text_box::RichTextBox}, view_class_posts::focused_post::{get_post_details, PostId}}, resources::images::svgs::{cancel_icon::CancelIcon, save_icon::SaveIcon}
 };
+use crate::data::math::math_to_source;
+use crate::data::mentions::mentions_to_source;
 use leptos::*;
 use leptos_router::use_params;
 
@@ -12,13 +17,17 @@ pub fn EditPostLoader() -> impl IntoView {
       move || post_params().expect("Tried to render edit post without post id").post_id
     };
 
-    let post_and_replies = create_resource(post_id, |post_id| async move {
-      get_post_details(post_id).await.unwrap()
+    let (user, _) = expect_logged_in_user!();
+    let post_and_replies = create_resource(post_id, move |post_id| async move {
+      get_post_details(post_id, user.get_untracked().id).await.ok()
     });
 
     view! {
       <Suspense fallback=move || "Loading editor...">
-        {move || post_and_replies().map(|(post, _)| view!{<EditPost post />})}
+        {move || post_and_replies().map(|post_and_replies| match post_and_replies {
+          Some((post, _)) => view!{<EditPost post />}.into_view(),
+          None => "This post doesn't exist or hasn't been shared with you.".into_view(),
+        })}
       </Suspense>
     }
 }
@@ -34,13 +43,22 @@ fn EditPost(post: PostDetails) -> impl IntoView {
     let (post_title, set_post_title) = create_signal(
         post.title,
     );
+    // Rendered math goes back to the LaTeX it was written as while editing
     let (post_contents, set_post_contents) = create_signal(
-        post.contents
+        mentions_to_source(&math_to_source(&post.contents))
     );
     let (private_state, set_private_state) = create_signal(post.private);
+    let audience = create_rw_signal(post.audience);
+    let audience_groups = create_rw_signal(post.audience_groups);
     let (anonymous_state, set_anonymous_state) = create_signal(post.anonymous);
+    let fully_anonymous = create_rw_signal(post.fully_anonymous);
+    let autosave = use_draft_autosave(
+        DraftContext::EditPost { post_id: post.post_id },
+        Some((post_title, set_post_title)),
+        (post_contents, set_post_contents),
+    );
 
-    let edit_post_action = create_action(move |(user_id, class_id, post_id, post_title, post_contents, private, anonymous): &(i32, i32, i32, String, String, bool, bool)| {
+    let edit_post_action = create_action(move |(user_id, class_id, post_id, post_title, post_contents, private, anonymous, fully_anonymous, audience, audience_groups): &(i32, i32, i32, String, String, bool, bool, bool, Vec<i32>, Vec<i32>)| {
         let user_id = *user_id;
         let class_id = *class_id;
         let post_id = *post_id;
@@ -48,6 +66,9 @@ fn EditPost(post: PostDetails) -> impl IntoView {
         let post_contents = post_contents.clone();
         let private = *private;
         let anonymous = *anonymous;
+        let fully_anonymous = *fully_anonymous;
+        let audience = audience.clone();
+        let audience_groups = audience_groups.clone();
         async move {
             match edit_post(
                 post_id,
@@ -56,6 +77,9 @@ fn EditPost(post: PostDetails) -> impl IntoView {
                 user_id,
                 private,
                 anonymous,
+                fully_anonymous,
+                audience,
+                audience_groups,
             )
             .await
             {
@@ -86,16 +110,20 @@ fn EditPost(post: PostDetails) -> impl IntoView {
             prop:value=post_title
           ></textarea>
           <p>"Contents:"</p>
-          <RichTextBox
-            id="edit_post_rich_text_box".to_string()
-            set_value=set_post_contents
-            value=post_contents
-          />
+          // The editor only takes its value when it is created, so wait for any draft
+          <Show when=autosave.ready>
+            <RichTextBox
+              id="edit_post_rich_text_box".to_string()
+              set_value=set_post_contents
+              value=post_contents
+            />
+          </Show>
+          <DraftStatus autosave />
         </div>
 
           <div class="flex gap-5 justify-end">
           <label for="privateToggle" class="flex items-center cursor-pointer select-none">
-          <span class="mx-2">"Private to Instructors:"</span>
+          <span class="mx-2">"Private:"</span>
           <div class="relative">
             <input
               type="checkbox"
@@ -111,6 +139,9 @@ fn EditPost(post: PostDetails) -> impl IntoView {
             <div class="absolute top-1 left-1 w-6 h-6 bg-white rounded-full transition peer-checked:translate-x-full peer-checked:bg-primary"></div>
           </div>
         </label>
+        <Show when=private_state>
+          <AudiencePicker class_id=class_id() audience audience_groups />
+        </Show>
         <label for="anonymousToggle" class="flex items-center cursor-pointer select-none">
         <span class="mx-2">"Post Anonymously:"</span>
         <div class="relative">
@@ -128,6 +159,7 @@ fn EditPost(post: PostDetails) -> impl IntoView {
          <div class="absolute top-1 left-1 w-6 h-6 bg-white rounded-full transition peer-checked:translate-x-full peer-checked:bg-primary"></div>
         </div>
       </label>
+      <AnonymityOptions class_id=class_id() anonymous=anonymous_state fully_anonymous />
       <div class="flex gap-5 justify-end">
       <button
         class="ml-4 py-2 px-4 text-white rounded-full focus:ring-2 focus:ring-offset-2 focus:outline-none bg-red-500 hover:bg-red-600 focus:ring-offset-red-500 flex items-center gap-2"
@@ -148,7 +180,8 @@ fn EditPost(post: PostDetails) -> impl IntoView {
       type="submit"
         class="py-3 px-4 text-white rounded-full focus:ring-2 focus:ring-offset-2 focus:ring-offset-coolBlue bg-coolBlue hover:bg-coolBlue-HOVER focus:outline-none inline-flex items-center gap-2"
         on:click=move |_| {
-          edit_post_action.dispatch((user().id, class_id(), post.post_id, post_title(), post_contents(), private_state(), anonymous_state()));
+          autosave.forget();
+          edit_post_action.dispatch((user().id, class_id(), post.post_id, post_title(), post_contents(), private_state(), anonymous_state(), fully_anonymous(), if private_state() { audience() } else { Vec::new() }, if private_state() { audience_groups() } else { Vec::new() }));
         }
       >
         Save Changes
@@ -1,15 +1,34 @@
 /**
  * This file contains the FocusedPost component which is used to display a single post and its replies.
  */
+use crate::data::database::attachment_functions::{get_post_attachments, Attachment};
 use crate::data::database::class_functions::check_user_is_instructor;
+use crate::data::database::draft_functions::DraftContext;
+use crate::data::math::math_to_source;
+use crate::data::mentions::mentions_to_source;
 use crate::data::database::post_functions::{remove_post, resolve_post, Post, PostFetcher};
+use crate::data::database::post_view_functions::mark_post_viewed;
 use crate::data::database::reply_functions::{add_reply, approve_reply, remove_reply};
+use crate::data::database::reaction_functions::{
+    get_reactions, get_reply_reactions, ReactionCounts, ReactionTarget,
+};
+use crate::data::database::revision_functions::{edit_reply, Revision, RevisionTarget};
 use crate::expect_logged_in_user;
+use crate::pages::global_components::draft_autosave::{use_draft_autosave, DraftStatus};
+use crate::pages::global_components::infinite_scroll::InfiniteScroll;
 use crate::pages::global_components::notification::{
     NotificationComponent, NotificationDetails, NotificationType,
 };
 use crate::pages::global_components::rich_text_box::{RichTextBox, TiptapContentWrapper};
+use crate::pages::view_class_posts::anonymity_options::AnonymityOptions;
+use crate::pages::view_class_posts::attachments::{AttachmentList, AttachmentUploader};
 use crate::pages::view_class_posts::class::ClassId;
+use crate::pages::view_class_posts::duplicate_questions::DuplicateControls;
+use crate::pages::view_class_posts::pin_controls::PinControls;
+use crate::pages::view_class_posts::post_answers::PostAnswerSections;
+use crate::pages::view_class_posts::reactions::ReactionBar;
+use crate::pages::view_class_posts::revision_history::{EditedMarker, RevisionHistory};
+use crate::pages::view_class_posts::watch_button::WatchButton;
 use crate::resources::images::svgs::approval_icon::ApproveIcon;
 use crate::resources::images::svgs::cancel_icon::CancelIcon;
 use crate::resources::images::svgs::check_icon::CheckIcon;
@@ -21,8 +40,9 @@ use crate::resources::images::svgs::unapprove_icon::UnapproveIcon;
 use crate::resources::images::svgs::unresolved_icon::UnresolvedIcon;
 use chrono::FixedOffset;
 use chrono::NaiveDateTime;
+use leptoaster::*;
 use leptos::*;
-use leptos_router::use_params;
+use leptos_router::{use_navigate, use_params, NavigateOptions};
 use leptos_router::Params;
 use serde::Deserialize;
 use serde::Serialize;
@@ -50,9 +70,16 @@ pub struct PostDetails {
     pub author_first_name: String,
     pub author_last_name: String,
     pub anonymous: bool,
+    pub fully_anonymous: bool,
     pub resolved: bool,
     pub author_id: i32,
     pub private: bool,
+    /// Classmates a private post is shared with
+    pub audience: Vec<i32>,
+    /// Groups a private post is shared with
+    pub audience_groups: Vec<i32>,
+    pub duplicate_of: Option<i32>,
+    pub edited_at: Option<NaiveDateTime>,
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq)]
@@ -63,9 +90,32 @@ pub struct Reply {
     pub author_name: String,
     pub author_id: i32,
     pub anonymous: bool,
+    pub fully_anonymous: bool,
     pub reply_id: i32,
     pub removed: bool,
     pub approved: bool,
+    pub edited_at: Option<NaiveDateTime>,
+}
+
+/// Number of replies returned with a post and by each call to `get_replies_page`
+pub const REPLIES_PAGE_SIZE: usize = 50;
+
+/**
+ * Position in a thread to continue from: the last reply of the previous page
+ */
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub struct ReplyCursor {
+    pub time: NaiveDateTime,
+    pub reply_id: i32,
+}
+
+impl ReplyCursor {
+    fn of(reply: &Reply) -> ReplyCursor {
+        ReplyCursor {
+            time: reply.time,
+            reply_id: reply.reply_id,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -73,6 +123,12 @@ pub struct AddReplyInfo {
     pub post_id: i32,
     pub contents: String,
     pub anonymous: bool,
+    /// Whether an anonymous reply is hidden from instructors too. Only kept when the class allows it.
+    #[serde(default)]
+    pub fully_anonymous: bool,
+    /// Uploads to attach to the reply
+    #[serde(default)]
+    pub attachment_ids: Vec<i32>,
 }
 
 #[component]
@@ -88,10 +144,9 @@ pub fn FocusedPost() -> impl IntoView {
         move || class_params().expect("Tried to render focused post without class id").class_id
     };
 
-    let post_and_replies = create_resource(post_id, |post_id| async move {
-        get_post_details(post_id)
-            .await
-            .expect("Failed to fetch post details")
+    // None when the post doesn't exist or is private and not shared with the user
+    let post_and_replies = create_resource(post_id, move |post_id| async move {
+        get_post_details(post_id, user.get_untracked().id).await.ok()
     });
 
     let is_instructor = create_resource(class_id, move |class_id| {
@@ -103,6 +158,65 @@ pub fn FocusedPost() -> impl IntoView {
         }
     });
 
+    // Students following a link to a duplicate are sent to the original question
+    let toaster = expect_toaster();
+    let navigate = use_navigate();
+    create_effect(move |_| {
+        if let Some(((post, _), false)) = post_and_replies().flatten().zip(is_instructor()) {
+            if let Some(original_post_id) = post.duplicate_of {
+                toaster.toast(
+                    ToastBuilder::new("This question was already asked. Showing the original.")
+                        .with_level(ToastLevel::Info)
+                        .with_dismissable(true)
+                        .with_expiry(Some(4_000))
+                        .with_progress(false)
+                        .with_position(ToastPosition::BottomRight),
+                );
+                navigate(
+                    format!("/classes/{}/{}", class_id(), original_post_id).as_str(),
+                    NavigateOptions {
+                        replace: true,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+    });
+
+    // Files attached to the post and its replies, fetched again when a reply adds more
+    let (attachments_version, set_attachments_version) = create_signal(0);
+    let attachments = create_resource(
+        move || (post_id(), user().id, attachments_version()),
+        |(post_id, user_id, _)| async move {
+            get_post_attachments(post_id, user_id)
+                .await
+                .unwrap_or_default()
+        },
+    );
+
+    // Records this visit, keeping the previous one to mark replies that are new since then
+    let last_visit = create_resource(post_id, move |post_id| {
+        let user_id = user().id;
+        async move { mark_post_viewed(post_id, user_id).await.unwrap_or(None) }
+    });
+
+    // Clear the unread markers on this post's tile once the visit is recorded
+    let posts = expect_context::<Resource<PostFetcher, Vec<Post>>>();
+    create_effect(move |_| {
+        if last_visit().is_some() {
+            let post_id = post_id();
+            posts.update(|posts| {
+                if let Some(post) = posts
+                    .as_mut()
+                    .and_then(|posts| posts.iter_mut().find(|post| post.post_id == post_id))
+                {
+                    post.unread = false;
+                    post.new_replies = 0;
+                }
+            });
+        }
+    });
+
     view! {
         <div class="flex flex-col gap-3 p-6 bg-white rounded shadow">
             <Suspense fallback=|| {
@@ -111,13 +225,27 @@ pub fn FocusedPost() -> impl IntoView {
                 {move || {
                     post_and_replies()
                         .zip(is_instructor())
-                        .map(|((post, replies), is_instructor)| {
+                        .zip(last_visit())
+                        .map(|((post_and_replies, is_instructor), last_visit)| {
+                            let Some((post, replies)) = post_and_replies else {
+                                return view! {
+                                    <DarkenedCard class="p-6">
+                                        "This post doesn't exist or hasn't been shared with you."
+                                    </DarkenedCard>
+                                }
+                                    .into_view();
+                            };
                             let (replies, set_replies) = create_signal(replies);
                             view! {
-                                <QuestionContent post=post class_id=class_id() is_instructor/>
+                                <QuestionContent post=post class_id=class_id() is_instructor attachments/>
+                                <PostAnswerSections post_id=post_id() is_instructor/>
                                 <RepliesList
+                                    post_id=post_id()
                                     replies
+                                    set_replies
                                     is_instructor
+                                    last_visit
+                                    attachments
                                     remove_reply_callback=move |reply_id_to_remove| {
                                         set_replies
                                             .update(|replies_vec| {
@@ -137,10 +265,12 @@ pub fn FocusedPost() -> impl IntoView {
                                     post_id=post_id()
                                     add_reply_callback=move |new_reply| {
                                         set_replies
-                                            .update(move |replies_vec| replies_vec.push(new_reply))
+                                            .update(move |replies_vec| replies_vec.push(new_reply));
+                                        set_attachments_version.update(|version| *version += 1);
                                     }
                                 />
                             }
+                                .into_view()
                         })
                 }}
 
@@ -167,14 +297,83 @@ pub fn SelectOrderOption(
     }
 }
 
+/**
+ * The files from the post's attachments belonging to the post itself, or to one of its replies
+ */
+#[component]
+fn AttachedFiles(
+    attachments: Resource<(i32, i32, i32), Vec<Attachment>>,
+    reply_id: Option<i32>,
+) -> impl IntoView {
+    view! {
+        <Transition fallback=|| ()>
+            {move || {
+                attachments()
+                    .map(|attachments| {
+                        attachments
+                            .into_iter()
+                            .filter(|attachment| attachment.reply_id == reply_id)
+                            .collect::<Vec<_>>()
+                    })
+                    .filter(|attachments| !attachments.is_empty())
+                    .map(|attachments| {
+                        view! {
+                            <div class="mt-3">
+                                <AttachmentList attachments/>
+                            </div>
+                        }
+                    })
+            }}
+        </Transition>
+    }
+}
+
 #[component]
-fn QuestionContent(post: PostDetails, class_id: i32, is_instructor: bool) -> impl IntoView {
+fn QuestionContent(
+    post: PostDetails,
+    class_id: i32,
+    is_instructor: bool,
+    attachments: Resource<(i32, i32, i32), Vec<Attachment>>,
+) -> impl IntoView {
     let (user, _) = expect_logged_in_user!();
+    let posts = expect_context::<Resource<PostFetcher, Vec<Post>>>();
+
+    // Kept in signals so an instructor rolling back an edit sees the restored version
+    let (title, set_title) = create_signal(post.title.clone());
+    let (contents, set_contents) = create_signal(post.contents.clone());
+    let (edited_at, set_edited_at) = create_signal(post.edited_at);
+    let (show_history, set_show_history) = create_signal(false);
+    let post_id = post.post_id;
+    let reactions = create_resource(
+        move || (post_id, user().id),
+        |(post_id, user_id)| async move {
+            get_reactions(ReactionTarget::Post(post_id), user_id)
+                .await
+                .unwrap_or_default()
+        },
+    );
 
     view! {
         <DarkenedCard class="relative p-5">
-            <p class="text-lg font-bold">{&post.title}</p>
+            <p class="text-lg font-bold">{title}</p>
+            {is_instructor
+                .then(|| {
+                    view! {
+                        <div class="flex flex-wrap gap-5 justify-end mb-2">
+                            <DuplicateControls
+                                class_id
+                                post_id=post.post_id
+                                duplicate_of=post.duplicate_of
+                            />
+                            <PinControls post_id=post.post_id/>
+                        </div>
+                    }
+                })}
             <div class="flex gap-5 justify-end">
+                <WatchButton
+                    post_id=post.post_id
+                    class="inline-flex items-center py-1 px-3 mr-10 text-sm text-gray-700 bg-white rounded-full hover:text-black hover:bg-gray-100"
+                />
                 <div class="flex items-center cursor-pointer select-none">
                     // Post Dropdown
                     {move || {
@@ -194,16 +393,78 @@ fn QuestionContent(post: PostDetails, class_id: i32, is_instructor: bool) -> imp
             </div>
             <p class="text-sm font-light">
                 "Posted by " {post.author_first_name} " " {post.author_last_name} " "
+                {(is_instructor && post.anonymous && !post.fully_anonymous)
+                    .then_some(view! { <AnonymityMarker/> })}
                 {post
                     .timestamp
                     .checked_add_offset(FixedOffset::west_opt(6 * 3600).unwrap())
                     .unwrap()
                     .format("at %l %p on %b %-d")
                     .to_string()}
+                {move || {
+                    edited_at()
+                        .map(|edited_at| {
+                            view! {
+                                <EditedMarker edited_at on_click=move || set_show_history(true)/>
+                            }
+                        })
+                }}
 
             </p>
             <br/>
-            <TiptapContentWrapper raw_html=post.contents/>
+            {move || view! { <TiptapContentWrapper raw_html=contents()/> }}
+            <AttachedFiles attachments reply_id=None/>
+            <Transition fallback=|| ()>
+                {move || {
+                    reactions()
+                        .map(|counts| {
+                            view! {
+                                <div class="mt-3">
+                                    <ReactionBar
+                                        target=ReactionTarget::Post(post_id)
+                                        counts
+                                        on_change=move |counts: ReactionCounts| {
+                                            posts
+                                                .update(|posts| {
+                                                    if let Some(post) = posts
+                                                        .as_mut()
+                                                        .and_then(|posts| {
+                                                            posts.iter_mut().find(|post| post.post_id == post_id)
+                                                        })
+                                                    {
+                                                        post.thanks_count = counts.thanks;
+                                                        post.same_question_count = counts.same_question;
+                                                        post.confused_count = counts.confused;
+                                                    }
+                                                })
+                                        }
+                                    />
+                                </div>
+                            }
+                        })
+                }}
+            </Transition>
+            <Show when=show_history fallback=|| ()>
+                <RevisionHistory
+                    target=RevisionTarget::Post(post_id)
+                    is_instructor
+                    on_close=move || set_show_history(false)
+                    on_rollback=move |restored: Revision| {
+                        let restored_title = restored.title.unwrap_or_default();
+                        posts.update(|posts| {
+                            if let Some(post) = posts
+                                .as_mut()
+                                .and_then(|posts| posts.iter_mut().find(|post| post.post_id == post_id))
+                            {
+                                post.title = restored_title.clone();
+                            }
+                        });
+                        set_title(restored_title);
+                        set_contents(restored.contents);
+                        set_edited_at(Some(restored.time));
+                    }
+                />
+            </Show>
         // TODO use the post's timestamp
         </DarkenedCard>
     }
@@ -217,6 +478,8 @@ where
     let (user, _) = expect_logged_in_user!();
     let (reply_contents, set_reply_contents) = create_signal(String::default());
     let (reply_anonymous_state, set_reply_anonymous_state) = create_signal(false);
+    let reply_fully_anonymous = create_rw_signal(false);
+    let reply_attachments = create_rw_signal(Vec::<Attachment>::new());
     let class_id = {
         let class_params = use_params::<ClassId>();
         move || class_params().expect("Tried to render create reply without class id").class_id
@@ -226,6 +489,11 @@ where
         create_signal(None::<NotificationDetails>);
 
     let (editor_count, set_editor_count) = create_signal(0);
+    let autosave = use_draft_autosave(
+        DraftContext::Reply { post_id },
+        None,
+        (reply_contents, set_reply_contents),
+    );
 
     let notification_view = move || {
         notification_details.get().map(|details| {
@@ -244,6 +512,8 @@ where
             match add_reply(reply_info, user().user_name).await {
                 Ok(reply) => {
                     set_reply_contents(String::default());
+                    autosave.forget();
+                    reply_attachments.set(Vec::new());
                     set_editor_count.update(|x| *x += 1);
                     add_reply_callback(reply);
                 }
@@ -263,16 +533,23 @@ where
             <p>"Answer this post:"</p>
             <div class="p-3 h-96 bg-white rounded-t-lg">
                 {move || {
-                    view! {
-                        <RichTextBox
-                            id=format!("reply_rich_text_box_{}", editor_count())
-                            set_value=set_reply_contents
-                            value=reply_contents
-                        />
-                    }
+                    autosave
+                        .ready
+                        .get()
+                        .then(|| {
+                            view! {
+                                <RichTextBox
+                                    id=format!("reply_rich_text_box_{}", editor_count())
+                                    set_value=set_reply_contents
+                                    value=reply_contents
+                                />
+                            }
+                        })
                 }}
 
             </div>
+            <DraftStatus autosave/>
+            <AttachmentUploader class_id=class_id() attachments=reply_attachments/>
             <div class="flex gap-5 justify-end">
                 <label for="anonymousToggle" class="flex items-center cursor-pointer select-none">
                     <span class="mx-2">"Reply Anonymously:"</span>
@@ -292,6 +569,11 @@ where
 
                     </div>
                 </label>
+                <AnonymityOptions
+                    class_id=class_id()
+                    anonymous=reply_anonymous_state
+                    fully_anonymous=reply_fully_anonymous
+                />
                 <button
                     class="flex gap-2 items-center py-2 px-4 ml-4 text-white bg-red-500 rounded-full hover:bg-red-600 focus:ring-2 focus:ring-offset-2 focus:ring-offset-red-500 focus:outline-none"
                     type="button"
@@ -324,6 +606,14 @@ where
                                 post_id,
                                 contents: reply_contents(),
                                 anonymous: reply_anonymous_state(),
+                                fully_anonymous: reply_fully_anonymous(),
+                                attachment_ids: reply_attachments
+                                    .with(|attachments| {
+                                        attachments
+                                            .iter()
+                                            .map(|attachment| attachment.attachment_id)
+                                            .collect()
+                                    }),
                             })
                     }
                 >
@@ -337,58 +627,227 @@ where
 }
 
 /**
- * Get all post information for a given the post id
+ * Inline editor replacing a reply's contents while its author edits it
+ */
+#[component]
+fn EditReply<C, S>(reply_id: i32, contents: String, on_cancel: C, on_saved: S) -> impl IntoView
+where
+    C: Fn() + 'static + Copy,
+    S: Fn(String, NaiveDateTime) + 'static + Copy,
+{
+    let (user, _) = expect_logged_in_user!();
+    // Rendered math goes back to the LaTeX it was written as while editing
+    let (reply_contents, set_reply_contents) =
+        create_signal(mentions_to_source(&math_to_source(&contents)));
+
+    let edit_reply_action = create_action(move |(user_id, contents): &(i32, String)| {
+        let user_id = *user_id;
+        let contents = contents.clone();
+        async move {
+            match edit_reply(reply_id, user_id, contents.clone()).await {
+                Ok(edited_at) => on_saved(contents, edited_at),
+                Err(_) => logging::error!("Attempt to edit reply failed. Please try again"),
+            }
+        }
+    });
+
+    view! {
+        <div class="flex flex-col gap-2">
+            <div class="p-3 h-72 bg-white rounded-t-lg">
+                <RichTextBox
+                    id=format!("edit_reply_rich_text_box_{}", reply_id)
+                    set_value=set_reply_contents
+                    value=reply_contents
+                />
+            </div>
+            <div class="flex gap-3 justify-end">
+                <button
+                    class="py-1 px-4 text-gray-700 bg-white rounded-full hover:bg-gray-100"
+                    on:click=move |_| on_cancel()
+                >
+                    "Cancel"
+                </button>
+                <button
+                    class="py-1 px-4 text-white rounded-full bg-customBlue hover:bg-customBlue-HOVER"
+                    disabled=move || reply_contents().is_empty() || edit_reply_action.pending()()
+                    on:click=move |_| edit_reply_action.dispatch((user().id, reply_contents()))
+                >
+                    "Save"
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/**
+ * Tag beside an author's name shown to instructors when classmates see the post or reply as anonymous
+ */
+#[component]
+fn AnonymityMarker() -> impl IntoView {
+    view! {
+        <span class="py-0.5 px-2 mr-1 text-xs font-normal text-gray-600 bg-gray-200 rounded-full">
+            "Anonymous to classmates"
+        </span>
+    }
+}
+
+/// Whether the viewer ($2) may see who wrote an anonymous post or reply: instructors and TAs can,
+/// unless the author hid from them too
+#[cfg(feature = "ssr")]
+pub const VIEWER_SEES_AUTHOR: &str = "(NOT anonymous OR (NOT fully_anonymous AND $2 IN (
+    SELECT professorid FROM instructing WHERE courseid = posts.classid
+    UNION SELECT id FROM ta WHERE classid = posts.classid)))";
+
+/**
+ * Get all post information for a given the post id, with anonymous authors hidden from the viewer
+ * unless they are allowed to see them
  */
 #[server(GetPostDetails)]
-pub async fn get_post_details(post_id: i32) -> Result<(PostDetails, Vec<Reply>), ServerFnError> {
+pub async fn get_post_details(
+    post_id: i32,
+    user_id: i32,
+) -> Result<(PostDetails, Vec<Reply>), ServerFnError> {
     use leptos::{server_fn::error::NoCustomError, use_context};
     use sqlx::postgres::PgPool;
     use tokio::*;
+    use crate::data::content::sanitize_html;
+    use crate::data::database::post_functions::check_user_can_see_post;
 
     let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
         "Unable to fetch posts".to_string(),
     ))?;
 
+    check_user_can_see_post(&pool, post_id, user_id).await?;
+
     let (post, replies) = join!(
-        sqlx::query_as::<_, PostDetails>(
+        sqlx::query_as::<_, PostDetails>(&format!(
             "SELECT 
                 postid as post_id,
                 timestamp,
                 title, 
                 contents, 
-                CASE WHEN anonymous THEN 'Anonymous'
-                    ELSE users.firstname 
+                CASE WHEN {VIEWER_SEES_AUTHOR} THEN users.firstname
+                    ELSE 'Anonymous'
                 END as author_first_name, 
-                CASE WHEN anonymous THEN 'Author'
-                    ELSE users.lastname
+                CASE WHEN {VIEWER_SEES_AUTHOR} THEN users.lastname
+                    ELSE 'Author'
                 END as author_last_name,
                 anonymous,
+                fully_anonymous,
                 resolved, 
-                authorid as author_id,
-                private
+                CASE WHEN {VIEWER_SEES_AUTHOR} OR authorid = $2 THEN authorid
+                    ELSE 0
+                END as author_id,
+                private,
+                ARRAY(SELECT userid FROM post_audience WHERE post_audience.postid = posts.postid) as audience,
+                ARRAY(SELECT groupid FROM post_audience_groups WHERE post_audience_groups.postid = posts.postid) as audience_groups,
+                duplicate_of,
+                edited_at
             FROM posts JOIN users ON posts.authorid = users.id WHERE posts.postid = $1"
-        )
+        ))
         .bind(post_id)
+        .bind(user_id)
         .fetch_optional(&pool),
-        sqlx::query_as::<_, Reply>(
+        sqlx::query_as::<_, Reply>(&format!(
             "SELECT 
                 time, 
-                contents,
-                CASE WHEN anonymous THEN 'Anonymous Author'
-                    ELSE users.firstname 
+                replies.contents,
+                CASE WHEN {VIEWER_SEES_AUTHOR} THEN users.firstname
+                    ELSE 'Anonymous Author'
                 END as author_name, 
-                authorid as author_id,
+                CASE WHEN {VIEWER_SEES_AUTHOR} OR replies.authorid = $2 THEN replies.authorid
+                    ELSE 0
+                END as author_id,
                 anonymous,
+                fully_anonymous,
                 replyid as reply_id,
-                removed,
-                approved
-            FROM replies JOIN users ON replies.authorid = users.id WHERE replies.postid = $1
-            ORDER BY time;"
-        )
+                replies.removed,
+                approved,
+                replies.edited_at
+            FROM replies JOIN users ON replies.authorid = users.id
+            JOIN (SELECT postid, classid FROM posts) posts ON replies.postid = posts.postid
+            WHERE replies.postid = $1
+            ORDER BY time DESC, replyid DESC
+            LIMIT $3;"
+        ))
         .bind(post_id)
+        .bind(user_id)
+        .bind(REPLIES_PAGE_SIZE as i64)
         .fetch_all(&pool)
     );
-    Ok((post.unwrap().unwrap(), replies.unwrap()))
+    // Cleaned again on the way out so content saved before sanitizing was added is safe to render
+    let mut post = post
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?
+        .ok_or(ServerFnError::<NoCustomError>::ServerError("Post not found".to_string()))?;
+    post.contents = sanitize_html(&post.contents);
+    let mut replies = replies.map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    for reply in replies.iter_mut() {
+        reply.contents = sanitize_html(&reply.contents);
+    }
+    Ok((post, replies))
+}
+
+/**
+ * Get the next page of replies to a post after the given reply, in either order
+ */
+#[server(GetRepliesPage)]
+pub async fn get_replies_page(
+    post_id: i32,
+    user_id: i32,
+    newest_first: bool,
+    cursor: Option<ReplyCursor>,
+) -> Result<Vec<Reply>, ServerFnError> {
+    use leptos::{server_fn::error::NoCustomError, use_context};
+    use sqlx::postgres::PgPool;
+    use crate::data::content::sanitize_html;
+    use crate::data::database::post_functions::check_user_can_see_post;
+
+    let pool = use_context::<PgPool>().ok_or(ServerFnError::<NoCustomError>::ServerError(
+        "Unable to fetch replies".to_string(),
+    ))?;
+
+    check_user_can_see_post(&pool, post_id, user_id).await?;
+
+    let (comparison, direction) = if newest_first { ("<", "DESC") } else { (">", "ASC") };
+    let replies: Vec<Reply> = sqlx::query_as(&format!(
+        "SELECT 
+            time, 
+            replies.contents,
+            CASE WHEN {VIEWER_SEES_AUTHOR} THEN users.firstname
+                ELSE 'Anonymous Author'
+            END as author_name, 
+            CASE WHEN {VIEWER_SEES_AUTHOR} OR replies.authorid = $2 THEN replies.authorid
+                ELSE 0
+            END as author_id,
+            anonymous,
+            fully_anonymous,
+            replyid as reply_id,
+            replies.removed,
+            approved,
+            replies.edited_at
+        FROM replies JOIN users ON replies.authorid = users.id
+        JOIN (SELECT postid, classid FROM posts) posts ON replies.postid = posts.postid
+        WHERE replies.postid = $1
+        AND ($3::TIMESTAMP IS NULL OR (time, replyid) {comparison} ($3, $4))
+        ORDER BY time {direction}, replyid {direction}
+        LIMIT $5;"
+    ))
+    .bind(post_id)
+    .bind(user_id)
+    .bind(cursor.map(|cursor| cursor.time))
+    .bind(cursor.map(|cursor| cursor.reply_id))
+    .bind(REPLIES_PAGE_SIZE as i64)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+
+    Ok(replies
+        .into_iter()
+        .map(|reply| Reply {
+            contents: sanitize_html(&reply.contents),
+            ..reply
+        })
+        .collect())
 }
 
 #[component]
@@ -428,7 +887,7 @@ pub fn FocusedDropdown(class_id: i32, post_id: i32, post_is_resolved: bool) -> i
     let resolve_action = create_action(move |post_id: &PostId| {
         let post_id = post_id.post_id;
         async move {
-            if let Ok(current_post) = get_post_details(post_id).await {
+            if let Ok(current_post) = get_post_details(post_id, user.get_untracked().id).await {
                 if (resolve_post(post_id, !current_post.0.resolved).await).is_ok() {
                     posts.update(|posts| {
                         if let Some(posts) = posts.as_mut() {
@@ -541,14 +1000,18 @@ pub fn FocusedDropdown(class_id: i32, post_id: i32, post_is_resolved: bool) -> i
 }
 
 #[component]
-pub fn ReplyDropdown<F>(
+pub fn ReplyDropdown<F, E>(
     remove_reply_callback: F,
     reply_approved: bool,
     reply_id: i32,
     is_instructor: bool,
+    /// Whether the user wrote the reply and so may edit it
+    can_edit: bool,
+    on_edit: E,
 ) -> impl IntoView
 where
     F: Fn(i32) + 'static + Copy,
+    E: Fn() + 'static + Copy,
 {
     let (user, _) = expect_logged_in_user!();
     let (_notification_details, set_notification_details) =
@@ -645,6 +1108,22 @@ where
                                 } else {
                                     view! { <div></div> }
                                 }}
+                                {can_edit
+                                    .then(|| {
+                                        view! {
+                                            <button
+                                                class="inline-flex items-center p-2 w-full text-sm leading-tight text-gray-700 rounded-md hover:text-black hover:bg-gray-100"
+                                                on:mousedown=move |_| {
+                                                    on_edit();
+                                                    set_menu_visible(false);
+                                                }
+                                            >
+
+                                                <EditPostIcon size="20px"/>
+                                                <span class="ml-2">Edit</span>
+                                            </button>
+                                        }
+                                    })}
                                 <button
                                     class="inline-flex items-center p-2 w-full text-sm leading-tight text-red-500 rounded-md hover:text-red-500 hover:bg-gray-100"
                                     on:mousedown=move |_| {
@@ -681,18 +1160,78 @@ fn sort_replies(replies: Vec<Reply>, order: &str) -> Vec<Reply> {
 
 #[component]
 fn RepliesList<F>(
+    post_id: i32,
     replies: ReadSignal<Vec<Reply>>,
+    set_replies: WriteSignal<Vec<Reply>>,
     remove_reply_callback: F,
     is_instructor: bool,
+    /// When the user last opened this post before now, if ever
+    last_visit: Option<NaiveDateTime>,
+    attachments: Resource<(i32, i32, i32), Vec<Attachment>>,
 ) -> impl IntoView
 where
     F: Fn(i32) + 'static + Copy,
 {
     let (user, _) = expect_logged_in_user!();
+
+    // Replies from other people posted since the user's last visit
+    let is_new_reply = move |reply: &Reply| {
+        last_visit.is_some_and(|last_visit| reply.time > last_visit)
+            && reply.author_id != user.get_untracked().id
+    };
+    let new_reply_count = move || {
+        replies()
+            .iter()
+            .filter(|reply| !reply.removed && is_new_reply(reply))
+            .count()
+    };
     let (_order_option, set_order_option) = create_signal("Newest First".to_string());
 
     let order_option_memo = create_memo(move |_| _order_option());
 
+    // Replies arrive a page at a time in the selected order, continuing from the last one fetched
+    let (reply_cursor, set_reply_cursor) =
+        create_signal(replies.with_untracked(|replies| replies.last().map(ReplyCursor::of)));
+    let (has_more, set_has_more) =
+        create_signal(replies.with_untracked(|replies| replies.len() >= REPLIES_PAGE_SIZE));
+
+    let load_replies_action = create_action(
+        move |(newest_first, cursor): &(bool, Option<ReplyCursor>)| {
+            let newest_first = *newest_first;
+            let cursor = *cursor;
+            async move {
+                match get_replies_page(post_id, user.get_untracked().id, newest_first, cursor).await {
+                    Ok(page) => {
+                        set_has_more(page.len() >= REPLIES_PAGE_SIZE);
+                        set_reply_cursor(page.last().map(ReplyCursor::of).or(cursor));
+                        set_replies.update(|replies| {
+                            // A first page in a new order replaces what was loaded in the old one
+                            if cursor.is_none() {
+                                replies.clear();
+                            }
+                            for reply in page {
+                                if !replies.iter().any(|loaded| loaded.reply_id == reply.reply_id) {
+                                    replies.push(reply);
+                                }
+                            }
+                        });
+                    }
+                    Err(_) => logging::error!("Attempt to load replies failed. Please try again"),
+                }
+            }
+        },
+    );
+
+    // Reaction counts for every loaded reply, fetched again as more replies load
+    let reply_reactions = create_resource(
+        move || (post_id, user().id, replies.with(Vec::len)),
+        |(post_id, user_id, _)| async move {
+            get_reply_reactions(post_id, user_id)
+                .await
+                .unwrap_or_default()
+        },
+    );
+
     let sorted_replies = move || {
         let order = order_option_memo();
         sort_replies(replies(), &order)
@@ -714,10 +1253,27 @@ where
                 } else {
                     view! {
                         <div class="flex justify-between">
-                            <b class="inline-block">"Replies:"</b>
+                            <span class="inline-block">
+                                <b>"Replies:"</b>
+                                {move || {
+                                    let count = new_reply_count();
+                                    (count > 0)
+                                        .then(|| {
+                                            view! {
+                                                <span class="ml-2 text-sm text-customBlue">
+                                                    {format!("{} new since your last visit", count)}
+                                                </span>
+                                            }
+                                        })
+                                }}
+                            </span>
                             <span class="inline-block">
                                 <select on:change=move |ev| {
                                     let new_value = event_target_value(&ev);
+                                    // Only part of the thread is loaded, so fetch it again from the other end
+                                    if has_more.get_untracked() {
+                                        load_replies_action.dispatch((new_value == "Newest First", None));
+                                    }
                                     set_order_option(new_value);
                                 }>
                                     <SelectOrderOption
@@ -735,112 +1291,216 @@ where
             }}
 
         </div>
-        <For each=sorted_replies key=|reply| reply.reply_id let:reply>
-            <div>
-                {if reply.author_name == "Encampus Assistant" {
-                    view! {
-                        <DarkenedCard class="relative p-5 rounded-lg border-2 border-[transparent] [border-image:linear-gradient(to_right,#60a5fa,#9333ea)_1]">
-                            <p class="flex gap-2 items-center font-bold">
-                                "Answered by " 
-                                <div class="flex gap-1 items-center">
-                                    <EncampusAssistantIcon size="2em" />
-                                    <span class="font-bold text-transparent bg-clip-text bg-gradient-to-r from-blue-500 to-purple-600">
-                                    {reply.author_name}
+        // Keyed on the edit time too so a reply re-renders after it is edited or rolled back
+        <For each=sorted_replies key=|reply| (reply.reply_id, reply.edited_at) let:reply>
+            {
+                let reply_id = reply.reply_id;
+                let can_edit = reply.author_id == user.get_untracked().id;
+                let (editing, set_editing) = create_signal(false);
+                let (show_history, set_show_history) = create_signal(false);
+                let update_reply = move |contents: String, edited_at: NaiveDateTime| {
+                    set_replies
+                        .update(|replies| {
+                            if let Some(reply) = replies
+                                .iter_mut()
+                                .find(|reply| reply.reply_id == reply_id)
+                            {
+                                reply.contents = contents;
+                                reply.edited_at = Some(edited_at);
+                            }
+                        })
+                };
+                let reaction_bar = view! {
+                    <Transition fallback=|| ()>
+                        {move || {
+                            reply_reactions()
+                                .map(|reactions| {
+                                    view! {
+                                        <div class="mt-3">
+                                            <ReactionBar
+                                                target=ReactionTarget::Reply(reply_id)
+                                                counts=reactions.get(&reply_id).cloned().unwrap_or_default()
+                                                on_change=|_| ()
+                                            />
+                                        </div>
+                                    }
+                                })
+                        }}
+                    </Transition>
+                };
+                let edited_marker = reply
+                    .edited_at
+                    .map(|edited_at| {
+                        view! {
+                            <EditedMarker edited_at on_click=move || set_show_history(true)/>
+                        }
+                    });
+                // Instructors see who wrote anonymous replies, marked so they don't give it away
+                let anonymity_marker = (is_instructor && reply.anonymous && !reply.fully_anonymous)
+                    .then_some(view! { <AnonymityMarker/> });
+                view! {
+                    <div>
+                        {is_new_reply(&reply)
+                            .then(|| {
+                                view! {
+                                    <span class="inline-block py-0.5 px-2 mb-1 text-xs text-white rounded-full bg-customBlue">
+                                        "New"
                                     </span>
-                                </div>
-                                {reply
-                                    .time
-                                    .checked_add_offset(FixedOffset::west_opt(6 * 3600).unwrap())
-                                    .unwrap()
-                                    .format(" at %l %p on %b %-d")
-                                    .to_string()} ":"
-                            </p>
-                            <div class="flex gap-5 justify-end">
-                                <div class="flex items-center cursor-pointer select-none">
-                                    {move || (reply.author_id == user().id || is_instructor)
-                                        .then(move || {
+                                }
+                            })}
+                        {if reply.author_name == "Encampus Assistant" {
+                            view! {
+                                <DarkenedCard class="relative p-5 rounded-lg border-2 border-[transparent] [border-image:linear-gradient(to_right,#60a5fa,#9333ea)_1]">
+                                    <p class="flex gap-2 items-center font-bold">
+                                        "Answered by " 
+                                        <div class="flex gap-1 items-center">
+                                            <EncampusAssistantIcon size="2em" />
+                                            <span class="font-bold text-transparent bg-clip-text bg-gradient-to-r from-blue-500 to-purple-600">
+                                            {reply.author_name}
+                                            </span>
+                                        </div>
+                                        {reply
+                                            .time
+                                            .checked_add_offset(FixedOffset::west_opt(6 * 3600).unwrap())
+                                            .unwrap()
+                                            .format(" at %l %p on %b %-d")
+                                            .to_string()} ":"
+                                        {edited_marker}
+                                    </p>
+                                    <div class="flex gap-5 justify-end">
+                                        <div class="flex items-center cursor-pointer select-none">
+                                            {move || (reply.author_id == user().id || is_instructor)
+                                                .then(move || {
+                                                    view! {
+                                                        <div>
+                                                            <ReplyDropdown
+                                                                remove_reply_callback
+                                                                reply_id=reply.reply_id
+                                                                reply_approved=reply.approved
+                                                                is_instructor=is_instructor
+                                                                can_edit
+                                                                on_edit=move || set_editing(true)
+                                                            />
+                                                        </div>
+                                                    }
+                                                })}
+
+                                        </div>
+                                    </div>
+                                    <br/>
+                                    <TiptapContentWrapper raw_html=reply.contents/>
+                                    {reply
+                                        .approved
+                                        .then_some(
                                             view! {
-                                                <div>
-                                                    <ReplyDropdown
-                                                        remove_reply_callback
-                                                        reply_id=reply.reply_id
-                                                        reply_approved=reply.approved
-                                                        is_instructor=is_instructor
-                                                    />
-                                                </div>
-                                            }
-                                        })}
-
-                                </div>
-                            </div>
-                            <br/>
-                            <TiptapContentWrapper raw_html=reply.contents/>
-                            {reply
-                                .approved
-                                .then_some(
-                                    view! {
-                                        <p class="text-sm font-light">
-                                            "Instructor Approved Response"
-                                        </p>
-                                    },
-                                )}
-
-                        // TODO use the reply's timestamp, author's name and anonymous info
-                        </DarkenedCard>
-                    }
-                } else {
-                    view! {
-                        // TODO use the reply's timestamp, author's name and anonymous info
-
-                        <DarkenedCard class="relative p-5">
-                            <p class="font-bold">
-                                "Answered by " {reply.author_name}
-                                {reply
-                                    .time
-                                    .checked_add_offset(FixedOffset::west_opt(6 * 3600).unwrap())
-                                    .unwrap()
-                                    .format(" at %l %p on %b %-d")
-                                    .to_string()} ":"
-                            </p>
-                            <div class="flex gap-5 justify-end">
-                                <div class="flex items-center cursor-pointer select-none">
-                                    {move || {
-                                        (reply.author_id == user().id || is_instructor)
-                                            .then(move || {
+                                                <p class="text-sm font-light">
+                                                    "Instructor Approved Response"
+                                                </p>
+                                            },
+                                        )}
+
+                                // TODO use the reply's timestamp, author's name and anonymous info
+                                </DarkenedCard>
+                            }
+                        } else {
+                            view! {
+                                // TODO use the reply's timestamp, author's name and anonymous info
+
+                                <DarkenedCard class="relative p-5">
+                                    <p class="font-bold">
+                                        "Answered by " {reply.author_name} {anonymity_marker}
+                                        {reply
+                                            .time
+                                            .checked_add_offset(FixedOffset::west_opt(6 * 3600).unwrap())
+                                            .unwrap()
+                                            .format(" at %l %p on %b %-d")
+                                            .to_string()} ":"
+                                        {edited_marker}
+                                    </p>
+                                    <div class="flex gap-5 justify-end">
+                                        <div class="flex items-center cursor-pointer select-none">
+                                            {move || {
+                                                (reply.author_id == user().id || is_instructor)
+                                                    .then(move || {
+                                                        view! {
+                                                            <div>
+                                                                <ReplyDropdown
+                                                                    remove_reply_callback
+                                                                    reply_id=reply.reply_id
+                                                                    reply_approved=reply.approved
+                                                                    is_instructor=is_instructor
+                                                                    can_edit
+                                                                    on_edit=move || set_editing(true)
+                                                                />
+                                                            </div>
+                                                        }
+                                                    })
+                                            }}
+
+                                        </div>
+                                    </div>
+                                    <br/>
+                                    {
+                                        let contents = reply.contents.clone();
+                                        move || {
+                                            if editing() {
                                                 view! {
-                                                    <div>
-                                                        <ReplyDropdown
-                                                            remove_reply_callback
-                                                            reply_id=reply.reply_id
-                                                            reply_approved=reply.approved
-                                                            is_instructor=is_instructor
-                                                        />
-                                                    </div>
+                                                    <EditReply
+                                                        reply_id
+                                                        contents=contents.clone()
+                                                        on_cancel=move || set_editing(false)
+                                                        on_saved=update_reply
+                                                    />
                                                 }
-                                            })
-                                    }}
-
-                                </div>
-                            </div>
-                            <br/>
-                            <TiptapContentWrapper raw_html=reply.contents/>
-                            {reply
-                                .approved
-                                .then_some(
-                                    view! {
-                                        <p class="text-sm font-light">
-                                            "Instructor Approved Response"
-                                        </p>
-                                    },
-                                )}
-
-                        // TODO use the reply's timestamp, author's name and anonymous info
-                        </DarkenedCard>
-                    }
-                }}
+                                                    .into_view()
+                                            } else {
+                                                view! { <TiptapContentWrapper raw_html=contents.clone()/> }
+                                                    .into_view()
+                                            }
+                                        }
+                                    }
+                                    <AttachedFiles attachments reply_id=Some(reply_id)/>
+                                    {reply
+                                        .approved
+                                        .then_some(
+                                            view! {
+                                                <p class="text-sm font-light">
+                                                    "Instructor Approved Response"
+                                                </p>
+                                            },
+                                        )}
+                                    {reaction_bar}
+
+                                // TODO use the reply's timestamp, author's name and anonymous info
+                                </DarkenedCard>
+                            }
+                        }}
+                        <Show when=show_history fallback=|| ()>
+                            <RevisionHistory
+                                target=RevisionTarget::Reply(reply_id)
+                                is_instructor
+                                on_close=move || set_show_history(false)
+                                on_rollback=move |restored: Revision| {
+                                    update_reply(restored.contents, restored.time)
+                                }
+                            />
+                        </Show>
 
-            </div>
+                    </div>
+                }
+            }
         </For>
+        <InfiniteScroll
+            has_more=has_more
+            loading=load_replies_action.pending()
+            on_load_more=move || {
+                load_replies_action
+                    .dispatch((
+                        order_option_memo.get_untracked() == "Newest First",
+                        reply_cursor.get_untracked(),
+                    ))
+            }
+        />
     }
     .into_view()
 }
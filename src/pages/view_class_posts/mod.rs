@@ -1,7 +1,19 @@
 pub mod announcement_details;
+pub mod anonymity_options;
+pub mod attachments;
+pub mod audience_picker;
 pub mod class;
+pub mod class_groups;
 pub mod class_details;
 pub mod create_post;
+pub mod duplicate_questions;
 pub mod edit_post;
 pub mod focused_post;
+pub mod pin_controls;
+pub mod post_answers;
+pub mod post_filter_bar;
 pub mod question_tile;
+pub mod reactions;
+pub mod revision_history;
+pub mod search_results;
+pub mod watch_button;
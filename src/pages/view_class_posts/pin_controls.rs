@@ -0,0 +1,97 @@
+/**
+ * PinControls component, letting instructors pin a post to the top of the class until an optional time
+ */
+use crate::data::database::post_functions::{pin_post, Post, PostFetcher};
+use crate::expect_logged_in_user;
+use crate::pages::global_components::local_datetime::{format_local, local_input_to_utc};
+use crate::resources::images::svgs::pin_icon::PinIcon;
+use crate::resources::images::svgs::unpinned_icon::UnPinIcon;
+use leptos::*;
+
+#[component]
+pub fn PinControls(post_id: i32) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    let posts = expect_context::<Resource<PostFetcher, Vec<Post>>>();
+    let (pinned_until_input, set_pinned_until_input) = create_signal(String::new());
+
+    let pinned_state = move || {
+        posts
+            .get()
+            .and_then(|posts| posts.into_iter().find(|post| post.post_id == post_id))
+            .map(|post| (post.pinned, post.pinned_until))
+            .unwrap_or((false, None))
+    };
+
+    let pin_action = create_action(
+        move |(user_id, pinned, pinned_until): &(i32, bool, Option<chrono::NaiveDateTime>)| {
+            let user_id = *user_id;
+            let pinned = *pinned;
+            let pinned_until = *pinned_until;
+            async move {
+                match pin_post(post_id, user_id, pinned, pinned_until).await {
+                    Ok(_) => posts.update(|posts| {
+                        if let Some(post) = posts
+                            .as_mut()
+                            .and_then(|posts| posts.iter_mut().find(|post| post.post_id == post_id))
+                        {
+                            post.pinned = pinned;
+                            post.pinned_until = pinned_until;
+                        }
+                    }),
+                    Err(_) => logging::error!("Attempt to pin post failed. Please try again"),
+                }
+            }
+        },
+    );
+
+    view! {
+      <div class="flex gap-2 items-center text-sm">
+        {move || {
+          let (pinned, pinned_until) = pinned_state();
+          if pinned {
+            view! {
+              <span class="text-gray-600">
+                {match pinned_until {
+                  Some(until) => format!("Pinned until {}", format_local(until, "%b %-d at %l:%M %p")),
+                  None => "Pinned".to_string(),
+                }}
+              </span>
+              <button
+                class="inline-flex gap-1 items-center py-1 px-3 text-gray-700 bg-white rounded-full hover:text-black hover:bg-gray-100"
+                on:click=move |_| pin_action.dispatch((user().id, false, None))
+              >
+                <UnPinIcon size="1em" />
+                "Unpin"
+              </button>
+            }
+              .into_view()
+          } else {
+            view! {
+              <label class="flex gap-1 items-center text-gray-600">
+                "Until"
+                <input
+                  type="datetime-local"
+                  class="py-0.5 px-1 rounded border border-gray-300"
+                  title="Leave empty to keep the post pinned until it is unpinned"
+                  prop:value=pinned_until_input
+                  on:input=move |ev| set_pinned_until_input(event_target_value(&ev))
+                />
+              </label>
+              <button
+                class="inline-flex gap-1 items-center py-1 px-3 text-gray-700 bg-white rounded-full hover:text-black hover:bg-gray-100"
+                on:click=move |_| {
+                  let pinned_until = local_input_to_utc(&pinned_until_input());
+                  pin_action.dispatch((user().id, true, pinned_until));
+                }
+              >
+                <PinIcon size="1em" />
+                "Pin"
+              </button>
+            }
+              .into_view()
+          }
+        }}
+      </div>
+    }
+    .into_view()
+}
@@ -0,0 +1,241 @@
+/**
+ * PostAnswerSections component, showing a post's collaboratively edited instructor and student answers
+ * above the discussion, each with its edit history
+ */
+use crate::data::database::answer_functions::{
+    get_answer_history, get_post_answers, save_answer, Answer, AnswerKind,
+};
+use crate::data::database::post_functions::{Post, PostFetcher};
+use crate::data::math::math_to_source;
+use crate::data::mentions::mentions_to_source;
+use crate::expect_logged_in_user;
+use crate::pages::global_components::rich_text_box::{RichTextBox, TiptapContentWrapper};
+use crate::resources::images::svgs::edit_post_icon::EditPostIcon;
+use chrono::{FixedOffset, NaiveDateTime};
+use leptoaster::*;
+use leptos::*;
+
+fn format_edit_time(time: NaiveDateTime) -> String {
+    time.checked_add_offset(FixedOffset::west_opt(6 * 3600).unwrap())
+        .unwrap()
+        .format("%b %-d at %l:%M %p")
+        .to_string()
+}
+
+#[component]
+pub fn PostAnswerSections(post_id: i32, is_instructor: bool) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    let answers = create_resource(
+        move || (post_id, user().id),
+        |(post_id, user_id)| async move { get_post_answers(post_id, user_id).await.unwrap_or_default() },
+    );
+
+    view! {
+        <Suspense fallback=|| ()>
+            {move || {
+                answers()
+                    .map(|answers| {
+                        view! {
+                            <AnswerCard
+                                post_id
+                                kind=AnswerKind::Instructor
+                                answer=answers.instructor
+                                can_edit=is_instructor
+                            />
+                            <AnswerCard
+                                post_id
+                                kind=AnswerKind::Student
+                                answer=answers.student
+                                can_edit=true
+                            />
+                        }
+                    })
+            }}
+        </Suspense>
+    }
+}
+
+#[component]
+fn AnswerCard(
+    post_id: i32,
+    kind: AnswerKind,
+    answer: Option<Answer>,
+    can_edit: bool,
+) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+    let toaster = expect_toaster();
+    let posts = expect_context::<Resource<PostFetcher, Vec<Post>>>();
+
+    let (answer, set_answer) = create_signal(answer);
+    let (editing, set_editing) = create_signal(false);
+    let (show_history, set_show_history) = create_signal(false);
+    let (draft, set_draft) = create_signal(String::new());
+
+    let (heading, card_class, empty_message) = match kind {
+        AnswerKind::Instructor => (
+            "Instructor answer",
+            "flex flex-col gap-2 p-5 rounded-xl border-l-4 bg-[#EEEEEE] border-customGreen-details",
+            "No instructor answer yet.",
+        ),
+        AnswerKind::Student => (
+            "Student answer",
+            "flex flex-col gap-2 p-5 rounded-xl border-l-4 bg-[#EEEEEE] border-customBlue",
+            "No student answer yet. Anyone in the class can write one.",
+        ),
+    };
+
+    let history = create_resource(
+        move || (show_history(), answer.with(|answer| answer.as_ref().map(|a| a.revision_count)), user().id),
+        move |(show_history, _, user_id)| async move {
+            if show_history {
+                get_answer_history(post_id, user_id, kind).await.unwrap_or_default()
+            } else {
+                vec![]
+            }
+        },
+    );
+
+    let save_action = create_action(move |(user_id, contents): &(i32, String)| {
+        let user_id = *user_id;
+        let contents = contents.clone();
+        async move {
+            match save_answer(post_id, user_id, kind, contents).await {
+                Ok(saved) => {
+                    set_answer(Some(saved));
+                    set_editing(false);
+                    // An instructor answer resolves the post
+                    if kind == AnswerKind::Instructor {
+                        posts.update(|posts| {
+                            if let Some(post) = posts
+                                .as_mut()
+                                .and_then(|posts| posts.iter_mut().find(|post| post.post_id == post_id))
+                            {
+                                post.resolved = true;
+                            }
+                        });
+                    }
+                }
+                Err(e) => toaster.toast(
+                    ToastBuilder::new(format!("Failed to save answer: {}", e))
+                        .with_level(ToastLevel::Error)
+                        .with_dismissable(true)
+                        .with_expiry(Some(4_000))
+                        .with_progress(false)
+                        .with_position(ToastPosition::BottomRight),
+                ),
+            }
+        }
+    });
+
+    view! {
+        <div class=card_class>
+            <div class="flex justify-between items-center">
+                <p class="font-bold">{heading}</p>
+                <div class="flex gap-3 items-center text-sm">
+                    <Show when=move || answer.with(Option::is_some) fallback=|| ()>
+                        <button
+                            class="text-gray-600 hover:underline"
+                            on:click=move |_| set_show_history(!show_history())
+                        >
+                            {move || {
+                                let count = answer
+                                    .with(|answer| answer.as_ref().map(|a| a.revision_count))
+                                    .unwrap_or_default();
+                                if show_history() {
+                                    "Hide history".to_string()
+                                } else {
+                                    format!("History ({})", count)
+                                }
+                            }}
+                        </button>
+                    </Show>
+                    <Show when=move || can_edit && !editing() fallback=|| ()>
+                        <button
+                            class="inline-flex gap-1 items-center py-1 px-3 text-gray-700 bg-white rounded-full hover:text-black hover:bg-gray-100"
+                            on:click=move |_| {
+                                set_draft(
+                                    answer.with(|answer| {
+                                        answer
+                                            .as_ref()
+                                            .map(|a| mentions_to_source(&math_to_source(&a.contents)))
+                                            .unwrap_or_default()
+                                    }),
+                                );
+                                set_editing(true);
+                            }
+                        >
+                            <EditPostIcon size="1em"/>
+                            {move || if answer.with(Option::is_some) { "Edit" } else { "Write answer" }}
+                        </button>
+                    </Show>
+                </div>
+            </div>
+            {move || {
+                if editing() {
+                    view! {
+                        <div class="p-3 h-72 bg-white rounded-t-lg">
+                            <RichTextBox
+                                id=format!("{}_answer_rich_text_box_{}", kind.as_str(), post_id)
+                                value=draft
+                                set_value=set_draft
+                            />
+                        </div>
+                        <div class="flex gap-3 justify-end">
+                            <button
+                                class="py-1 px-4 text-gray-700 bg-white rounded-full hover:bg-gray-100"
+                                on:click=move |_| set_editing(false)
+                            >
+                                "Cancel"
+                            </button>
+                            <button
+                                class="py-1 px-4 text-white rounded-full bg-customBlue hover:bg-customBlue-HOVER"
+                                disabled=save_action.pending()
+                                on:click=move |_| save_action.dispatch((user().id, draft()))
+                            >
+                                "Save answer"
+                            </button>
+                        </div>
+                    }
+                        .into_view()
+                } else {
+                    match answer() {
+                        Some(answer) => {
+                            view! {
+                                <TiptapContentWrapper raw_html=answer.contents/>
+                                <p class="text-xs text-gray-500">
+                                    "Last edited by " {answer.editor_name} " on "
+                                    {format_edit_time(answer.edited_at)}
+                                </p>
+                            }
+                                .into_view()
+                        }
+                        None => view! { <p class="text-gray-500">{empty_message}</p> }.into_view(),
+                    }
+                }
+            }}
+            <Show when=show_history fallback=|| ()>
+                <Transition fallback=|| view! { <p class="text-sm">"Loading history..."</p> }>
+                    <ol class="flex flex-col gap-2 pl-4 border-l border-gray-300">
+                        {move || {
+                            history()
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|revision| {
+                                    view! {
+                                        <li class="p-2 bg-white rounded">
+                                            <p class="mb-1 text-xs text-gray-500">
+                                                {revision.editor_name} " on "
+                                                {format_edit_time(revision.time)}
+                                            </p>
+                                            <TiptapContentWrapper raw_html=revision.contents/>
+                                        </li>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+                    </ol>
+                </Transition>
+            </Show>
+        </div>
+    }
+}
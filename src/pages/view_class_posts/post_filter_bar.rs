@@ -0,0 +1,296 @@
+/**
+ * PostFilterBar component, editing the post grid's filters and sort order through the URL query
+ * string so a filtered view can be shared as a link
+ */
+use crate::data::database::class_functions::check_user_is_instructor;
+use crate::data::database::group_functions::get_class_groups;
+use crate::data::database::post_functions::{PostQuery, PostSort};
+use crate::expect_logged_in_user;
+use crate::pages::view_class_posts::class::ClassId;
+use chrono::NaiveDate;
+use leptos::*;
+use leptos_router::{
+    use_location, use_navigate, use_params, use_query_map, NavigateOptions, ParamsMap,
+};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+fn sort_to_param(sort: PostSort) -> &'static str {
+    match sort {
+        PostSort::Activity => "activity",
+        PostSort::Newest => "newest",
+        PostSort::Oldest => "oldest",
+        PostSort::OldestUnanswered => "unanswered",
+        PostSort::SameQuestion => "same_question",
+    }
+}
+
+fn sort_from_param(value: &str) -> PostSort {
+    match value {
+        "newest" => PostSort::Newest,
+        "oldest" => PostSort::Oldest,
+        "unanswered" => PostSort::OldestUnanswered,
+        "same_question" => PostSort::SameQuestion,
+        _ => PostSort::Activity,
+    }
+}
+
+/// Read the post grid's query from the URL query string, ignoring anything that doesn't parse
+pub fn post_query_from_params(params: &ParamsMap) -> PostQuery {
+    let flag = |key: &str| params.get(key).is_some_and(|value| value == "1");
+    let date = |key: &str| {
+        params
+            .get(key)
+            .and_then(|value| NaiveDate::parse_from_str(value, DATE_FORMAT).ok())
+    };
+
+    PostQuery {
+        resolved: match params.get("status").map(String::as_str) {
+            Some("resolved") => Some(true),
+            Some("unresolved") => Some(false),
+            _ => None,
+        },
+        mine: flag("mine"),
+        endorsed: flag("endorsed"),
+        private: flag("private"),
+        unanswered: flag("unanswered"),
+        unread: flag("unread"),
+        after: date("after"),
+        before: date("before"),
+        group_id: params.get("group").and_then(|value| value.parse().ok()),
+        sort: params
+            .get("sort")
+            .map(|value| sort_from_param(value))
+            .unwrap_or_default(),
+    }
+}
+
+/// Write the post grid's query as URL query params, leaving out everything at its default
+fn post_query_to_params(query: &PostQuery) -> ParamsMap {
+    let mut params = ParamsMap::new();
+    if let Some(resolved) = query.resolved {
+        params.insert(
+            "status".to_string(),
+            if resolved { "resolved" } else { "unresolved" }.to_string(),
+        );
+    }
+    for (key, set) in [
+        ("mine", query.mine),
+        ("endorsed", query.endorsed),
+        ("private", query.private),
+        ("unanswered", query.unanswered),
+        ("unread", query.unread),
+    ] {
+        if set {
+            params.insert(key.to_string(), "1".to_string());
+        }
+    }
+    if let Some(after) = query.after {
+        params.insert("after".to_string(), after.format(DATE_FORMAT).to_string());
+    }
+    if let Some(before) = query.before {
+        params.insert("before".to_string(), before.format(DATE_FORMAT).to_string());
+    }
+    if let Some(group_id) = query.group_id {
+        params.insert("group".to_string(), group_id.to_string());
+    }
+    if query.sort != PostSort::default() {
+        params.insert("sort".to_string(), sort_to_param(query.sort).to_string());
+    }
+    params
+}
+
+#[component]
+pub fn PostFilterBar() -> impl IntoView {
+    let query_map = use_query_map();
+    let query = move || query_map.with(post_query_from_params);
+    let pathname = use_location().pathname;
+    let (user, _) = expect_logged_in_user!();
+    let class_id = {
+        let class_params = use_params::<ClassId>();
+        move || class_params().expect("Tried to render filter bar without class id").class_id
+    };
+    let is_instructor = create_resource(
+        move || (class_id(), user().id),
+        |(class_id, user_id)| async move {
+            check_user_is_instructor(user_id, class_id)
+                .await
+                .unwrap_or(false)
+        },
+    );
+
+    let groups = create_resource(
+        move || (class_id(), user().id),
+        |(class_id, user_id)| async move { get_class_groups(class_id, user_id).await.unwrap_or_default() },
+    );
+
+    // Every change goes through the URL; the class page refetches from it
+    let set_query = move |update: &dyn Fn(&mut PostQuery)| {
+        let mut new_query = query();
+        update(&mut new_query);
+        let navigate = use_navigate();
+        navigate(
+            &format!(
+                "{}{}",
+                pathname.get_untracked(),
+                post_query_to_params(&new_query).to_query_string()
+            ),
+            NavigateOptions {
+                scroll: false,
+                ..Default::default()
+            },
+        );
+    };
+
+    let toggle_button = move |label: &'static str,
+                              is_on: fn(&PostQuery) -> bool,
+                              toggle: fn(&mut PostQuery)| {
+        view! {
+          <button
+            class=move || {
+              if is_on(&query()) {
+                "py-1 px-3 text-sm text-white rounded-full shadow bg-customBlue hover:bg-customBlue-HOVER"
+              } else {
+                "py-1 px-3 text-sm text-gray-700 bg-white rounded-full shadow hover:bg-gray-100"
+              }
+            }
+            on:click=move |_| set_query(&|query| toggle(query))
+          >
+            {label}
+          </button>
+        }
+    };
+
+    view! {
+      <div class="flex flex-wrap gap-2 items-center">
+        {toggle_button(
+          "Unresolved",
+          |query| query.resolved == Some(false),
+          |query| query.resolved = if query.resolved == Some(false) { None } else { Some(false) },
+        )}
+        {toggle_button(
+          "Resolved",
+          |query| query.resolved == Some(true),
+          |query| query.resolved = if query.resolved == Some(true) { None } else { Some(true) },
+        )}
+        {toggle_button("My posts", |query| query.mine, |query| query.mine = !query.mine)}
+        {toggle_button("Endorsed", |query| query.endorsed, |query| query.endorsed = !query.endorsed)}
+        {toggle_button("Private", |query| query.private, |query| query.private = !query.private)}
+        {toggle_button(
+          "No replies",
+          |query| query.unanswered,
+          |query| query.unanswered = !query.unanswered,
+        )}
+        {toggle_button("Unread", |query| query.unread, |query| query.unread = !query.unread)}
+        <label class="flex gap-1 items-center ml-2 text-sm text-gray-700">
+          "From"
+          <input
+            type="date"
+            class="py-1 px-2 bg-white rounded border border-gray-300"
+            prop:value=move || {
+              query().after.map(|date| date.format(DATE_FORMAT).to_string()).unwrap_or_default()
+            }
+            on:change=move |ev| {
+              let after = NaiveDate::parse_from_str(&event_target_value(&ev), DATE_FORMAT).ok();
+              set_query(&|query| query.after = after);
+            }
+          />
+        </label>
+        <label class="flex gap-1 items-center text-sm text-gray-700">
+          "To"
+          <input
+            type="date"
+            class="py-1 px-2 bg-white rounded border border-gray-300"
+            prop:value=move || {
+              query().before.map(|date| date.format(DATE_FORMAT).to_string()).unwrap_or_default()
+            }
+            on:change=move |ev| {
+              let before = NaiveDate::parse_from_str(&event_target_value(&ev), DATE_FORMAT).ok();
+              set_query(&|query| query.before = before);
+            }
+          />
+        </label>
+        // Classes without groups don't get a group filter
+        <Transition fallback=|| ()>
+          {move || {
+            groups()
+              .filter(|groups| !groups.is_empty())
+              .map(|groups| {
+                view! {
+                  <select
+                    class="py-1 px-2 ml-2 text-sm bg-white rounded border border-gray-300"
+                    on:change=move |ev| {
+                      let group_id = event_target_value(&ev).parse().ok();
+                      set_query(&|query| query.group_id = group_id);
+                    }
+                  >
+                    <option value="" selected=move || query().group_id.is_none()>
+                      "All groups"
+                    </option>
+                    {groups
+                      .into_iter()
+                      .map(|group| {
+                        let group_id = group.group_id;
+                        view! {
+                          <option
+                            value=group_id
+                            selected=move || query().group_id == Some(group_id)
+                          >
+                            {group.name}
+                          </option>
+                        }
+                      })
+                      .collect_view()}
+                  </select>
+                }
+              })
+          }}
+        </Transition>
+        <select
+          class="py-1 px-2 ml-2 text-sm bg-white rounded border border-gray-300"
+          on:change=move |ev| {
+            let sort = sort_from_param(&event_target_value(&ev));
+            set_query(&|query| query.sort = sort);
+          }
+        >
+          <option value="activity" selected=move || query().sort == PostSort::Activity>
+            "Recent activity"
+          </option>
+          <option value="newest" selected=move || query().sort == PostSort::Newest>
+            "Newest"
+          </option>
+          <option value="oldest" selected=move || query().sort == PostSort::Oldest>
+            "Oldest"
+          </option>
+          <option value="unanswered" selected=move || query().sort == PostSort::OldestUnanswered>
+            "Oldest unanswered"
+          </option>
+          // Sorting by how many students have the same question is for triage by instructors
+          <Transition fallback=|| ()>
+            {move || {
+              is_instructor()
+                .unwrap_or(false)
+                .then(|| {
+                  view! {
+                    <option
+                      value="same_question"
+                      selected=move || query().sort == PostSort::SameQuestion
+                    >
+                      "Most \"same question\""
+                    </option>
+                  }
+                })
+            }}
+          </Transition>
+        </select>
+        <Show when=move || query() != PostQuery::default() fallback=|| ()>
+          <button
+            class="text-sm text-gray-500 hover:underline"
+            on:click=move |_| set_query(&|query| *query = PostQuery::default())
+          >
+            "Clear filters"
+          </button>
+        </Show>
+      </div>
+    }
+}
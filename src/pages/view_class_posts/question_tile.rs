@@ -3,13 +3,15 @@
  */
 use crate::data::database::class_functions::check_user_is_instructor;
 use crate::data::database::post_functions::{
-    bump_post, endorse_post, get_reply_counts, remove_post, Post, PostFetcher,
+    bump_post, endorse_post, get_reply_counts, pin_post, remove_post, Post, PostFetcher,
 };
 use crate::expect_logged_in_user;
 use crate::pages::global_components::notification::{
     NotificationComponent, NotificationDetails, NotificationType,
 };
 use crate::pages::view_class_posts::class::ClassId;
+use crate::pages::view_class_posts::reactions::ReactionSummary;
+use crate::pages::view_class_posts::watch_button::WatchButton;
 use crate::resources::images::svgs::bump_icon::BumpIcon;
 use crate::resources::images::svgs::check_icon::CheckIcon;
 use crate::resources::images::svgs::dots_icon::DotsIcon;
@@ -18,7 +20,9 @@ use crate::resources::images::svgs::graduation_cap_icon::GraduationCapIcon;
 use crate::resources::images::svgs::instructor_endorsed_icon::InstructorEndorsedIcon;
 use crate::resources::images::svgs::instructor_icon::InstructorIcon;
 use crate::resources::images::svgs::lock_icon::LockIcon;
+use crate::resources::images::svgs::pin_icon::PinIcon;
 use crate::resources::images::svgs::remove_icon::RemoveIcon;
+use crate::resources::images::svgs::unpinned_icon::UnPinIcon;
 use crate::resources::images::svgs::unresolved_icon::UnresolvedIcon;
 use leptos::*;
 use leptos_router::{use_params, A};
@@ -28,6 +32,8 @@ enum TagPillProperties {
     Private,
     Endorsed,
     Resolved,
+    Unread,
+    Pinned,
 }
 
 #[component]
@@ -36,6 +42,8 @@ pub fn DropDownMenu(
     post_author_id: i32,
     set_endorsed: WriteSignal<bool>,
     is_endorsed: ReadSignal<bool>,
+    is_pinned: bool,
+    watched: bool,
     //remove_action: Action<PostId, ()>,
 ) -> impl IntoView {
     let posts: Resource<PostFetcher, Vec<Post>> =
@@ -89,6 +97,7 @@ pub fn DropDownMenu(
 
                         // Update the last_bumped timestamp to current time
                         bumped_post.last_bumped = Some(chrono::Utc::now().naive_utc());
+                        bumped_post.last_activity = chrono::Utc::now().naive_utc();
 
                         // Find the correct position based on last_bumped timestamps
                         let insert_position = posts_mut
@@ -131,6 +140,33 @@ pub fn DropDownMenu(
         }
     });
 
+    // Pin logic
+    let pin_action = create_action(move |(post_id, user_id, pinned): &(i32, i32, bool)| {
+        let post_id = *post_id;
+        let user_id = *user_id;
+        let pinned = *pinned;
+        async move {
+            match pin_post(post_id, user_id, pinned, None).await {
+                Ok(_) => posts.update(|posts| {
+                    if let Some(post) = posts
+                        .as_mut()
+                        .and_then(|posts| posts.iter_mut().find(|post| post.post_id == post_id))
+                    {
+                        post.pinned = pinned;
+                        post.pinned_until = None;
+                    }
+                }),
+                Err(_) => {
+                    logging::error!("Attempt to pin post failed. Please try again");
+                    set_notification_details(Some(NotificationDetails {
+                        message: "Failed to pin post. Please try again.".to_string(),
+                        notification_type: NotificationType::Error,
+                    }));
+                }
+            }
+        }
+    });
+
     // Remove logic
     let remove_action = create_action(move |(post_id, user_id, class_id): &(i32, i32, i32)| {
         let post_id = *post_id;
@@ -175,6 +211,19 @@ pub fn DropDownMenu(
                   <span class="ml-2">Endorse</span>
                 </button>
               </div>
+              <div class="p-1">
+                <button
+                  class="inline-flex items-center p-1 w-full text-sm leading-tight text-gray-700 rounded-md hover:text-black hover:bg-gray-100"
+                  on:mousedown=move |_| pin_action.dispatch((post_id, user().id, !is_pinned))
+                >
+                  {if is_pinned {
+                    view! { <UnPinIcon size="20px" /> }
+                  } else {
+                    view! { <PinIcon size="20px" /> }
+                  }}
+                  <span class="ml-2">{if is_pinned { "Unpin" } else { "Pin" }}</span>
+                </button>
+              </div>
             }
               .into_view()
           }
@@ -203,6 +252,13 @@ pub fn DropDownMenu(
               .into_view()
           }
         }}
+        <div class="p-1">
+          <WatchButton
+            post_id=post_id
+            watched=watched
+            class="inline-flex items-center p-1 w-full text-sm leading-tight text-gray-700 rounded-md hover:text-black hover:bg-gray-100"
+          />
+        </div>
         {move || {
           if is_on_my_post() {
             Some(
@@ -234,6 +290,11 @@ pub fn QuestionTile(
 ) -> impl IntoView {
     let (menu_invisible, set_menu_invisible) = create_signal(true);
     let (is_endorsed, set_endorsed) = create_signal(post.endorsed);
+    let is_unread = post.unread;
+    let new_replies = post.new_replies;
+    let is_pinned = post.pinned;
+    let (thanks_count, same_question_count, confused_count) =
+        (post.thanks_count, post.same_question_count, post.confused_count);
 
     // Gets the reply counts.
     let reply_counts = create_resource(
@@ -270,6 +331,8 @@ pub fn QuestionTile(
 
             // Card header
             <div class="flex top-0 left-0 z-10 gap-2 items-center pl-2 w-full h-12 text-xs rounded-t-lg shadow-md bg-card-header">
+              {is_pinned.then(|| view! { <TagPill props=TagPillProperties::Pinned /> })}
+              {is_unread.then(|| view! { <TagPill props=TagPillProperties::Unread /> })}
               {move || {
                 if is_resolved() {
                   Some(view! { <TagPill props=TagPillProperties::Unresolved /> })
@@ -310,8 +373,27 @@ pub fn QuestionTile(
             </div>
             // Info string
             <div class="flex justify-between items-center p-4 w-full text-sm text-gray-600">
-              <span>{format_time_ago()}</span>
-              <div class="flex gap-4">
+              <span>
+                {format_time_ago()}
+                {(new_replies > 0)
+                  .then(|| {
+                    view! {
+                      <span class="ml-2 font-semibold text-customBlue">
+                        {if new_replies == 1 {
+                          "1 new reply".to_string()
+                        } else {
+                          format!("{} new replies", new_replies)
+                        }}
+                      </span>
+                    }
+                  })}
+              </span>
+              <div class="flex gap-4 items-center">
+                <ReactionSummary
+                  thanks=thanks_count
+                  same_question=same_question_count
+                  confused=confused_count
+                />
                 // Student responses
                 <ResponseCounter
                   bg_color="bg-[#3256BE]"
@@ -363,6 +445,8 @@ pub fn QuestionTile(
               post_author_id=post.author_id
               set_endorsed=set_endorsed
               is_endorsed=is_endorsed
+              is_pinned=is_pinned
+              watched=post.watched
             />
 
           </div>
@@ -445,6 +529,17 @@ fn TagPill(props: TagPillProperties) -> impl IntoView {
             "Resolved"
           </div>
         },
+        TagPillProperties::Unread => view! {
+          <div class=[sharedClassesAll, "bg-customBlue text-white"].join(" ")>"New"</div>
+        },
+        TagPillProperties::Pinned => view! {
+          <div class=[sharedClassesAll, sharedClassesWithIcon, "bg-white text-gray-700"].join(" ")>
+            <span class="relative top-[2px]">
+              <PinIcon size="1em" />
+            </span>
+            "Pinned"
+          </div>
+        },
         // TagPillProperties::Custom(CustomTag { title }) => {
         //     view! { <div class=[sharedClassesAll, "bg-white text-gray-600"].join(" ")>{title}</div> }
         // }
@@ -0,0 +1,81 @@
+/**
+ * ReactionBar component for reacting to a post or reply, and ReactionSummary for showing the counts on a tile
+ */
+use crate::data::database::reaction_functions::{
+    toggle_reaction, Reaction, ReactionCounts, ReactionTarget,
+};
+use crate::expect_logged_in_user;
+use leptos::*;
+
+#[component]
+pub fn ReactionBar<F>(target: ReactionTarget, counts: ReactionCounts, on_change: F) -> impl IntoView
+where
+    F: Fn(ReactionCounts) + 'static + Copy,
+{
+    let (user, _) = expect_logged_in_user!();
+    let (counts, set_counts) = create_signal(counts);
+
+    let toggle_action = create_action(move |(reaction, user_id): &(Reaction, i32)| {
+        let reaction = *reaction;
+        let user_id = *user_id;
+        async move {
+            match toggle_reaction(target, user_id, reaction).await {
+                Ok(new_counts) => {
+                    set_counts(new_counts.clone());
+                    on_change(new_counts);
+                }
+                Err(_) => logging::error!("Attempt to react failed. Please try again"),
+            }
+        }
+    });
+
+    view! {
+        <div class="flex gap-2 items-center">
+            {Reaction::ALL
+                .into_iter()
+                .map(|reaction| {
+                    view! {
+                        <button
+                            class=move || {
+                                if counts.with(|counts| counts.has_reacted(reaction)) {
+                                    "inline-flex gap-1 items-center py-0.5 px-2 text-sm rounded-full border border-customBlue bg-blue-50"
+                                } else {
+                                    "inline-flex gap-1 items-center py-0.5 px-2 text-sm bg-white rounded-full border border-gray-300 hover:bg-gray-100"
+                                }
+                            }
+                            title=reaction.label()
+                            disabled=toggle_action.pending()
+                            on:click=move |_| toggle_action.dispatch((reaction, user().id))
+                        >
+                            <span>{reaction.emoji()}</span>
+                            <span class="text-xs">{reaction.label()}</span>
+                            <span class="text-xs font-semibold">
+                                {move || counts.with(|counts| counts.count(reaction))}
+                            </span>
+                        </button>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+}
+
+#[component]
+pub fn ReactionSummary(thanks: i64, same_question: i64, confused: i64) -> impl IntoView {
+    [
+        (Reaction::Thanks, thanks),
+        (Reaction::SameQuestion, same_question),
+        (Reaction::Confused, confused),
+    ]
+    .into_iter()
+    .filter(|(_, count)| *count > 0)
+    .map(|(reaction, count)| {
+        view! {
+            <span class="inline-flex gap-0.5 items-center" title=reaction.label()>
+                {reaction.emoji()}
+                {count}
+            </span>
+        }
+    })
+    .collect_view()
+}
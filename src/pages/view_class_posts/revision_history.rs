@@ -0,0 +1,370 @@
+/**
+ * RevisionHistory dialog, listing every version of a post or reply with a word-level diff against
+ * the version before it. Instructors can restore any earlier version.
+ */
+use crate::data::database::revision_functions::{
+    get_revisions, rollback_revision, Revision, RevisionTarget,
+};
+use crate::data::math::math_to_source;
+use crate::expect_logged_in_user;
+use chrono::{FixedOffset, NaiveDateTime};
+use leptos::*;
+
+/// Diffs needing more than this many word comparisons, once the words they start and end with in
+/// common are set aside, are shown as a full replacement instead. Their table takes up to 500 KB.
+const MAX_DIFF_CELLS: usize = 250_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DiffKind {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct DiffPart {
+    text: String,
+    kind: DiffKind,
+}
+
+/// The text of some stored HTML with the tags dropped and the common entities decoded
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push(' ');
+            }
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Word-level diff of two texts from the longest common subsequence of their words, with runs of
+/// the same kind merged
+fn word_diff(old: &str, new: &str) -> Vec<DiffPart> {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+
+    // Edits usually change a small part of the text, so the words before and after it aren't compared
+    let prefix = old.iter().zip(&new).take_while(|(old, new)| old == new).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let changed_old = &old[prefix..old.len() - suffix];
+    let changed_new = &new[prefix..new.len() - suffix];
+
+    let mut words: Vec<(&str, DiffKind)> =
+        old[..prefix].iter().map(|word| (*word, DiffKind::Same)).collect();
+    let width = changed_new.len() + 1;
+    if (changed_old.len() + 1) * width > MAX_DIFF_CELLS {
+        words.extend(changed_old.iter().map(|word| (*word, DiffKind::Removed)));
+        words.extend(changed_new.iter().map(|word| (*word, DiffKind::Added)));
+    } else {
+        // lcs[i * width + j] is the length of the common subsequence of changed_old[i..] and
+        // changed_new[j..]. It is at most the length of the shorter side, which the cap keeps under
+        // 500 words, so it fits in a u16.
+        let mut lcs = vec![0u16; (changed_old.len() + 1) * width];
+        for i in (0..changed_old.len()).rev() {
+            for j in (0..changed_new.len()).rev() {
+                lcs[i * width + j] = if changed_old[i] == changed_new[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < changed_old.len() && j < changed_new.len() {
+            if changed_old[i] == changed_new[j] {
+                words.push((changed_old[i], DiffKind::Same));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                words.push((changed_old[i], DiffKind::Removed));
+                i += 1;
+            } else {
+                words.push((changed_new[j], DiffKind::Added));
+                j += 1;
+            }
+        }
+        words.extend(changed_old[i..].iter().map(|word| (*word, DiffKind::Removed)));
+        words.extend(changed_new[j..].iter().map(|word| (*word, DiffKind::Added)));
+    }
+    words.extend(old[old.len() - suffix..].iter().map(|word| (*word, DiffKind::Same)));
+
+    let mut parts: Vec<DiffPart> = Vec::new();
+    for (word, kind) in words {
+        match parts.last_mut() {
+            Some(part) if part.kind == kind => {
+                part.text.push(' ');
+                part.text.push_str(word);
+            }
+            _ => parts.push(DiffPart {
+                text: word.to_string(),
+                kind,
+            }),
+        }
+    }
+    parts
+}
+
+fn revision_text(revision: &Revision) -> String {
+    // Math is compared as the LaTeX it was written as rather than the text of its MathML
+    let contents = html_to_text(&math_to_source(&revision.contents));
+    match &revision.title {
+        Some(title) => format!("{}\n{}", title, contents),
+        None => contents,
+    }
+}
+
+fn format_revision_time(time: NaiveDateTime) -> String {
+    time.checked_add_offset(FixedOffset::west_opt(6 * 3600).unwrap())
+        .unwrap()
+        .format("%b %-d at %l:%M %p")
+        .to_string()
+}
+
+#[component]
+fn Diff(parts: Vec<DiffPart>) -> impl IntoView {
+    parts
+        .into_iter()
+        .map(|part| {
+            let class = match part.kind {
+                DiffKind::Same => "",
+                DiffKind::Added => "bg-green-100 text-green-900",
+                DiffKind::Removed => "bg-red-100 text-red-900 line-through",
+            };
+            view! { <span class=class>{part.text}</span> " " }
+        })
+        .collect_view()
+}
+
+/**
+ * Small "edited" marker shown next to edited content, opening its history when clicked
+ */
+#[component]
+pub fn EditedMarker<F>(edited_at: NaiveDateTime, on_click: F) -> impl IntoView
+where
+    F: Fn() + 'static,
+{
+    view! {
+        <button
+            class="ml-2 text-xs italic text-gray-500 hover:underline"
+            title=format!("Last edited {}", format_revision_time(edited_at))
+            on:click=move |_| on_click()
+        >
+            "(edited)"
+        </button>
+    }
+}
+
+#[component]
+pub fn RevisionHistory<C, R>(
+    target: RevisionTarget,
+    is_instructor: bool,
+    on_close: C,
+    /// Called with the restored version after an instructor rolls back
+    on_rollback: R,
+) -> impl IntoView
+where
+    C: Fn() + Copy + 'static,
+    R: Fn(Revision) + Copy + 'static,
+{
+    let (user, _) = expect_logged_in_user!();
+    let revisions = create_resource(
+        move || (target, user().id),
+        |(target, user_id)| async move { get_revisions(target, user_id).await.unwrap_or_default() },
+    );
+
+    let rollback_action = create_action(move |(revision_id, user_id): &(i32, i32)| {
+        let revision_id = *revision_id;
+        let user_id = *user_id;
+        async move {
+            match rollback_revision(target, revision_id, user_id).await {
+                Ok(restored) => {
+                    on_rollback(restored);
+                    on_close();
+                }
+                Err(_) => logging::error!("Attempt to roll back edit failed. Please try again"),
+            }
+        }
+    });
+
+    view! {
+        <div class="flex fixed inset-0 z-50 justify-center items-center p-4 bg-black/50">
+            <div class="overflow-y-auto relative p-6 w-11/12 max-w-3xl bg-white rounded-xl shadow-2xl max-h-[85vh]">
+                <button
+                    class="flex absolute top-4 right-4 justify-center items-center w-8 h-8 text-white bg-red-500 rounded-full hover:bg-red-600"
+                    on:click=move |_| on_close()
+                >
+                    <span class="text-xl font-bold leading-none">"×"</span>
+                </button>
+                <h2 class="mb-4 text-xl font-bold">"Edit history"</h2>
+                <Suspense fallback=|| view! { <p>"Loading history..."</p> }>
+                    {move || {
+                        revisions()
+                            .map(|revisions| {
+                                if revisions.is_empty() {
+                                    return view! { <p class="text-gray-500">"This has not been edited."</p> }
+                                        .into_view();
+                                }
+                                // Newest first, so each revision is compared with the one after it
+                                let texts: Vec<String> = revisions.iter().map(revision_text).collect();
+                                revisions
+                                    .into_iter()
+                                    .enumerate()
+                                    .map(|(index, revision)| {
+                                        let revision_id = revision.revision_id;
+                                        let diff = match texts.get(index + 1) {
+                                            Some(previous) => word_diff(previous, &texts[index]),
+                                            None => word_diff("", &texts[index])
+                                                .into_iter()
+                                                .map(|part| DiffPart { kind: DiffKind::Same, ..part })
+                                                .collect(),
+                                        };
+                                        let label = if index == 0 {
+                                            "Current version"
+                                        } else if index + 1 == texts.len() {
+                                            "Original"
+                                        } else {
+                                            "Edit"
+                                        };
+                                        view! {
+                                            <div class="py-3 border-b last:border-b-0">
+                                                <div class="flex justify-between items-center mb-1">
+                                                    <p class="text-sm text-gray-600">
+                                                        <span class="font-semibold">{label}</span>
+                                                        " by " {revision.editor_name} " on "
+                                                        {format_revision_time(revision.time)}
+                                                    </p>
+                                                    {(is_instructor && index > 0)
+                                                        .then(|| {
+                                                            view! {
+                                                                <button
+                                                                    class="py-1 px-3 text-sm text-gray-700 bg-white rounded-full shadow hover:bg-gray-100"
+                                                                    disabled=rollback_action.pending()
+                                                                    on:click=move |_| {
+                                                                        rollback_action.dispatch((revision_id, user().id))
+                                                                    }
+                                                                >
+                                                                    "Restore this version"
+                                                                </button>
+                                                            }
+                                                        })}
+                                                </div>
+                                                <p class="whitespace-pre-wrap">
+                                                    <Diff parts=diff/>
+                                                </p>
+                                            </div>
+                                        }
+                                    })
+                                    .collect_view()
+                            })
+                    }}
+                </Suspense>
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(text: &str, kind: DiffKind) -> DiffPart {
+        DiffPart {
+            text: text.to_string(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn identical_texts_are_the_same() {
+        assert_eq!(
+            word_diff("the quick  brown fox", "the quick brown\nfox"),
+            vec![part("the quick brown fox", DiffKind::Same)]
+        );
+        assert_eq!(word_diff("", ""), vec![]);
+    }
+
+    #[test]
+    fn inserted_words_are_added() {
+        assert_eq!(
+            word_diff("the fox", "the quick brown fox"),
+            vec![
+                part("the", DiffKind::Same),
+                part("quick brown", DiffKind::Added),
+                part("fox", DiffKind::Same),
+            ]
+        );
+        assert_eq!(word_diff("", "new text"), vec![part("new text", DiffKind::Added)]);
+    }
+
+    #[test]
+    fn deleted_words_are_removed() {
+        assert_eq!(
+            word_diff("the quick brown fox", "the fox"),
+            vec![
+                part("the", DiffKind::Same),
+                part("quick brown", DiffKind::Removed),
+                part("fox", DiffKind::Same),
+            ]
+        );
+        assert_eq!(word_diff("old text", ""), vec![part("old text", DiffKind::Removed)]);
+    }
+
+    #[test]
+    fn replaced_words_are_removed_then_added() {
+        assert_eq!(
+            word_diff("a b c d", "a x c y"),
+            vec![
+                part("a", DiffKind::Same),
+                part("b", DiffKind::Removed),
+                part("x", DiffKind::Added),
+                part("c", DiffKind::Same),
+                part("d", DiffKind::Removed),
+                part("y", DiffKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn diffs_over_the_cap_are_full_replacements() {
+        let words = |prefix: &str| {
+            (0..600)
+                .map(|i| format!("{}{}", prefix, i))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let (old, new) = (words("old"), words("new"));
+        // A shared word in the middle would be kept if the words were compared
+        let diff = word_diff(
+            &format!("start {} shared {} end", old, old),
+            &format!("start {} shared {} end", new, new),
+        );
+        assert_eq!(
+            diff,
+            vec![
+                part("start", DiffKind::Same),
+                part(&format!("{} shared {}", old, old), DiffKind::Removed),
+                part(&format!("{} shared {}", new, new), DiffKind::Added),
+                part("end", DiffKind::Same),
+            ]
+        );
+    }
+}
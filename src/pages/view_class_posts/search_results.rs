@@ -0,0 +1,71 @@
+/**
+ * SearchResults component, listing the posts matching a search with the matches highlighted
+ */
+use crate::data::database::search_functions::{HighlightedText, SearchResult};
+use crate::resources::images::svgs::check_icon::CheckIcon;
+use leptos::*;
+use leptos_router::A;
+
+#[component]
+pub fn SearchResults(class_id: i32, results: Vec<SearchResult>) -> impl IntoView {
+    if results.is_empty() {
+        return view! { <p class="text-gray-600">"No posts match your search."</p> }.into_view();
+    }
+
+    view! {
+      <ul class="flex flex-col gap-3">
+        {results
+          .into_iter()
+          .map(|result| {
+            view! {
+              <li>
+                <A
+                  href=format!("/classes/{}/{}", class_id, result.post_id)
+                  class="block p-4 bg-white rounded-lg shadow hover:bg-gray-50"
+                >
+                  <div class="flex gap-2 items-center">
+                    <p class="text-base font-bold">
+                      <Highlighted parts=result.title />
+                    </p>
+                    {result
+                      .resolved
+                      .then(|| {
+                        view! {
+                          <span class="text-customGreen-details">
+                            <CheckIcon size="1em" />
+                          </span>
+                        }
+                      })}
+                  </div>
+                  <p class="text-sm text-gray-700">
+                    {result
+                      .matched_reply
+                      .then(|| view! { <span class="mr-1 font-semibold">"In a reply:"</span> })}
+                    <Highlighted parts=result.snippet />
+                  </p>
+                  <p class="text-xs text-gray-500">
+                    {result.timestamp.format("Posted %b %-d, %Y").to_string()}
+                  </p>
+                </A>
+              </li>
+            }
+          })
+          .collect_view()}
+      </ul>
+    }
+    .into_view()
+}
+
+#[component]
+pub fn Highlighted(parts: Vec<HighlightedText>) -> impl IntoView {
+    parts
+        .into_iter()
+        .map(|part| {
+            if part.highlighted {
+                view! { <mark class="bg-customYellow">{part.text}</mark> }.into_view()
+            } else {
+                part.text.into_view()
+            }
+        })
+        .collect_view()
+}
@@ -0,0 +1,56 @@
+/**
+ * WatchButton component, toggling whether the user is notified about activity on a post
+ */
+use crate::data::database::watch_functions::{get_post_watched, set_post_watched};
+use crate::expect_logged_in_user;
+use crate::resources::images::svgs::watch_icon::WatchIcon;
+use leptos::*;
+
+#[component]
+pub fn WatchButton(
+    post_id: i32,
+    /// Whether the user watches the post, when it was loaded along with the post
+    #[prop(optional)]
+    watched: Option<bool>,
+    #[prop(optional, into)] class: String,
+) -> impl IntoView {
+    let (user, _) = expect_logged_in_user!();
+
+    let watching = create_resource(
+        move || (post_id, user().id),
+        move |(post_id, user_id)| async move {
+            match watched {
+                Some(watched) => watched,
+                None => get_post_watched(post_id, user_id).await.unwrap_or(false),
+            }
+        },
+    );
+
+    let toggle_watch_action = create_action(move |(user_id, status): &(i32, bool)| {
+        let user_id = *user_id;
+        let status = *status;
+        async move {
+            match set_post_watched(post_id, user_id, status).await {
+                Ok(_) => watching.set(status),
+                Err(_) => logging::error!("Attempt to watch post failed. Please try again"),
+            }
+        }
+    });
+
+    view! {
+      <button
+        class=class
+        on:mousedown=move |_| {
+          if let Some(is_watching) = watching.get() {
+            toggle_watch_action.dispatch((user().id, !is_watching));
+          }
+        }
+      >
+        <WatchIcon size="20px" />
+        <span class="ml-2">
+          {move || if watching.get().unwrap_or(false) { "Unwatch" } else { "Watch" }}
+        </span>
+      </button>
+    }
+    .into_view()
+}
@@ -0,0 +1,18 @@
+/**
+ * Code block icon component, used in the rich text editor
+ */
+use leptos::{component, view, IntoView};
+
+#[component]
+pub fn CodeBlockIcon(
+    /// css style size (E.g. sized="20px")
+    /// (Note: Currently only supports static strings.
+    ///  If you require dynamic sizing edit this component to take in a closure instead.)
+    size: &'static str,
+) -> impl IntoView {
+    view! {
+      <svg width=size height=size xmlns="http://www.w3.org/2000/svg" viewBox="0 0 16 16">
+        <path d="M10.478 1.647a.5.5 0 1 0-.956-.294l-4 13a.5.5 0 0 0 .956.294zM4.854 4.146a.5.5 0 0 1 0 .708L1.707 8l3.147 3.146a.5.5 0 0 1-.708.708l-3.5-3.5a.5.5 0 0 1 0-.708l3.5-3.5a.5.5 0 0 1 .708 0m6.292 0a.5.5 0 0 0 0 .708L14.293 8l-3.147 3.146a.5.5 0 0 0 .708.708l3.5-3.5a.5.5 0 0 0 0-.708l-3.5-3.5a.5.5 0 0 0-.708 0" />
+      </svg>
+    }
+}
@@ -11,6 +11,7 @@ pub mod bold_icon;
 pub mod bump_icon;
 pub mod cancel_icon;
 pub mod check_icon;
+pub mod code_block_icon;
 pub mod create_post_icon;
 pub mod dashboard_icon;
 pub mod dots_icon;
@@ -46,3 +47,4 @@ pub mod unapprove_icon;
 pub mod unpinned_icon;
 pub mod unresolved_icon;
 pub mod warning_icon;
+pub mod watch_icon;
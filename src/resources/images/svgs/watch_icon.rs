@@ -0,0 +1,21 @@
+/**
+ * Watch (eye) icon component, used to follow a post.
+ */
+use leptos::{component, view, IntoView};
+
+#[component]
+pub fn WatchIcon(
+    /// css style size (E.g. sized="20px")
+    /// (Note: Currently only supports static strings.
+    ///  If you require dynamic sizing edit this component to take in a closure instead.)
+    size: &'static str,
+) -> impl IntoView {
+    view! {
+      <svg xmlns="http://www.w3.org/2000/svg" width=size height=size viewBox="0 0 24 24">
+        <path
+          fill="currentColor"
+          d="M12 4.5C7 4.5 2.73 7.61 1 12c1.73 4.39 6 7.5 11 7.5s9.27-3.11 11-7.5c-1.73-4.39-6-7.5-11-7.5M12 17a5 5 0 1 1 0-10a5 5 0 0 1 0 10m0-8a3 3 0 1 0 0 6a3 3 0 0 0 0-6"
+        />
+      </svg>
+    }
+}
@@ -0,0 +1,73 @@
+/*
+ * Editor commands that leptos-tiptap doesn't expose, run against the editors it keeps in
+ * window._leptosTiptapEditors. Called from rich_text_box.rs.
+ */
+
+function editorWithId(id) {
+  const entry = window._leptosTiptapEditors && window._leptosTiptapEditors.get(id);
+  return entry ? entry.editor : undefined;
+}
+
+// Turns the current block into a code block in the given language, or back into a paragraph
+window.encampusToggleCodeBlock = function (id, language) {
+  const editor = editorWithId(id);
+  if (editor) {
+    editor.chain().focus().toggleCodeBlock(language ? { language: language } : {}).run();
+  }
+};
+
+// Changes the language of the code block the cursor is in
+window.encampusSetCodeBlockLanguage = function (id, language) {
+  const editor = editorWithId(id);
+  if (editor && editor.isActive("codeBlock")) {
+    editor.chain().focus().updateAttributes("codeBlock", { language: language || null }).run();
+  }
+};
+
+// The language of the code block the cursor is in: "" for no language, or null outside of one
+window.encampusCodeBlockLanguage = function (id) {
+  const editor = editorWithId(id);
+  if (!editor || !editor.isActive("codeBlock")) {
+    return null;
+  }
+  return editor.getAttributes("codeBlock").language || "";
+};
+
+// Inserts plain text at the cursor, without parsing it as HTML
+window.encampusInsertText = function (id, text) {
+  const editor = editorWithId(id);
+  if (editor) {
+    editor.chain().focus().insertContent({ type: "text", text: text }).run();
+  }
+};
+
+// The text of the current block up to the cursor, with anything that isn't text as one character
+window.encampusTextBeforeCursor = function (id) {
+  const editor = editorWithId(id);
+  if (!editor) {
+    return null;
+  }
+  const { $from } = editor.state.selection;
+  return $from.parent.textBetween(0, $from.parentOffset, undefined, "￼");
+};
+
+// Replaces the @mention or #reference being typed before the cursor with the given text
+window.encampusReplaceMentionQuery = function (id, text) {
+  const editor = editorWithId(id);
+  if (!editor) {
+    return;
+  }
+  const { $from } = editor.state.selection;
+  const before = $from.parent.textBetween(0, $from.parentOffset, undefined, "￼");
+  const start = Math.max(before.lastIndexOf("@"), before.lastIndexOf("#"));
+  if (start < 0) {
+    return;
+  }
+  const from = $from.pos - (before.length - start);
+  editor
+    .chain()
+    .focus()
+    .deleteRange({ from: from, to: $from.pos })
+    .insertContent({ type: "text", text: text })
+    .run();
+};
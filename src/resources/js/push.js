@@ -0,0 +1,47 @@
+/*
+ * Registers the service worker and subscribes the browser to Web Push, or unsubscribes it.
+ * Called from push_notifications.rs, which tells the server about the subscription.
+ */
+
+function urlBase64ToUint8Array(base64String) {
+  const padding = "=".repeat((4 - (base64String.length % 4)) % 4);
+  const base64 = (base64String + padding).replace(/-/g, "+").replace(/_/g, "/");
+  const raw = window.atob(base64);
+  return Uint8Array.from([...raw].map((char) => char.charCodeAt(0)));
+}
+
+// Resolves to the JSON-encoded PushSubscription, or rejects if push isn't available
+window.encampusSubscribeToPush = async function (vapidPublicKey) {
+  if (!("serviceWorker" in navigator) || !("PushManager" in window)) {
+    throw new Error("Web Push is not supported in this browser");
+  }
+
+  const registration = await navigator.serviceWorker.register("/service-worker.js");
+  await navigator.serviceWorker.ready;
+
+  let subscription = await registration.pushManager.getSubscription();
+  if (subscription === null) {
+    subscription = await registration.pushManager.subscribe({
+      userVisibleOnly: true,
+      applicationServerKey: urlBase64ToUint8Array(vapidPublicKey),
+    });
+  }
+
+  return JSON.stringify(subscription.toJSON());
+};
+
+// Unsubscribes the browser from Web Push, resolving to the endpoint it was subscribed with or null
+window.encampusUnsubscribeFromPush = async function () {
+  if (!("serviceWorker" in navigator)) {
+    return null;
+  }
+
+  const registration = await navigator.serviceWorker.getRegistration();
+  const subscription = registration && (await registration.pushManager.getSubscription());
+  if (!subscription) {
+    return null;
+  }
+
+  await subscription.unsubscribe();
+  return subscription.endpoint;
+};
@@ -0,0 +1,298 @@
+/**
+ * Axum handlers for uploading attachments and serving them back. Uploads are checked against the
+ * size and type limits in `attachment_functions`, images get a thumbnail, and every download is
+ * checked against the visibility of the post the file is attached to.
+ */
+use crate::data::database::attachment_functions::{
+    user_can_view_attachment, user_in_class, Attachment, DOCUMENT_TYPES, IMAGE_TYPES,
+    MAX_ATTACHMENT_BYTES,
+};
+use crate::data::storage::{SharedStorage, StorageError};
+use crate::AuthenticationSession;
+use axum::extract::{Multipart, Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use leptos::logging;
+use serde::Deserialize;
+use sqlx::postgres::PgPool;
+use tower_sessions::Session;
+
+/// Thumbnails fit in a square this many pixels wide
+const THUMBNAIL_SIZE: u32 = 320;
+
+#[derive(Deserialize)]
+pub struct UploadParams {
+    class_id: i32,
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredAttachment {
+    storage_key: String,
+    thumbnail_key: Option<String>,
+    filename: String,
+    content_type: String,
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, message.to_string()).into_response()
+}
+
+async fn logged_in_user_id(session: Session) -> Option<i32> {
+    AuthenticationSession::new(session)
+        .get_authentication()
+        .await
+        .get_user()
+        .map(|user| user.id)
+}
+
+/// The content type to store a file under, or None if it isn't allowed. Images are identified by
+/// their contents rather than the type the browser claims.
+fn checked_content_type(declared: &str, bytes: &[u8]) -> Option<String> {
+    match image::guess_format(bytes) {
+        Ok(format) if IMAGE_TYPES.contains(&format.to_mime_type()) => {
+            Some(format.to_mime_type().to_string())
+        }
+        _ if DOCUMENT_TYPES.contains(&declared) && !declared.starts_with("image/") => {
+            Some(declared.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// A PNG thumbnail of an image, or None if it can't be decoded
+fn make_thumbnail(bytes: &[u8]) -> Option<Vec<u8>> {
+    let image = image::load_from_memory(bytes).ok()?;
+    let mut thumbnail = Vec::new();
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .write_to(
+            &mut std::io::Cursor::new(&mut thumbnail),
+            image::ImageFormat::Png,
+        )
+        .ok()?;
+    Some(thumbnail)
+}
+
+/// Characters safe to put in a Content-Disposition filename
+fn header_safe_filename(filename: &str) -> String {
+    filename
+        .chars()
+        .map(|c| if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' { c } else { '_' })
+        .collect()
+}
+
+/**
+ * Accept a file uploaded as the "file" field of a multipart form to `/uploads?class_id=N`. The
+ * file stays unattached until the post or reply it was uploaded for is submitted.
+ */
+pub async fn upload_handler(
+    State(pool): State<PgPool>,
+    State(storage): State<SharedStorage>,
+    session: Session,
+    Query(params): Query<UploadParams>,
+    mut multipart: Multipart,
+) -> Response {
+    let Some(user_id) = logged_in_user_id(session).await else {
+        return error_response(StatusCode::UNAUTHORIZED, "You need to be logged in to upload files");
+    };
+    match user_in_class(&pool, params.class_id, user_id).await {
+        Ok(true) => {}
+        Ok(false) => return error_response(StatusCode::FORBIDDEN, "You are not in this class"),
+        Err(e) => {
+            logging::error!("Failed to check class membership for upload: {:?}", e);
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Unable to upload file");
+        }
+    }
+
+    let field = loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) if field.name() == Some("file") => break field,
+            Ok(Some(_)) => continue,
+            Ok(None) => return error_response(StatusCode::BAD_REQUEST, "No file was uploaded"),
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.body_text()),
+        }
+    };
+    let filename = field.file_name().unwrap_or("upload").to_string();
+    let declared_type = field.content_type().unwrap_or_default().to_string();
+    let bytes = match field.bytes().await {
+        Ok(bytes) => bytes.to_vec(),
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &e.body_text()),
+    };
+
+    let size_bytes = bytes.len() as i64;
+    if bytes.len() > MAX_ATTACHMENT_BYTES {
+        return error_response(StatusCode::PAYLOAD_TOO_LARGE, "File is too large");
+    }
+    let Some(content_type) = checked_content_type(&declared_type, &bytes) else {
+        return error_response(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Only images, PDFs, text files and zip archives can be uploaded",
+        );
+    };
+
+    let (attachment_id, storage_key): (i32, String) = match sqlx::query_as(
+        "INSERT INTO attachments (filename, content_type, size_bytes, uploaderid, classid)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING attachmentid, storage_key",
+    )
+    .bind(&filename)
+    .bind(&content_type)
+    .bind(size_bytes)
+    .bind(user_id)
+    .bind(params.class_id)
+    .fetch_one(&pool)
+    .await
+    {
+        Ok(row) => row,
+        Err(e) => {
+            logging::error!("Failed to record upload: {:?}", e);
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Unable to upload file");
+        }
+    };
+
+    let thumbnail = if IMAGE_TYPES.contains(&content_type.as_str()) {
+        let image_bytes = bytes.clone();
+        tokio::task::spawn_blocking(move || make_thumbnail(&image_bytes))
+            .await
+            .ok()
+            .flatten()
+    } else {
+        None
+    };
+
+    if let Err(e) = storage.put(&storage_key, bytes, &content_type).await {
+        logging::error!("Failed to store upload: {:?}", e);
+        let _ = sqlx::query("DELETE FROM attachments WHERE attachmentid = $1")
+            .bind(attachment_id)
+            .execute(&pool)
+            .await;
+        return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Unable to upload file");
+    }
+
+    let mut has_thumbnail = false;
+    if let Some(thumbnail) = thumbnail {
+        let thumbnail_key = format!("{}-thumbnail", storage_key);
+        match storage.put(&thumbnail_key, thumbnail, "image/png").await {
+            Ok(()) => {
+                has_thumbnail = sqlx::query("UPDATE attachments SET thumbnail_key = $1 WHERE attachmentid = $2")
+                    .bind(&thumbnail_key)
+                    .bind(attachment_id)
+                    .execute(&pool)
+                    .await
+                    .is_ok();
+            }
+            // The upload still works without a thumbnail
+            Err(e) => logging::error!("Failed to store thumbnail: {:?}", e),
+        }
+    }
+
+    Json(Attachment {
+        attachment_id,
+        size_bytes,
+        filename,
+        content_type,
+        has_thumbnail,
+        post_id: None,
+        reply_id: None,
+    })
+    .into_response()
+}
+
+async fn serve_attachment(
+    pool: PgPool,
+    storage: SharedStorage,
+    session: Session,
+    attachment_id: i32,
+    thumbnail: bool,
+) -> Response {
+    let Some(user_id) = logged_in_user_id(session).await else {
+        return error_response(StatusCode::UNAUTHORIZED, "You need to be logged in to view files");
+    };
+    match user_can_view_attachment(&pool, attachment_id, user_id).await {
+        Ok(true) => {}
+        // Files the user can't see look the same as files that don't exist
+        Ok(false) => return error_response(StatusCode::NOT_FOUND, "File not found"),
+        Err(e) => {
+            logging::error!("Failed to check attachment access: {:?}", e);
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Unable to load file");
+        }
+    }
+
+    let stored: StoredAttachment = match sqlx::query_as(
+        "SELECT storage_key, thumbnail_key, filename, content_type FROM attachments WHERE attachmentid = $1",
+    )
+    .bind(attachment_id)
+    .fetch_one(&pool)
+    .await
+    {
+        Ok(stored) => stored,
+        Err(_) => return error_response(StatusCode::NOT_FOUND, "File not found"),
+    };
+
+    let (key, content_type) = if thumbnail {
+        match stored.thumbnail_key {
+            Some(thumbnail_key) => (thumbnail_key, "image/png".to_string()),
+            None => return error_response(StatusCode::NOT_FOUND, "File has no thumbnail"),
+        }
+    } else {
+        (stored.storage_key, stored.content_type)
+    };
+
+    let bytes = match storage.get(&key).await {
+        Ok(bytes) => bytes,
+        Err(StorageError::NotFound) => return error_response(StatusCode::NOT_FOUND, "File not found"),
+        Err(e) => {
+            logging::error!("Failed to read attachment {}: {:?}", attachment_id, e);
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Unable to load file");
+        }
+    };
+
+    // Only images are shown inline; everything else is downloaded so it can't run in our origin
+    let disposition = if IMAGE_TYPES.contains(&content_type.as_str()) {
+        "inline"
+    } else {
+        "attachment"
+    };
+    (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "{}; filename=\"{}\"",
+                    disposition,
+                    header_safe_filename(&stored.filename)
+                ),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (header::CACHE_CONTROL, "private, max-age=3600".to_string()),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+/**
+ * Serve an attachment at `/uploads/:attachment_id`
+ */
+pub async fn download_handler(
+    State(pool): State<PgPool>,
+    State(storage): State<SharedStorage>,
+    session: Session,
+    Path(attachment_id): Path<i32>,
+) -> Response {
+    serve_attachment(pool, storage, session, attachment_id, false).await
+}
+
+/**
+ * Serve an image attachment's thumbnail at `/uploads/:attachment_id/thumbnail`
+ */
+pub async fn thumbnail_handler(
+    State(pool): State<PgPool>,
+    State(storage): State<SharedStorage>,
+    session: Session,
+    Path(attachment_id): Path<i32>,
+) -> Response {
+    serve_attachment(pool, storage, session, attachment_id, true).await
+}